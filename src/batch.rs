@@ -0,0 +1,268 @@
+use crate::card::*;
+use crate::cli::{parse_cards, Format};
+use crate::equity::*;
+use crate::error::{Error, Result};
+use crate::game::Game;
+
+use rayon::prelude::*;
+use std::io::{self, BufRead, Write};
+
+/// number of lines evaluated in parallel before their results are written
+const CHUNK_SIZE: usize = 256;
+
+/// A single line of batch input
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spot {
+    pub hero: Vec<Card>,
+    pub vilan: Vec<Card>,
+    pub community_cards: Vec<Card>,
+    pub dead: Vec<Card>,
+}
+
+impl Spot {
+    /// Parse `<Holding> <Holding> [COMMUNITY_CARDS] [DEAD_CARDS]`. Use `-` for an empty board if
+    /// dead cards follow, e.g. `AhAd KcKs - 7s2d`.
+    pub fn from(line: &str, game: Game) -> Result<Self> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 2 || fields.len() > 4 {
+            return Err(Error::ParseError);
+        }
+
+        let cards = |idx: usize| match fields.get(idx) {
+            Some(&"-") | None => Ok(vec![]),
+            Some(expr) => parse_cards(expr),
+        };
+        let spot = Spot {
+            hero: cards(0)?,
+            vilan: cards(1)?,
+            community_cards: cards(2)?,
+            dead: cards(3)?,
+        };
+
+        if !game.holding_size(spot.hero.len())
+            || !game.holding_size(spot.vilan.len())
+            || spot.community_cards.len() > 5
+        {
+            return Err(Error::ParseError);
+        }
+        Ok(spot)
+    }
+
+    fn known(&self) -> Vec<Card> {
+        [
+            &self.hero[..],
+            &self.vilan[..],
+            &self.community_cards[..],
+            &self.dead[..],
+        ]
+        .concat()
+    }
+}
+
+/// Evaluate a `Spot` the same way as `game_combos`
+pub fn evaluate(spot: Spot, game: Game) -> Result<Equity> {
+    let deck = game.remaining_cards(&spot.known())?;
+    Ok(game_combos(
+        game,
+        spot.hero,
+        spot.vilan,
+        spot.community_cards,
+        deck,
+    ))
+}
+
+/// Evaluate one spot per line of `input` and write one result line per input line to `output`.
+/// Empty lines and lines starting with '#' are skipped; a line which can't be read or evaluated
+/// yields an error line instead of aborting the run. Reading stops after the first error other
+/// than a line which isn't valid UTF-8.
+pub fn run<R: BufRead, W: Write>(
+    input: R,
+    output: &mut W,
+    format: Format,
+    game: Game,
+) -> Result<()> {
+    #[cfg(not(feature = "serde"))]
+    {
+        if format == Format::Json {
+            return Err(Error::MissingFeature("serde"));
+        }
+    }
+
+    // split pots don't fit into a single `Equity`, stud hands don't share a board
+    if game.is_split() || game.is_stud() {
+        return Err(Error::UnsupportedGame);
+    }
+
+    if format == Format::Csv {
+        writeln!(output, "line,{},error", crate::CSV_HEADER).map_err(Error::Io)?;
+    }
+
+    let mut chunk: Vec<(usize, Result<String>)> = Vec::with_capacity(CHUNK_SIZE);
+    let mut lines = input.lines().enumerate();
+    let mut broken = false;
+    loop {
+        let next = if broken { None } else { lines.next() };
+        let done = next.is_none();
+        if let Some((idx, line)) = next {
+            match line {
+                Ok(line) => {
+                    let trimmed = line.trim();
+                    if !trimmed.is_empty() && !trimmed.starts_with('#') {
+                        chunk.push((idx + 1, Ok(line)));
+                    }
+                }
+                Err(err) => {
+                    // the reader skips a line which isn't UTF-8, but can't go on after other errors
+                    broken = err.kind() != io::ErrorKind::InvalidData;
+                    chunk.push((idx + 1, Err(Error::Io(err))));
+                }
+            }
+            if chunk.len() < CHUNK_SIZE && !broken {
+                continue;
+            }
+        }
+
+        let results: Vec<String> = std::mem::take(&mut chunk)
+            .into_par_iter()
+            .map(|(n, line)| {
+                let result = line
+                    .and_then(|line| Spot::from(&line, game))
+                    .and_then(|spot| evaluate(spot, game));
+                format_result(n, result, format)
+            })
+            .collect();
+        for result in results {
+            writeln!(output, "{}", result).map_err(Error::Io)?;
+        }
+
+        if done {
+            return Ok(());
+        }
+    }
+}
+
+/// Format the result of input line `n`
+pub fn format_result(n: usize, result: Result<Equity>, format: Format) -> String {
+    match (result, format) {
+        (Ok(equity), Format::Text) => format!(
+            "{}: {} vs {} on {}: equity {:.2}% (win {:.2}%, tie {:.2}%)",
+            n,
+            notation(&equity.hero),
+            notation(&equity.vilan),
+            notation(&equity.community_cards),
+            equity.equity(),
+            equity.win_pct(),
+            equity.tie_pct()
+        ),
+        (Err(err), Format::Text) => format!("{}: error: {}", n, err),
+        (Ok(equity), Format::Csv) => format!("{},{},", n, crate::csv_record(&equity)),
+        (Err(err), Format::Csv) => {
+            let fields = crate::CSV_HEADER.split(',').count();
+            format!("{},{}{}", n, ",".repeat(fields), err)
+        }
+        #[cfg(feature = "serde")]
+        (Ok(equity), Format::Json) => format!(
+            "{{\"line\":{},\"equity\":{}}}",
+            n,
+            serde_json::to_string(&equity).unwrap()
+        ),
+        #[cfg(feature = "serde")]
+        (Err(err), Format::Json) => format!(
+            "{{\"line\":{},\"error\":{}}}",
+            n,
+            serde_json::to_string(&err.to_string()).unwrap()
+        ),
+        #[cfg(not(feature = "serde"))]
+        (_, Format::Json) => format!("{}: error: {}", n, Error::MissingFeature("serde")),
+    }
+}
+
+fn notation(cards: &[Card]) -> String {
+    if cards.is_empty() {
+        return String::from("-");
+    }
+    cards.iter().map(|card| card.notation()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spot_from() {
+        let spot = Spot::from("AhAd KcKs 2c7d9h 3s", Game::Holdem).unwrap();
+        assert_eq!(spot.hero, parse_cards("AhAd").unwrap());
+        assert_eq!(spot.vilan, parse_cards("KcKs").unwrap());
+        assert_eq!(spot.community_cards, parse_cards("2c7d9h").unwrap());
+        assert_eq!(spot.dead, parse_cards("3s").unwrap());
+
+        let spot = Spot::from("AhAd KcKs - 3s", Game::Holdem).unwrap();
+        assert!(spot.community_cards.is_empty());
+        assert_eq!(spot.dead.len(), 1);
+
+        assert!(Spot::from("AhAd", Game::Holdem).is_err());
+        assert!(Spot::from("AhAd Kc", Game::Holdem).is_err());
+        assert!(Spot::from("AhAd KcKs 2c7d9h3s4s5s", Game::Holdem).is_err());
+        assert!(Spot::from("AhAd KcKs", Game::Omaha).is_err());
+        assert!(Spot::from("AhAdQcQs KcKsJhJd", Game::Omaha).is_ok());
+    }
+
+    #[test]
+    fn duplicates() {
+        let spot = Spot::from("AhAd AhKs 2c7d9h", Game::Holdem).unwrap();
+        assert!(evaluate(spot, Game::Holdem).is_err());
+    }
+
+    #[test]
+    fn run_lines() {
+        let input = "AhAd KcKs 2c7d9hTs3c\n\n# comment\nAhAd AhKs\nAhAd KcKs 2c7d9hTs\n";
+        let mut output: Vec<u8> = vec![];
+        run(input.as_bytes(), &mut output, Format::Text, Game::Holdem).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("1: AhAd vs KcKs on 2c7d9hTs3c: equity 100.00%"));
+        assert_eq!(lines[1], "4: error: card is already in use");
+        assert!(lines[2].starts_with("5: "));
+    }
+
+    #[test]
+    fn run_csv() {
+        let input = "AhAd KcKs 2c7d9hTs3c\nfoo\n";
+        let mut output: Vec<u8> = vec![];
+        run(input.as_bytes(), &mut output, Format::Csv, Game::Holdem).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 3);
+        let fields = lines[0].split(',').count();
+        assert!(lines.iter().all(|line| line.split(',').count() == fields));
+        assert!(lines[1].starts_with("1,AhAd,KcKs,2c7d9hTs3c,1,1,0,0,"));
+        assert!(lines[2].ends_with(",could not parse expression"));
+    }
+
+    #[test]
+    fn unsupported_games() {
+        let mut output: Vec<u8> = vec![];
+        for game in [Game::OmahaHiLo, Game::Stud, Game::StudHiLo].iter() {
+            let result = run(&b"AhAd KcKs\n"[..], &mut output, Format::Text, *game);
+            assert!(matches!(result, Err(Error::UnsupportedGame)));
+        }
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn run_invalid_utf8() {
+        let input = b"AhAd KcKs 2c7d9hTs3c\n\xff\xfe\nAhAd KcKs 2c7d9hTs4c\n";
+        let mut output: Vec<u8> = vec![];
+        run(&input[..], &mut output, Format::Text, Game::Holdem).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("1: "));
+        assert!(lines[1].starts_with("2: error: "));
+        assert!(lines[2].starts_with("3: "));
+    }
+}
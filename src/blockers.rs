@@ -0,0 +1,304 @@
+use crate::card::*;
+use crate::classify::{classify, Draw, MadeHand};
+use crate::error::Result;
+use crate::hand_rank::Strength;
+use crate::nuts::NutRanking;
+use crate::range::Range;
+use std::cmp::Reverse;
+use std::fmt;
+
+/// The combos of a range making one kind of hand on the board
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ClassCount {
+    pub made: MadeHand,
+    /// the strongest hand of its kind possible on the board, e.g. the nut flush; never set for
+    /// `MadeHand::HighCard`
+    pub nuts: bool,
+    /// combos which don't use a board card
+    pub total: usize,
+    /// combos which use neither a board card nor one of hero's cards
+    pub live: usize,
+}
+
+/// The value and bluff combos a single hole card removes from a range
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CardBlocks {
+    pub card: Card,
+    pub value: usize,
+    pub draws: usize,
+    pub bluffs: usize,
+}
+
+/// How hero's holding changes the composition of an opponent's range on a board
+#[derive(Clone, Debug, PartialEq)]
+pub struct Blockers {
+    /// the strongest kind of hand first
+    pub classes: Vec<ClassCount>,
+    /// hero's cards in the order given
+    pub cards: Vec<CardBlocks>,
+    /// value combos without and with hero's cards removed
+    pub value: (usize, usize),
+    /// combos without a pair but with a draw to a flush or a straight, without and with hero's
+    /// cards removed
+    pub draws: (usize, usize),
+    /// bluff combos, i.e. without a pair or a draw, without and with hero's cards removed
+    pub bluffs: (usize, usize),
+}
+
+/// Count the combos of `range` by what they make on `board`, before and after removing the
+/// cards of hero's `holding`. Combos making `value` or better count as value. Combos without a
+/// pair of their own count as draws with a flush draw or a straight draw, as bluffs otherwise.
+pub fn blockers(
+    holding: &[Card],
+    board: &[Card],
+    range: &Range,
+    value: MadeHand,
+) -> Result<Blockers> {
+    // validates the holding and the board
+    classify(holding, board)?;
+
+    // the strongest hand of every kind possible on the board
+    let mut nuts: Vec<(MadeHand, Strength)> = vec![];
+    for (value, combos) in NutRanking::new(board)?.tiers.iter() {
+        for combo in combos {
+            let made = classify(combo, board)?.made;
+            if !nuts.iter().any(|(kind, _)| *kind == made) {
+                nuts.push((made, *value));
+            }
+        }
+    }
+
+    let mut classes: Vec<ClassCount> = vec![];
+    let mut cards: Vec<CardBlocks> = holding
+        .iter()
+        .map(|card| CardBlocks {
+            card: *card,
+            value: 0,
+            draws: 0,
+            bluffs: 0,
+        })
+        .collect();
+    let (mut values, mut draws, mut bluffs) = ((0, 0), (0, 0), (0, 0));
+    for combo in range.without(board).iter() {
+        let classification = classify(combo, board)?;
+        let made = classification.made;
+        let is_nuts = made > MadeHand::HighCard && nuts.contains(&(made, classification.strength));
+        let live = !holding.contains(&combo[0]) && !holding.contains(&combo[1]);
+        let is_value = made >= value;
+        let is_draw = made == MadeHand::HighCard
+            && classification.draws.iter().any(|draw| {
+                matches!(
+                    draw,
+                    Draw::FlushDraw
+                        | Draw::NutFlushDraw
+                        | Draw::OpenEnder
+                        | Draw::Gutshot
+                        | Draw::DoubleGutter
+                )
+            });
+        let is_bluff = made == MadeHand::HighCard && !is_draw;
+        let class = match classes
+            .iter_mut()
+            .find(|class| class.made == made && class.nuts == is_nuts)
+        {
+            Some(class) => class,
+            None => {
+                classes.push(ClassCount {
+                    made,
+                    nuts: is_nuts,
+                    total: 0,
+                    live: 0,
+                });
+                classes.last_mut().unwrap()
+            }
+        };
+        class.total += 1;
+        let counts = [
+            (is_value, &mut values),
+            (is_draw, &mut draws),
+            (is_bluff, &mut bluffs),
+        ];
+        for (is, count) in counts {
+            count.0 += is as usize;
+            count.1 += (is && live) as usize;
+        }
+        if live {
+            class.live += 1;
+        }
+        for blocks in cards.iter_mut().filter(|b| combo.contains(&b.card)) {
+            blocks.value += is_value as usize;
+            blocks.draws += is_draw as usize;
+            blocks.bluffs += is_bluff as usize;
+        }
+    }
+    classes.sort_by_key(|class| Reverse((class.made, class.nuts)));
+
+    Ok(Blockers {
+        classes,
+        cards,
+        value: values,
+        draws,
+        bluffs,
+    })
+}
+
+impl Blockers {
+    /// The card removing the most value combos, ties going to the one removing fewer bluffs
+    pub fn value_blocker(&self) -> &CardBlocks {
+        self.cards
+            .iter()
+            .max_by(|a, b| a.value.cmp(&b.value).then(b.bluffs.cmp(&a.bluffs)))
+            // `blockers` only accepts two card holdings
+            .unwrap()
+    }
+
+    /// The card removing the most bluff combos, ties going to the one removing fewer value combos
+    pub fn bluff_blocker(&self) -> &CardBlocks {
+        self.cards
+            .iter()
+            .max_by(|a, b| a.bluffs.cmp(&b.bluffs).then(b.value.cmp(&a.value)))
+            // `blockers` only accepts two card holdings
+            .unwrap()
+    }
+}
+
+impl fmt::Display for Blockers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for class in self.classes.iter() {
+            let name = if class.nuts {
+                format!("nut {}", class.made)
+            } else {
+                class.made.to_string()
+            };
+            writeln!(f, "{:>22}: {:>4} -> {:>4}", name, class.total, class.live)?;
+        }
+        writeln!(
+            f,
+            "{:>22}: {:>4} -> {:>4}",
+            "value", self.value.0, self.value.1
+        )?;
+        writeln!(
+            f,
+            "{:>22}: {:>4} -> {:>4}",
+            "draws", self.draws.0, self.draws.1
+        )?;
+        write!(
+            f,
+            "{:>22}: {:>4} -> {:>4}",
+            "bluffs", self.bluffs.0, self.bluffs.1
+        )?;
+        for blocks in self.cards.iter() {
+            write!(
+                f,
+                "\n{} blocks {} value, {} draw and {} bluff combos",
+                blocks.card.notation(),
+                blocks.value,
+                blocks.draws,
+                blocks.bluffs
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::parse_cards;
+
+    fn report(holding: &str) -> Blockers {
+        let range = Range::from("QQ,77,AKs,JhTh,98o").unwrap();
+        blockers(
+            &parse_cards(holding).unwrap(),
+            &parse_cards("Qh7h2c").unwrap(),
+            &range,
+            MadeHand::TwoPair,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn card_removal() {
+        let report = report("AhKc");
+        // QhQx and 7h7x use a board card
+        assert_eq!(
+            report.classes,
+            vec![
+                ClassCount {
+                    made: MadeHand::Set,
+                    nuts: true,
+                    total: 3,
+                    live: 3
+                },
+                ClassCount {
+                    made: MadeHand::Set,
+                    nuts: false,
+                    total: 3,
+                    live: 3
+                },
+                ClassCount {
+                    made: MadeHand::HighCard,
+                    nuts: false,
+                    total: 17,
+                    live: 15
+                }
+            ]
+        );
+        // AhKh and JhTh draw to a flush
+        assert_eq!(
+            (report.value, report.draws, report.bluffs),
+            ((6, 6), (2, 1), (15, 14))
+        );
+        // AhKh and AcKc
+        assert_eq!(report.cards[0].draws, 1);
+        assert_eq!((report.cards[1].value, report.cards[1].bluffs), (0, 1));
+        let text = report.to_string();
+        assert!(text.contains("nut set:    3 ->    3"));
+        assert!(text.contains("Ah blocks 0 value, 1 draw and 0 bluff combos"));
+    }
+
+    #[test]
+    fn value_blocker() {
+        let report = report("QsJh");
+        // QsQc and QsQd
+        assert_eq!(report.value, (6, 4));
+        assert_eq!(report.value_blocker().card, Card::from("Qs").unwrap());
+        assert_eq!(report.bluff_blocker().card, Card::from("Jh").unwrap());
+        assert_eq!(report.cards[1].draws, 1);
+
+        let range = Range::from("AA").unwrap();
+        let board = parse_cards("Qh7h2c").unwrap();
+        let holding = parse_cards("Qh7c").unwrap();
+        assert!(blockers(&holding, &board, &range, MadeHand::TwoPair).is_err());
+    }
+
+    #[test]
+    fn nut_flush() {
+        let range = Range::from("AhKh,QhJh,AsAd,8h6c,AcQc").unwrap();
+        let board = parse_cards("Th7h2h").unwrap();
+        let report = blockers(
+            &parse_cards("KhKs").unwrap(),
+            &board,
+            &range,
+            MadeHand::Flush,
+        )
+        .unwrap();
+        let classes: Vec<(MadeHand, bool, usize)> = report
+            .classes
+            .iter()
+            .map(|class| (class.made, class.nuts, class.live))
+            .collect();
+        assert_eq!(
+            classes,
+            vec![
+                (MadeHand::Flush, true, 0),
+                (MadeHand::Flush, false, 1),
+                (MadeHand::Overpair, true, 1),
+                (MadeHand::HighCard, false, 2),
+            ]
+        );
+        assert_eq!(report.value, (2, 1));
+        // 8h6c has a flush draw and a gutshot
+        assert_eq!((report.draws, report.bluffs), ((1, 1), (1, 1)));
+    }
+}
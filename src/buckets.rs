@@ -0,0 +1,507 @@
+use crate::card::*;
+use crate::deck::{check_duplicates, remaining_deck};
+use crate::error::{Error, Result};
+use crate::game::Game;
+use crate::hand_rank::Strength;
+use crate::range::{Combo, Range};
+
+use itertools::Itertools;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::io::Write;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A distribution of equities over equally wide bins between 0 and 1
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Histogram {
+    pub bins: Vec<f64>,
+}
+
+impl Histogram {
+    /// # Panics
+    ///
+    /// If `bins` is 0, there's no bin to count an equity in
+    pub fn new(bins: usize) -> Self {
+        assert!(bins > 0, "a histogram needs at least one bin");
+        Histogram {
+            bins: vec![0.0; bins],
+        }
+    }
+
+    /// Count an equity between 0 and 1, an equity of 1 goes into the last bin
+    pub fn add(&mut self, equity: f64) {
+        let n = self.bins.len();
+        let bin = ((equity * n as f64) as usize).min(n - 1);
+        self.bins[bin] += 1.0;
+    }
+
+    /// Scale the bins to sum up to 1
+    pub fn normalize(&mut self) {
+        let total: f64 = self.bins.iter().sum();
+        if total > 0.0 {
+            for bin in self.bins.iter_mut() {
+                *bin /= total;
+            }
+        }
+    }
+
+    /// The mean equity, taking the center of every bin
+    pub fn mean(&self) -> f64 {
+        let n = self.bins.len() as f64;
+        let total: f64 = self.bins.iter().sum();
+        if total == 0.0 {
+            return 0.0;
+        }
+        self.bins
+            .iter()
+            .enumerate()
+            .map(|(i, count)| count * (i as f64 + 0.5) / n)
+            .sum::<f64>()
+            / total
+    }
+
+    /// The earth mover's distance to a normalized histogram of the same size: the equity mass
+    /// which has to move times how far it moves
+    pub fn emd(&self, other: &Histogram) -> f64 {
+        let n = self.bins.len() as f64;
+        let (mut carried, mut distance) = (0.0, 0.0);
+        for (a, b) in self.bins.iter().zip(other.bins.iter()) {
+            carried += a - b;
+            distance += carried.abs();
+        }
+        distance / n
+    }
+
+    /// Write the bins as csv records of the bin's lower and upper equity and its frequency
+    pub fn write_csv<W: Write>(&self, output: &mut W) -> Result<()> {
+        let n = self.bins.len() as f64;
+        writeln!(output, "low,high,frequency").map_err(Error::Io)?;
+        for (i, frequency) in self.bins.iter().enumerate() {
+            writeln!(
+                output,
+                "{:.4},{:.4},{:.6}",
+                i as f64 / n,
+                (i + 1) as f64 / n,
+                frequency
+            )
+            .map_err(Error::Io)?;
+        }
+        Ok(())
+    }
+}
+
+fn validate(holding: &[Card], board: &[Card], bins: usize) -> Result<Vec<Card>> {
+    if holding.len() != 2 || board.len() == 1 || board.len() == 2 || board.len() > 5 || bins == 0 {
+        return Err(Error::ParseError);
+    }
+    let known = [holding, board].concat();
+    check_duplicates(&known)?;
+    Ok(remaining_deck(&known))
+}
+
+/// The normalized distribution of `holding`'s river hand strength against `range` (every combo
+/// if `None`) over every runout of a flop, turn or river
+pub fn equity_distribution(
+    holding: &[Card],
+    board: &[Card],
+    range: Option<&Range>,
+    bins: usize,
+) -> Result<Histogram> {
+    let deck = validate(holding, board, bins)?;
+    if board.is_empty() {
+        return Err(Error::ParseError);
+    }
+    let hand = [[holding[0], holding[1]]];
+    let mut histogram = Histogram::new(bins);
+    let mut runout = board.to_vec();
+    for cards in deck.iter().combinations(5 - board.len()) {
+        runout.truncate(board.len());
+        runout.extend(cards.into_iter().copied());
+        if let Some(strength) = runout_strengths(&hand, &runout, range)[0] {
+            histogram.add(strength);
+        }
+    }
+    histogram.normalize();
+    Ok(histogram)
+}
+
+/// The river hand strength of every one of `hands` against `range` (every combo if `None`),
+/// ties counting half; `None` for a hand using a card of the `runout` or leaving no combo of
+/// `range`. The opponent's combos are evaluated once for all hands.
+fn runout_strengths(hands: &[Combo], runout: &[Card], range: Option<&Range>) -> Vec<Option<f64>> {
+    let game = Game::Holdem;
+    let opponents: Vec<Combo> = match range {
+        Some(range) => range.without(runout),
+        None => remaining_deck(runout)
+            .iter()
+            .tuple_combinations()
+            .map(|(a, b)| [*a, *b])
+            .collect(),
+    };
+    let values: Vec<Strength> = opponents
+        .iter()
+        .map(|combo| game.strength(combo, runout))
+        .collect();
+    let mut sorted = values.clone();
+    sorted.sort_unstable();
+    let mut by_card: HashMap<Card, Vec<usize>> = HashMap::new();
+    for (i, combo) in opponents.iter().enumerate() {
+        for card in combo.iter() {
+            by_card.entry(*card).or_default().push(i);
+        }
+    }
+
+    hands
+        .iter()
+        .map(|hand| {
+            if hand.iter().any(|card| runout.contains(card)) {
+                return None;
+            }
+            let hero = game.strength(hand, runout);
+            let mut below = sorted.partition_point(|value| *value < hero);
+            let mut tied = sorted.partition_point(|value| *value <= hero) - below;
+            let mut total = sorted.len();
+            // the combos sharing a card with the hand can't be dealt
+            let blocked = hand
+                .iter()
+                .flat_map(|card| by_card.get(card).into_iter().flatten())
+                .unique();
+            for i in blocked {
+                total -= 1;
+                if values[*i] < hero {
+                    below -= 1;
+                } else if values[*i] == hero {
+                    tied -= 1;
+                }
+            }
+            if total == 0 {
+                return None;
+            }
+            Some((below as f64 + tied as f64 / 2.0) / total as f64)
+        })
+        .collect()
+}
+
+/// Like `equity_distribution`, but from `samples` random runouts; works preflop, too. Runouts
+/// which leave no combo of `range` are skipped.
+pub fn sample_equity_distribution<R: Rng>(
+    holding: &[Card],
+    board: &[Card],
+    range: Option<&Range>,
+    bins: usize,
+    samples: usize,
+    rng: &mut R,
+) -> Result<Histogram> {
+    let deck = validate(holding, board, bins)?;
+    let hand = [[holding[0], holding[1]]];
+    let mut histogram = Histogram::new(bins);
+    let mut runout = board.to_vec();
+    for _ in 0..samples {
+        runout.truncate(board.len());
+        runout.extend(deck.choose_multiple(rng, 5 - board.len()).copied());
+        if let Some(strength) = runout_strengths(&hand, &runout, range)[0] {
+            histogram.add(strength);
+        }
+    }
+    histogram.normalize();
+    Ok(histogram)
+}
+
+/// Holdings grouped into buckets of similar equity distributions
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Buckets {
+    pub hands: Vec<Combo>,
+    pub histograms: Vec<Histogram>,
+    /// the bucket of every hand
+    pub assignments: Vec<usize>,
+    /// the mean histogram of every bucket, buckets ordered by their mean equity
+    pub centers: Vec<Histogram>,
+}
+
+impl Buckets {
+    /// Cluster the `hands` by their normalized `histograms` into at most `k` buckets, using
+    /// k-means with the earth mover's distance and k-means++ seeding
+    pub fn cluster<R: Rng>(
+        hands: Vec<Combo>,
+        histograms: Vec<Histogram>,
+        k: usize,
+        iterations: usize,
+        rng: &mut R,
+    ) -> Self {
+        let k = k.min(histograms.len());
+        if k == 0 {
+            return Buckets::default();
+        }
+
+        let mut centers = vec![histograms.choose(rng).unwrap().clone()];
+        while centers.len() < k {
+            let distances: Vec<f64> = histograms
+                .iter()
+                .map(|h| nearest(&centers, h).1.powi(2))
+                .collect();
+            let total: f64 = distances.iter().sum();
+            if total == 0.0 {
+                // fewer distinct histograms than buckets
+                break;
+            }
+            let mut pick = rng.gen_range(0.0, total);
+            let mut chosen = histograms.len() - 1;
+            for (i, distance) in distances.iter().enumerate() {
+                if pick < *distance {
+                    chosen = i;
+                    break;
+                }
+                pick -= distance;
+            }
+            centers.push(histograms[chosen].clone());
+        }
+
+        let mut assignments = vec![usize::MAX; histograms.len()];
+        for _ in 0..iterations.max(1) {
+            let mut changed = false;
+            for (assignment, h) in assignments.iter_mut().zip(histograms.iter()) {
+                let bucket = nearest(&centers, h).0;
+                changed |= *assignment != bucket;
+                *assignment = bucket;
+            }
+            if !changed {
+                break;
+            }
+            for (bucket, center) in centers.iter_mut().enumerate() {
+                let members: Vec<&Histogram> = histograms
+                    .iter()
+                    .zip(assignments.iter())
+                    .filter(|(_, a)| **a == bucket)
+                    .map(|(h, _)| h)
+                    .collect();
+                // an empty bucket keeps its center
+                if members.is_empty() {
+                    continue;
+                }
+                for (i, bin) in center.bins.iter_mut().enumerate() {
+                    *bin = members.iter().map(|h| h.bins[i]).sum::<f64>() / members.len() as f64;
+                }
+            }
+        }
+
+        // number the buckets from the weakest to the strongest
+        let mut order: Vec<usize> = (0..centers.len()).collect();
+        order.sort_by(|a, b| centers[*a].mean().partial_cmp(&centers[*b].mean()).unwrap());
+        let mut renumber = vec![0; centers.len()];
+        for (new, old) in order.iter().enumerate() {
+            renumber[*old] = new;
+        }
+        Buckets {
+            hands,
+            histograms,
+            assignments: assignments.iter().map(|a| renumber[*a]).collect(),
+            centers: order.iter().map(|i| centers[*i].clone()).collect(),
+        }
+    }
+
+    /// The bucket of `hand` in either card order
+    pub fn bucket(&self, hand: &[Card]) -> Option<usize> {
+        self.hands
+            .iter()
+            .position(|c| hand.len() == 2 && c.contains(&hand[0]) && c.contains(&hand[1]))
+            .map(|i| self.assignments[i])
+    }
+
+    /// Write a csv record of every hand with its bucket, mean equity and histogram
+    pub fn write_csv<W: Write>(&self, output: &mut W) -> Result<()> {
+        let bins = self.centers.first().map_or(0, |c| c.bins.len());
+        let header: Vec<String> = (0..bins).map(|i| format!("bin_{}", i)).collect();
+        writeln!(output, "hand,bucket,mean,{}", header.join(",")).map_err(Error::Io)?;
+        for ((hand, bucket), histogram) in self
+            .hands
+            .iter()
+            .zip(self.assignments.iter())
+            .zip(self.histograms.iter())
+        {
+            let bins: Vec<String> = histogram.bins.iter().map(|b| format!("{:.6}", b)).collect();
+            writeln!(
+                output,
+                "{}{},{},{:.4},{}",
+                hand[0].notation(),
+                hand[1].notation(),
+                bucket,
+                histogram.mean(),
+                bins.join(",")
+            )
+            .map_err(Error::Io)?;
+        }
+        Ok(())
+    }
+}
+
+/// The index of and the distance to the closest center
+fn nearest(centers: &[Histogram], histogram: &Histogram) -> (usize, f64) {
+    centers
+        .iter()
+        .map(|center| histogram.emd(center))
+        .enumerate()
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+        .unwrap()
+}
+
+/// Bucket every holding possible on a street: one per starting hand class preflop, every
+/// combo without a board card after. All hands share the same `samples` random runouts, a single
+/// one on the river, and each histogram counts the runouts which don't use a card of its hand.
+/// The runouts are evaluated in parallel.
+pub fn bucket_hands<R: Rng>(
+    board: &[Card],
+    range: Option<&Range>,
+    bins: usize,
+    k: usize,
+    samples: usize,
+    rng: &mut R,
+) -> Result<Buckets> {
+    if board.len() == 1 || board.len() == 2 || board.len() > 5 || bins == 0 {
+        return Err(Error::ParseError);
+    }
+    check_duplicates(board)?;
+    let hands: Vec<Combo> = if board.is_empty() {
+        crate::range::HandClass::all()
+            .iter()
+            .map(|class| class.combos()[0])
+            .collect()
+    } else {
+        remaining_deck(board)
+            .iter()
+            .tuple_combinations()
+            .map(|(a, b)| if a > b { [*a, *b] } else { [*b, *a] })
+            .collect()
+    };
+    let deck = remaining_deck(board);
+    let runouts: Vec<Vec<Card>> = if board.len() == 5 {
+        vec![board.to_vec()]
+    } else {
+        (0..samples)
+            .map(|_| {
+                let cards = deck.choose_multiple(rng, 5 - board.len());
+                board.iter().chain(cards).copied().collect()
+            })
+            .collect()
+    };
+    let strengths: Vec<Vec<Option<f64>>> = runouts
+        .par_iter()
+        .map(|runout| runout_strengths(&hands, runout, range))
+        .collect();
+
+    let histograms = (0..hands.len())
+        .map(|i| {
+            let mut histogram = Histogram::new(bins);
+            for strength in strengths.iter().filter_map(|s| s[i]) {
+                histogram.add(strength);
+            }
+            histogram.normalize();
+            histogram
+        })
+        .collect();
+    Ok(Buckets::cluster(hands, histograms, k, 100, rng))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::parse_cards;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn histogram(bins: &[f64]) -> Histogram {
+        Histogram {
+            bins: bins.to_vec(),
+        }
+    }
+
+    #[test]
+    fn histograms() {
+        let mut h = Histogram::new(4);
+        for equity in [0.0, 0.3, 0.3, 1.0].iter() {
+            h.add(*equity);
+        }
+        h.normalize();
+        assert_eq!(h.bins, vec![0.25, 0.5, 0.0, 0.25]);
+        assert!((h.mean() - 0.4375).abs() < 1e-9);
+
+        let low = histogram(&[1.0, 0.0, 0.0, 0.0]);
+        let high = histogram(&[0.0, 0.0, 0.0, 1.0]);
+        // all the mass moves three bins of a quarter
+        assert!((low.emd(&high) - 0.75).abs() < 1e-9);
+        assert!((low.emd(&low)).abs() < 1e-9);
+        assert!((h.emd(&high) - high.emd(&h)).abs() < 1e-9);
+
+        let mut csv = vec![];
+        low.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.starts_with("low,high,frequency\n0.0000,0.2500,1.000000\n"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn no_bins() {
+        Histogram::new(0);
+    }
+
+    #[test]
+    fn distribution() {
+        let range = Range::from("AdAc").unwrap();
+        // a flush draw on the turn hits 9 of 44 rivers
+        let (holding, board) = (
+            parse_cards("8h7h").unwrap(),
+            parse_cards("KhTh2c3d").unwrap(),
+        );
+        let h = equity_distribution(&holding, &board, Some(&range), 10).unwrap();
+        assert!((h.bins[9] - 9.0 / 44.0).abs() < 1e-9);
+        assert!((h.bins[0] - 35.0 / 44.0).abs() < 1e-9);
+
+        let mut rng = StdRng::seed_from_u64(48);
+        let sampled =
+            sample_equity_distribution(&holding, &board, Some(&range), 10, 2000, &mut rng).unwrap();
+        assert!(sampled.emd(&h) < 0.02);
+        assert!(equity_distribution(&holding, &[], None, 10).is_err());
+        assert!(equity_distribution(&holding, &board, None, 0).is_err());
+    }
+
+    #[test]
+    fn clustering() {
+        let hands = parse_cards("AhAdKhKd2c3c7s2d").unwrap();
+        let hands: Vec<Combo> = hands.chunks(2).map(|c| [c[0], c[1]]).collect();
+        let histograms = vec![
+            histogram(&[0.0, 0.0, 0.1, 0.9]),
+            histogram(&[0.0, 0.0, 0.2, 0.8]),
+            histogram(&[0.9, 0.1, 0.0, 0.0]),
+            histogram(&[0.8, 0.2, 0.0, 0.0]),
+        ];
+        let mut rng = StdRng::seed_from_u64(48);
+        let buckets = Buckets::cluster(hands, histograms, 2, 10, &mut rng);
+        assert_eq!(buckets.assignments, vec![1, 1, 0, 0]);
+        assert_eq!(buckets.bucket(&parse_cards("2d7s").unwrap()), Some(0));
+        assert!(buckets.centers[0].mean() < buckets.centers[1].mean());
+
+        let mut csv = vec![];
+        buckets.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().count(), 5);
+        assert!(csv.lines().nth(1).unwrap().starts_with("AhAd,1,"));
+    }
+
+    #[test]
+    fn river_buckets() {
+        // on the river every histogram is a single equity
+        let board = parse_cards("AhKd8c7s3h").unwrap();
+        let range = Range::from("KK,88,AQ").unwrap();
+        let mut rng = StdRng::seed_from_u64(48);
+        let buckets = bucket_hands(&board, Some(&range), 10, 3, 1, &mut rng).unwrap();
+        assert_eq!(buckets.hands.len(), 47 * 46 / 2);
+        let sets = buckets.bucket(&parse_cards("AsAd").unwrap()).unwrap();
+        let air = buckets.bucket(&parse_cards("5c4c").unwrap()).unwrap();
+        assert_eq!((sets, air), (2, 0));
+    }
+}
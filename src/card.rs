@@ -2,8 +2,12 @@ use crate::error::{Error, Result};
 use crate::Beats;
 use std::fmt;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 /// Suit
 #[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Suit {
     Clubs,
     Spades,
@@ -13,6 +17,7 @@ pub enum Suit {
 
 /// Rank
 #[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Rank {
     Two,
     Three,
@@ -31,6 +36,7 @@ pub enum Rank {
 
 /// Card
 #[derive(Copy, Clone, Eq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Card {
     pub rank: Rank,
     pub suit: Suit,
@@ -42,35 +48,70 @@ impl Card {
     }
 
     pub fn from(expr: &str) -> Result<Self> {
-        if expr.len() != 2 {
+        if expr.len() != 2 || !expr.is_char_boundary(1) {
             return Err(Error::ParseError);
         }
+        let rank = Rank::parse(&expr[..1])?;
+        let suit = Suit::parse(&expr[1..2])?;
+        Ok(Card { rank, suit })
+    }
 
-        let rank = match &expr[..1] {
-            "A" => Rank::Ace,
-            "K" => Rank::King,
-            "Q" => Rank::Queen,
-            "J" => Rank::Jack,
-            "T" => Rank::Ten,
-            "9" => Rank::Nine,
-            "8" => Rank::Eight,
-            "7" => Rank::Seven,
-            "6" => Rank::Six,
-            "5" => Rank::Five,
-            "4" => Rank::Four,
-            "3" => Rank::Three,
-            "2" => Rank::Two,
-            _ => return Err(Error::ParseError),
+    /// The inverse of `Card::from`, e.g. "Ah" for the Ace of Hearts
+    pub fn notation(&self) -> String {
+        let suit = match self.suit {
+            Suit::Clubs => "c",
+            Suit::Spades => "s",
+            Suit::Hearts => "h",
+            Suit::Diamonds => "d",
         };
+        format!("{}{}", self.rank, suit)
+    }
+}
 
-        let suit = match &expr[1..2] {
-            "c" => Suit::Clubs,
-            "s" => Suit::Spades,
-            "h" => Suit::Hearts,
-            "d" => Suit::Diamonds,
-            _ => return Err(Error::ParseError),
-        };
-        Ok(Card { rank, suit })
+impl Rank {
+    /// Parse a single rank character, e.g. "A" or "7"
+    pub fn parse(expr: &str) -> Result<Self> {
+        match expr {
+            "A" => Ok(Rank::Ace),
+            "K" => Ok(Rank::King),
+            "Q" => Ok(Rank::Queen),
+            "J" => Ok(Rank::Jack),
+            "T" => Ok(Rank::Ten),
+            "9" => Ok(Rank::Nine),
+            "8" => Ok(Rank::Eight),
+            "7" => Ok(Rank::Seven),
+            "6" => Ok(Rank::Six),
+            "5" => Ok(Rank::Five),
+            "4" => Ok(Rank::Four),
+            "3" => Ok(Rank::Three),
+            "2" => Ok(Rank::Two),
+            _ => Err(Error::ParseError),
+        }
+    }
+}
+
+impl Suit {
+    /// Parse a single suit character, e.g. "h" or "c"
+    pub fn parse(expr: &str) -> Result<Self> {
+        match expr {
+            "c" => Ok(Suit::Clubs),
+            "s" => Ok(Suit::Spades),
+            "h" => Ok(Suit::Hearts),
+            "d" => Ok(Suit::Diamonds),
+            _ => Err(Error::ParseError),
+        }
+    }
+
+    /// The rank of the suit where suits have to be ordered, e.g. to determine the bring-in in
+    /// Stud: Clubs (0) < Diamonds (1) < Hearts (2) < Spades (3). The derived `Ord` only reflects
+    /// the declaration order and isn't meaningful in play.
+    pub fn order(&self) -> usize {
+        match self {
+            Suit::Clubs => 0,
+            Suit::Diamonds => 1,
+            Suit::Hearts => 2,
+            Suit::Spades => 3,
+        }
     }
 }
 
@@ -127,10 +168,10 @@ impl Beats for Card {
 impl fmt::Display for Suit {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Suit::Clubs => write!(f, "{}", "\u{2663}"),
-            Suit::Spades => write!(f, "{}", "\u{2660}"),
-            Suit::Hearts => write!(f, "{}", "\u{2764}"),
-            Suit::Diamonds => write!(f, "{}", "\u{2666}"),
+            Suit::Clubs => write!(f, "\u{2663}"),
+            Suit::Spades => write!(f, "\u{2660}"),
+            Suit::Hearts => write!(f, "\u{2764}"),
+            Suit::Diamonds => write!(f, "\u{2666}"),
         }
     }
 }
@@ -138,19 +179,19 @@ impl fmt::Display for Suit {
 impl fmt::Display for Rank {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Rank::Ace => write!(f, "{}", "A"),
-            Rank::King => write!(f, "{}", "K"),
-            Rank::Queen => write!(f, "{}", "Q"),
-            Rank::Jack => write!(f, "{}", "J"),
-            Rank::Ten => write!(f, "{}", "T"),
-            Rank::Nine => write!(f, "{}", "9"),
-            Rank::Eight => write!(f, "{}", "8"),
-            Rank::Seven => write!(f, "{}", "7"),
-            Rank::Six => write!(f, "{}", "6"),
-            Rank::Five => write!(f, "{}", "5"),
-            Rank::Four => write!(f, "{}", "4"),
-            Rank::Three => write!(f, "{}", "3"),
-            Rank::Two => write!(f, "{}", "2"),
+            Rank::Ace => write!(f, "A"),
+            Rank::King => write!(f, "K"),
+            Rank::Queen => write!(f, "Q"),
+            Rank::Jack => write!(f, "J"),
+            Rank::Ten => write!(f, "T"),
+            Rank::Nine => write!(f, "9"),
+            Rank::Eight => write!(f, "8"),
+            Rank::Seven => write!(f, "7"),
+            Rank::Six => write!(f, "6"),
+            Rank::Five => write!(f, "5"),
+            Rank::Four => write!(f, "4"),
+            Rank::Three => write!(f, "3"),
+            Rank::Two => write!(f, "2"),
         }
     }
 }
@@ -179,6 +220,13 @@ mod tests {
         assert_eq!(Suit::from(3), Suit::Diamonds);
     }
 
+    #[test]
+    fn suit_order() {
+        assert!(Suit::Clubs.order() < Suit::Diamonds.order());
+        assert!(Suit::Diamonds.order() < Suit::Hearts.order());
+        assert!(Suit::Hearts.order() < Suit::Spades.order());
+    }
+
     #[test]
     fn rank_from() {
         assert_eq!(Rank::from(0), Rank::Two);
@@ -413,6 +461,15 @@ mod tests {
         assert!(Card::from("As+").is_err()); // Card cannot be a hand range
         assert!(Card::from("23s").is_err()); // Card cannot be suited connectors
         assert!(Card::from("1s").is_err()); // Card cannot be a hand range
+        assert!(Card::from("\u{e9}").is_err()); // two bytes but a single char
+    }
+
+    #[test]
+    fn notation() {
+        assert_eq!(Card::from("Ah").unwrap().notation(), "Ah");
+        assert_eq!(Card::from("Tc").unwrap().notation(), "Tc");
+        assert_eq!(Card::from("2d").unwrap().notation(), "2d");
+        assert_eq!(Card::from("9s").unwrap().notation(), "9s");
     }
 
     #[test]
@@ -0,0 +1,417 @@
+pub mod kuhn;
+pub mod leduc;
+
+use rand::Rng;
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+/// A two player zero-sum game in extensive form with perfect recall.
+///
+/// Player 0 and player 1 alternate with chance nodes in between. Utilities are player 0's, player
+/// 1 gets the negation.
+pub trait ExtensiveGame {
+    type State: Clone;
+    type Action: Copy + Debug + PartialEq;
+
+    fn root(&self) -> Self::State;
+
+    fn is_terminal(&self, state: &Self::State) -> bool;
+
+    /// player 0's payoff at a terminal `state`
+    fn utility(&self, state: &Self::State) -> f64;
+
+    fn is_chance(&self, state: &Self::State) -> bool;
+
+    /// every outcome of a chance node and its probability
+    fn chance_outcomes(&self, state: &Self::State) -> Vec<(Self::Action, f64)>;
+
+    /// the player to act, 0 or 1
+    fn player(&self, state: &Self::State) -> usize;
+
+    /// the legal actions of the player to act, always in the same order within an information set
+    fn actions(&self, state: &Self::State) -> Vec<Self::Action>;
+
+    fn apply(&self, state: &Self::State, action: Self::Action) -> Self::State;
+
+    /// identifies what the player to act knows, equal for states they can't tell apart
+    fn info_set(&self, state: &Self::State) -> String;
+}
+
+/// Action probabilities by information set, in the order of `ExtensiveGame::actions`; information
+/// sets which are missing are played uniformly at random
+pub type Strategy = HashMap<String, Vec<f64>>;
+
+/// Which flavor of counterfactual regret minimization `Cfr` runs
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Variant {
+    /// both players update simultaneously, the average strategy weights every iteration equally
+    Vanilla,
+    /// negative regrets are reset to zero, players update alternately and the average strategy
+    /// weights iteration t by t
+    Plus,
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    regret: Vec<f64>,
+    strategy_sum: Vec<f64>,
+    /// the strategy of the running iteration, fixed while the tree is traversed
+    current: Vec<f64>,
+}
+
+impl Node {
+    fn new(actions: usize) -> Self {
+        Node {
+            regret: vec![0.0; actions],
+            strategy_sum: vec![0.0; actions],
+            current: vec![1.0 / actions as f64; actions],
+        }
+    }
+
+    /// regret matching: play actions in proportion to their positive regret
+    fn strategy(&self) -> Vec<f64> {
+        let positive: f64 = self.regret.iter().map(|r| r.max(0.0)).sum();
+        let n = self.regret.len();
+        if positive > 0.0 {
+            self.regret.iter().map(|r| r.max(0.0) / positive).collect()
+        } else {
+            vec![1.0 / n as f64; n]
+        }
+    }
+
+    fn average(&self) -> Vec<f64> {
+        let total: f64 = self.strategy_sum.iter().sum();
+        let n = self.strategy_sum.len();
+        if total > 0.0 {
+            self.strategy_sum.iter().map(|s| s / total).collect()
+        } else {
+            vec![1.0 / n as f64; n]
+        }
+    }
+}
+
+/// Counterfactual regret minimization of an `ExtensiveGame`: the average strategy of the
+/// iterations converges to a Nash equilibrium
+pub struct Cfr<'a, G: ExtensiveGame> {
+    game: &'a G,
+    variant: Variant,
+    nodes: HashMap<String, Node>,
+    /// number of completed iterations
+    pub iterations: usize,
+}
+
+impl<'a, G: ExtensiveGame> Cfr<'a, G> {
+    pub fn new(game: &'a G, variant: Variant) -> Self {
+        Cfr {
+            game,
+            variant,
+            nodes: HashMap::new(),
+            iterations: 0,
+        }
+    }
+
+    /// Run `iterations` full traversals of the game tree
+    pub fn iterate(&mut self, iterations: usize) {
+        let root = self.game.root();
+        for _ in 0..iterations {
+            self.iterations += 1;
+            match self.variant {
+                Variant::Vanilla => {
+                    self.traverse(&root, [1.0, 1.0], None);
+                    self.update();
+                }
+                Variant::Plus => {
+                    for player in 0..2 {
+                        self.traverse(&root, [1.0, 1.0], Some(player));
+                        self.update();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Apply the regrets of a traversal to the strategies of the next one
+    fn update(&mut self) {
+        let plus = self.variant == Variant::Plus;
+        for node in self.nodes.values_mut() {
+            if plus {
+                for regret in node.regret.iter_mut() {
+                    *regret = regret.max(0.0);
+                }
+            }
+            node.current = node.strategy();
+        }
+    }
+
+    /// Run `iterations` of external sampling Monte Carlo CFR: for each player, chance and the
+    /// opponent's actions are sampled while all of the player's own actions are explored
+    pub fn iterate_sampled<R: Rng>(&mut self, iterations: usize, rng: &mut R) {
+        let root = self.game.root();
+        for _ in 0..iterations {
+            self.iterations += 1;
+            for player in 0..2 {
+                self.sample(&root, player, rng);
+            }
+        }
+    }
+
+    /// The average strategy of all iterations so far
+    pub fn strategy(&self) -> Strategy {
+        self.nodes
+            .iter()
+            .map(|(key, node)| (key.clone(), node.average()))
+            .collect()
+    }
+
+    fn node(&mut self, key: &str, actions: usize) -> &mut Node {
+        self.nodes
+            .entry(String::from(key))
+            .or_insert_with(|| Node::new(actions))
+    }
+
+    /// Returns player 0's expected utility of `state`. `reach` holds each player's own
+    /// contribution to the probability of reaching `state`, chance is folded into both.
+    fn traverse(&mut self, state: &G::State, reach: [f64; 2], update: Option<usize>) -> f64 {
+        let game = self.game;
+        if game.is_terminal(state) {
+            return game.utility(state);
+        }
+        if game.is_chance(state) {
+            return game
+                .chance_outcomes(state)
+                .into_iter()
+                .map(|(action, p)| {
+                    let next = game.apply(state, action);
+                    p * self.traverse(&next, [reach[0] * p, reach[1] * p], update)
+                })
+                .sum();
+        }
+
+        let player = game.player(state);
+        let actions = game.actions(state);
+        let key = game.info_set(state);
+        let strategy = self.node(&key, actions.len()).current.clone();
+        let mut values = vec![0.0; actions.len()];
+        let mut value = 0.0;
+        for (i, action) in actions.iter().enumerate() {
+            let mut next_reach = reach;
+            next_reach[player] *= strategy[i];
+            values[i] = self.traverse(&game.apply(state, *action), next_reach, update);
+            value += strategy[i] * values[i];
+        }
+
+        if update.is_none_or(|p| p == player) {
+            let sign = if player == 0 { 1.0 } else { -1.0 };
+            let weight = match self.variant {
+                Variant::Vanilla => 1.0,
+                Variant::Plus => self.iterations as f64,
+            };
+            let node = self.node(&key, actions.len());
+            for i in 0..actions.len() {
+                node.regret[i] += reach[1 - player] * sign * (values[i] - value);
+                node.strategy_sum[i] += weight * reach[player] * strategy[i];
+            }
+        }
+        value
+    }
+
+    /// Returns `player`'s sampled utility of `state`
+    fn sample<R: Rng>(&mut self, state: &G::State, player: usize, rng: &mut R) -> f64 {
+        let game = self.game;
+        if game.is_terminal(state) {
+            let utility = game.utility(state);
+            return if player == 0 { utility } else { -utility };
+        }
+        if game.is_chance(state) {
+            let action = pick(&game.chance_outcomes(state), rng);
+            return self.sample(&game.apply(state, action), player, rng);
+        }
+
+        let actions = game.actions(state);
+        let key = game.info_set(state);
+        let strategy = self.node(&key, actions.len()).strategy();
+        if game.player(state) != player {
+            let node = self.node(&key, actions.len());
+            for (sum, p) in node.strategy_sum.iter_mut().zip(strategy.iter()) {
+                *sum += p;
+            }
+            let outcomes: Vec<(G::Action, f64)> = actions
+                .iter()
+                .copied()
+                .zip(strategy.iter().copied())
+                .collect();
+            let action = pick(&outcomes, rng);
+            return self.sample(&game.apply(state, action), player, rng);
+        }
+
+        let values: Vec<f64> = actions
+            .iter()
+            .map(|action| self.sample(&game.apply(state, *action), player, rng))
+            .collect();
+        let value: f64 = values.iter().zip(strategy.iter()).map(|(v, p)| v * p).sum();
+        let plus = self.variant == Variant::Plus;
+        let node = self.node(&key, actions.len());
+        for (regret, v) in node.regret.iter_mut().zip(values.iter()) {
+            *regret += v - value;
+            if plus {
+                *regret = regret.max(0.0);
+            }
+        }
+        value
+    }
+}
+
+/// Draw one of the `outcomes` according to its probability
+fn pick<A: Copy, R: Rng>(outcomes: &[(A, f64)], rng: &mut R) -> A {
+    let mut x: f64 = rng.gen();
+    for (action, p) in outcomes {
+        if x < *p {
+            return *action;
+        }
+        x -= p;
+    }
+    outcomes[outcomes.len() - 1].0
+}
+
+fn probabilities(strategy: &Strategy, key: &str, actions: usize) -> Vec<f64> {
+    match strategy.get(key) {
+        Some(probabilities) if probabilities.len() == actions => probabilities.clone(),
+        _ => vec![1.0 / actions as f64; actions],
+    }
+}
+
+/// Player 0's expected utility if both players follow `strategy`
+pub fn expected_value<G: ExtensiveGame>(game: &G, strategy: &Strategy) -> f64 {
+    fn value<G: ExtensiveGame>(game: &G, strategy: &Strategy, state: &G::State) -> f64 {
+        if game.is_terminal(state) {
+            return game.utility(state);
+        }
+        let outcomes = if game.is_chance(state) {
+            game.chance_outcomes(state)
+        } else {
+            let actions = game.actions(state);
+            let p = probabilities(strategy, &game.info_set(state), actions.len());
+            actions.into_iter().zip(p).collect()
+        };
+        outcomes
+            .into_iter()
+            .map(|(action, p)| p * value(game, strategy, &game.apply(state, action)))
+            .sum()
+    }
+    value(game, strategy, &game.root())
+}
+
+/// The value `player` achieves with a best response against the opponent playing `strategy`
+pub fn best_response_value<G: ExtensiveGame>(game: &G, strategy: &Strategy, player: usize) -> f64 {
+    let mut response = BestResponse {
+        game,
+        strategy,
+        player,
+        states: HashMap::new(),
+        choices: HashMap::new(),
+    };
+    response.collect(&game.root(), 1.0);
+    let value = response.value(&game.root());
+    if player == 0 {
+        value
+    } else {
+        -value
+    }
+}
+
+/// How much two best responses win against `strategy` on average; 0 at a Nash equilibrium
+pub fn exploitability<G: ExtensiveGame>(game: &G, strategy: &Strategy) -> f64 {
+    (best_response_value(game, strategy, 0) + best_response_value(game, strategy, 1)) / 2.0
+}
+
+struct BestResponse<'a, G: ExtensiveGame> {
+    game: &'a G,
+    strategy: &'a Strategy,
+    player: usize,
+    /// the states of each of the player's information sets and the chance of the opponent and
+    /// chance reaching them
+    states: HashMap<String, Vec<(G::State, f64)>>,
+    /// the best action in each of the player's information sets
+    choices: HashMap<String, usize>,
+}
+
+impl<'a, G: ExtensiveGame> BestResponse<'a, G> {
+    fn collect(&mut self, state: &G::State, reach: f64) {
+        let game = self.game;
+        if game.is_terminal(state) {
+            return;
+        }
+        if game.is_chance(state) {
+            for (action, p) in game.chance_outcomes(state) {
+                self.collect(&game.apply(state, action), reach * p);
+            }
+            return;
+        }
+        let actions = game.actions(state);
+        let key = game.info_set(state);
+        if game.player(state) == self.player {
+            self.states
+                .entry(key)
+                .or_default()
+                .push((state.clone(), reach));
+            for action in actions {
+                self.collect(&game.apply(state, action), reach);
+            }
+        } else {
+            let p = probabilities(self.strategy, &key, actions.len());
+            for (action, p) in actions.into_iter().zip(p) {
+                self.collect(&game.apply(state, action), reach * p);
+            }
+        }
+    }
+
+    /// player 0's utility of `state` with the player best responding
+    fn value(&mut self, state: &G::State) -> f64 {
+        let game = self.game;
+        if game.is_terminal(state) {
+            return game.utility(state);
+        }
+        if game.is_chance(state) {
+            return game
+                .chance_outcomes(state)
+                .into_iter()
+                .map(|(action, p)| p * self.value(&game.apply(state, action)))
+                .sum();
+        }
+        let actions = game.actions(state);
+        let key = game.info_set(state);
+        if game.player(state) != self.player {
+            let p = probabilities(self.strategy, &key, actions.len());
+            return actions
+                .into_iter()
+                .zip(p)
+                .map(|(action, p)| p * self.value(&game.apply(state, action)))
+                .sum();
+        }
+        let choice = self.choose(&key, actions.len());
+        self.value(&game.apply(state, actions[choice]))
+    }
+
+    /// The action with the best counterfactual value summed over the information set
+    fn choose(&mut self, key: &str, actions: usize) -> usize {
+        if let Some(choice) = self.choices.get(key) {
+            return *choice;
+        }
+        let sign = if self.player == 0 { 1.0 } else { -1.0 };
+        let states = self.states.get(key).cloned().unwrap_or_default();
+        let mut best = (0, f64::NEG_INFINITY);
+        for i in 0..actions {
+            let mut total = 0.0;
+            for (state, reach) in &states {
+                let action = self.game.actions(state)[i];
+                total += reach * sign * self.value(&self.game.apply(state, action));
+            }
+            if total > best.1 {
+                best = (i, total);
+            }
+        }
+        self.choices.insert(String::from(key), best.0);
+        best.0
+    }
+}
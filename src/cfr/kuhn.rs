@@ -0,0 +1,149 @@
+use crate::card::Rank;
+use crate::cfr::ExtensiveGame;
+
+/// Kuhn poker: three cards, one each and an ante of 1. Player 0 passes or bets 1, a bet is called
+/// or folded to, and a pass can be answered with a bet once. The game value is -1/18 for player 0.
+#[derive(Clone, Debug, Default)]
+pub struct Kuhn;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum KuhnAction {
+    /// chance deals a card to each player
+    Deal([Rank; 2]),
+    /// check or fold
+    Pass,
+    /// bet or call
+    Bet,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KuhnState {
+    pub cards: Option<[Rank; 2]>,
+    pub history: Vec<KuhnAction>,
+}
+
+const CARDS: [Rank; 3] = [Rank::Jack, Rank::Queen, Rank::King];
+
+impl ExtensiveGame for Kuhn {
+    type State = KuhnState;
+    type Action = KuhnAction;
+
+    fn root(&self) -> KuhnState {
+        KuhnState::default()
+    }
+
+    fn is_terminal(&self, state: &KuhnState) -> bool {
+        use KuhnAction::*;
+        matches!(state.history[..], [Pass, Pass] | [Bet, _] | [Pass, Bet, _])
+    }
+
+    fn utility(&self, state: &KuhnState) -> f64 {
+        use KuhnAction::*;
+        let cards = state.cards.unwrap();
+        let showdown = if cards[0] > cards[1] { 1.0 } else { -1.0 };
+        match state.history[..] {
+            [Pass, Pass] => showdown,
+            [Bet, Pass] => 1.0,
+            [Pass, Bet, Pass] => -1.0,
+            _ => 2.0 * showdown,
+        }
+    }
+
+    fn is_chance(&self, state: &KuhnState) -> bool {
+        state.cards.is_none()
+    }
+
+    fn chance_outcomes(&self, _: &KuhnState) -> Vec<(KuhnAction, f64)> {
+        let mut outcomes = vec![];
+        for first in CARDS.iter() {
+            for second in CARDS.iter().filter(|card| *card != first) {
+                outcomes.push((KuhnAction::Deal([*first, *second]), 1.0 / 6.0));
+            }
+        }
+        outcomes
+    }
+
+    fn player(&self, state: &KuhnState) -> usize {
+        state.history.len() % 2
+    }
+
+    fn actions(&self, _: &KuhnState) -> Vec<KuhnAction> {
+        vec![KuhnAction::Pass, KuhnAction::Bet]
+    }
+
+    fn apply(&self, state: &KuhnState, action: KuhnAction) -> KuhnState {
+        let mut next = state.clone();
+        match action {
+            KuhnAction::Deal(cards) => next.cards = Some(cards),
+            action => next.history.push(action),
+        }
+        next
+    }
+
+    fn info_set(&self, state: &KuhnState) -> String {
+        let card = state.cards.unwrap()[self.player(state)];
+        let history: String = state
+            .history
+            .iter()
+            .map(|action| match action {
+                KuhnAction::Bet => 'b',
+                _ => 'p',
+            })
+            .collect();
+        format!("{}{}", card, history)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cfr::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const VALUE: f64 = -1.0 / 18.0;
+
+    #[test]
+    fn uniform() {
+        let strategy = Strategy::new();
+        // player 0 profits from player 1 calling with a Jack and folding a King
+        assert!((expected_value(&Kuhn, &strategy) - 0.125).abs() < 1e-9);
+        assert!((best_response_value(&Kuhn, &strategy, 0) - 0.5).abs() < 1e-9);
+        assert!(exploitability(&Kuhn, &strategy) > 0.4);
+    }
+
+    #[test]
+    fn vanilla() {
+        let mut cfr = Cfr::new(&Kuhn, Variant::Vanilla);
+        cfr.iterate(1000);
+        let strategy = cfr.strategy();
+        assert_eq!(strategy.len(), 12);
+        assert!(exploitability(&Kuhn, &strategy) < 0.01);
+        assert!((expected_value(&Kuhn, &strategy) - VALUE).abs() < 0.005);
+        // player 1 always calls with a King and never with a Jack
+        assert!(strategy["Kb"][1] > 0.99);
+        assert!(strategy["Jb"][1] < 0.01);
+    }
+
+    #[test]
+    fn plus() {
+        let mut cfr = Cfr::new(&Kuhn, Variant::Plus);
+        cfr.iterate(200);
+        let strategy = cfr.strategy();
+        assert!(exploitability(&Kuhn, &strategy) < 0.002);
+        assert!((expected_value(&Kuhn, &strategy) - VALUE).abs() < 0.005);
+
+        // player 0 bluffs with a Jack a third as often as they bet a King
+        let alpha = strategy["J"][1];
+        assert!((strategy["K"][1] - 3.0 * alpha).abs() < 0.05);
+    }
+
+    #[test]
+    fn sampled() {
+        let mut rng = StdRng::seed_from_u64(41);
+        let mut cfr = Cfr::new(&Kuhn, Variant::Vanilla);
+        cfr.iterate_sampled(20000, &mut rng);
+        let strategy = cfr.strategy();
+        assert!(exploitability(&Kuhn, &strategy) < 0.02);
+    }
+}
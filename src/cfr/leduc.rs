@@ -0,0 +1,242 @@
+use crate::card::{Card, Rank, Suit};
+use crate::cfr::ExtensiveGame;
+
+/// Leduc Hold'em: a deck of two Jacks, Queens and Kings, one private card each and an ante of 1.
+/// Two betting rounds with bets of 2 and 4 and at most two raises each, player 0 acts first in
+/// both; a single board card is dealt in between. Pairing the board wins, otherwise the higher
+/// card.
+#[derive(Clone, Debug, Default)]
+pub struct Leduc;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LeducAction {
+    /// chance deals a private card to each player
+    Deal([Card; 2]),
+    /// chance deals the board card
+    Board(Card),
+    Fold,
+    /// check or call
+    Call,
+    /// bet or raise
+    Raise,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LeducState {
+    pub cards: Option<[Card; 2]>,
+    pub board: Option<Card>,
+    /// the betting actions of the first and the second round
+    pub rounds: [Vec<LeducAction>; 2],
+    /// chips each player put in, antes included
+    pub bets: [u32; 2],
+    pub folded: Option<usize>,
+}
+
+impl LeducState {
+    fn round(&self) -> usize {
+        if self.board.is_some() {
+            1
+        } else {
+            0
+        }
+    }
+}
+
+fn deck() -> Vec<Card> {
+    [Rank::Jack, Rank::Queen, Rank::King]
+        .iter()
+        .flat_map(|rank| {
+            [Suit::Hearts, Suit::Spades]
+                .iter()
+                .map(move |suit| Card::new(*rank, *suit))
+        })
+        .collect()
+}
+
+/// A round is over once a check or a bet is called
+fn round_over(actions: &[LeducAction]) -> bool {
+    actions.len() >= 2 && actions.last() == Some(&LeducAction::Call)
+}
+
+impl ExtensiveGame for Leduc {
+    type State = LeducState;
+    type Action = LeducAction;
+
+    fn root(&self) -> LeducState {
+        LeducState {
+            cards: None,
+            board: None,
+            rounds: [vec![], vec![]],
+            bets: [1, 1],
+            folded: None,
+        }
+    }
+
+    fn is_terminal(&self, state: &LeducState) -> bool {
+        state.folded.is_some() || (state.board.is_some() && round_over(&state.rounds[1]))
+    }
+
+    fn utility(&self, state: &LeducState) -> f64 {
+        let bets = state.bets;
+        match state.folded {
+            Some(0) => return -(bets[0] as f64),
+            Some(_) => return bets[1] as f64,
+            None => {}
+        }
+        let (cards, board) = (state.cards.unwrap(), state.board.unwrap());
+        let value = |card: Card| (card.rank == board.rank, card.rank);
+        match value(cards[0]).cmp(&value(cards[1])) {
+            std::cmp::Ordering::Greater => bets[1] as f64,
+            std::cmp::Ordering::Less => -(bets[0] as f64),
+            std::cmp::Ordering::Equal => 0.0,
+        }
+    }
+
+    fn is_chance(&self, state: &LeducState) -> bool {
+        state.cards.is_none() || (state.board.is_none() && round_over(&state.rounds[0]))
+    }
+
+    fn chance_outcomes(&self, state: &LeducState) -> Vec<(LeducAction, f64)> {
+        let deck = deck();
+        match state.cards {
+            None => {
+                let mut outcomes = vec![];
+                for first in deck.iter() {
+                    for second in deck.iter().filter(|card| *card != first) {
+                        outcomes.push((LeducAction::Deal([*first, *second]), 1.0 / 30.0));
+                    }
+                }
+                outcomes
+            }
+            Some(cards) => deck
+                .into_iter()
+                .filter(|card| !cards.contains(card))
+                .map(|card| (LeducAction::Board(card), 0.25))
+                .collect(),
+        }
+    }
+
+    fn player(&self, state: &LeducState) -> usize {
+        state.rounds[state.round()].len() % 2
+    }
+
+    fn actions(&self, state: &LeducState) -> Vec<LeducAction> {
+        let raises = state.rounds[state.round()]
+            .iter()
+            .filter(|action| **action == LeducAction::Raise)
+            .count();
+        let mut actions = vec![];
+        if state.bets[0] != state.bets[1] {
+            actions.push(LeducAction::Fold);
+        }
+        actions.push(LeducAction::Call);
+        if raises < 2 {
+            actions.push(LeducAction::Raise);
+        }
+        actions
+    }
+
+    fn apply(&self, state: &LeducState, action: LeducAction) -> LeducState {
+        let mut next = state.clone();
+        let player = self.player(state);
+        let round = state.round();
+        let top = state.bets[0].max(state.bets[1]);
+        match action {
+            LeducAction::Deal(cards) => next.cards = Some(cards),
+            LeducAction::Board(card) => next.board = Some(card),
+            LeducAction::Fold => next.folded = Some(player),
+            LeducAction::Call => next.bets[player] = top,
+            LeducAction::Raise => next.bets[player] = top + if round == 0 { 2 } else { 4 },
+        }
+        if !matches!(action, LeducAction::Deal(_) | LeducAction::Board(_)) {
+            next.rounds[round].push(action);
+        }
+        next
+    }
+
+    fn info_set(&self, state: &LeducState) -> String {
+        let card = state.cards.unwrap()[self.player(state)];
+        let board = state.board.map(|card| card.rank.to_string());
+        let history = |actions: &[LeducAction]| -> String {
+            actions
+                .iter()
+                .map(|action| match action {
+                    LeducAction::Fold => 'f',
+                    LeducAction::Raise => 'r',
+                    _ => 'c',
+                })
+                .collect()
+        };
+        format!(
+            "{}{}:{}/{}",
+            card.rank,
+            board.unwrap_or_default(),
+            history(&state.rounds[0]),
+            history(&state.rounds[1])
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cfr::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn rules() {
+        let game = Leduc;
+        let root = game.root();
+        assert!(game.is_chance(&root));
+        assert_eq!(game.chance_outcomes(&root).len(), 30);
+
+        let cards = [Card::from("Kh").unwrap(), Card::from("Qs").unwrap()];
+        let state = game.apply(&root, LeducAction::Deal(cards));
+        assert_eq!(
+            game.actions(&state),
+            vec![LeducAction::Call, LeducAction::Raise]
+        );
+        let state = game.apply(&state, LeducAction::Raise);
+        let state = game.apply(&state, LeducAction::Raise);
+        assert_eq!(state.bets, [3, 5]);
+        // two raises, the last one can only be called or folded to
+        assert_eq!(
+            game.actions(&state),
+            vec![LeducAction::Fold, LeducAction::Call]
+        );
+        let state = game.apply(&state, LeducAction::Call);
+        assert!(game.is_chance(&state));
+        assert_eq!(game.chance_outcomes(&state).len(), 4);
+
+        let state = game.apply(&state, LeducAction::Board(Card::from("Qh").unwrap()));
+        assert_eq!(game.info_set(&state), "KQ:rrc/");
+        let state = game.apply(&state, LeducAction::Raise);
+        assert_eq!(game.info_set(&state), "QQ:rrc/r");
+        let state = game.apply(&state, LeducAction::Call);
+        assert!(game.is_terminal(&state));
+        // the Queen paired the board
+        assert_eq!(game.utility(&state), -9.0);
+    }
+
+    #[test]
+    fn plus() {
+        let mut cfr = Cfr::new(&Leduc, Variant::Plus);
+        cfr.iterate(100);
+        let strategy = cfr.strategy();
+        assert_eq!(strategy.len(), 288);
+        assert!(exploitability(&Leduc, &strategy) < 0.02);
+        // the game value for player 0 is about -0.0856
+        assert!((expected_value(&Leduc, &strategy) + 0.0856).abs() < 0.005);
+    }
+
+    #[test]
+    fn sampled() {
+        let mut rng = StdRng::seed_from_u64(41);
+        let uniform = exploitability(&Leduc, &Strategy::new());
+        let mut cfr = Cfr::new(&Leduc, Variant::Vanilla);
+        cfr.iterate_sampled(2000, &mut rng);
+        let strategy = cfr.strategy();
+        assert!(exploitability(&Leduc, &strategy) < uniform / 4.0);
+    }
+}
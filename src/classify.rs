@@ -0,0 +1,407 @@
+use crate::card::*;
+use crate::deck::check_duplicates;
+use crate::error::{Error, Result};
+use crate::hand_rank::{straight_high, strength, Strength};
+use crate::raw_data::RawData;
+use std::fmt;
+
+/// What a Hold'em holding makes on the board, from the holding's point of view: a pair on the
+/// board alone doesn't count, a single hole card matching it makes `Trips`. Ordered from the
+/// weakest to the strongest; a `Set` ranks above `Trips` since it's hidden and nobody else can
+/// hold the case card with a better kicker
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MadeHand {
+    /// no hole card pairs
+    HighCard,
+    /// a pocket pair below every board card
+    Underpair,
+    BottomPair,
+    /// a hole card pairing neither the top nor the bottom board card, or a pocket pair between
+    MiddlePair,
+    TopPair {
+        /// the kicker is the highest rank not on the board
+        top_kicker: bool,
+    },
+    /// a pocket pair above every board card
+    Overpair,
+    TwoPair,
+    /// three of a kind with one hole card and a pair on the board
+    Trips,
+    /// three of a kind with a pocket pair
+    Set,
+    Straight,
+    Flush,
+    FullHouse,
+    Quads,
+    StraightFlush,
+}
+
+impl MadeHand {
+    /// Parse the names used on the command line, e.g. `two-pair` or `tptk` for top pair, top kicker
+    pub fn from(expr: &str) -> Result<Self> {
+        match expr {
+            "high-card" => Ok(MadeHand::HighCard),
+            "underpair" => Ok(MadeHand::Underpair),
+            "bottom-pair" => Ok(MadeHand::BottomPair),
+            "middle-pair" => Ok(MadeHand::MiddlePair),
+            "top-pair" => Ok(MadeHand::TopPair { top_kicker: false }),
+            "tptk" => Ok(MadeHand::TopPair { top_kicker: true }),
+            "overpair" => Ok(MadeHand::Overpair),
+            "two-pair" => Ok(MadeHand::TwoPair),
+            "trips" => Ok(MadeHand::Trips),
+            "set" => Ok(MadeHand::Set),
+            "straight" => Ok(MadeHand::Straight),
+            "flush" => Ok(MadeHand::Flush),
+            "full-house" => Ok(MadeHand::FullHouse),
+            "quads" => Ok(MadeHand::Quads),
+            "straight-flush" => Ok(MadeHand::StraightFlush),
+            _ => Err(Error::ParseError),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Draw {
+    /// four cards of a suit, at least one of them a hole card
+    FlushDraw,
+    /// a flush draw holding the highest card of the suit that isn't on the board
+    NutFlushDraw,
+    /// eight outs to a straight on either end
+    OpenEnder,
+    /// four outs to an inside straight
+    Gutshot,
+    /// two inside straight draws
+    DoubleGutter,
+    /// three cards of a suit on the flop
+    BackdoorFlush,
+    /// a straight with the turn and the river on the flop
+    BackdoorStraight,
+    /// the number of hole cards above every board card without a pair
+    Overcards(usize),
+}
+
+/// A holding's made hand and its draws on a flop or turn; there are no draws on the river
+#[derive(Clone, Debug, PartialEq)]
+pub struct Classification {
+    pub made: MadeHand,
+    pub draws: Vec<Draw>,
+    /// the exact strength of the best five cards
+    pub strength: Strength,
+}
+
+fn rank_mask(cards: &[Card]) -> u16 {
+    cards
+        .iter()
+        .fold(0, |mask, card| mask | 1 << card.rank as usize)
+}
+
+/// Return the ranks which complete a straight using at least one hole card
+fn straight_outs(hole: u16, board: u16) -> Vec<usize> {
+    (0..13)
+        .filter(|rank| straight_high(hole | board | 1 << rank) > straight_high(board | 1 << rank))
+        .collect()
+}
+
+/// Classify the two card Hold'em `holding` on a `board` of three to five cards
+pub fn classify(holding: &[Card], board: &[Card]) -> Result<Classification> {
+    if holding.len() != 2 || board.len() < 3 || board.len() > 5 {
+        return Err(Error::ParseError);
+    }
+    let cards = [holding, board].concat();
+    check_duplicates(&cards)?;
+
+    let made_strength = strength(&RawData::from_cards(holding), &RawData::from_cards(board));
+    let count = |cards: &[Card], rank: Rank| cards.iter().filter(|c| c.rank == rank).count();
+    let board_high = board.iter().map(|card| card.rank).max().unwrap();
+    let board_low = board.iter().map(|card| card.rank).min().unwrap();
+
+    // hands a five card board makes on its own are played by every holding
+    let board_strength = strength(&RawData::from_cards(board), &RawData::from_cards(&[]));
+    let improves = made_strength > board_strength;
+    let made = match made_strength.category() {
+        8 | 9 if improves => MadeHand::StraightFlush,
+        7 if count(board, made_strength.kickers()[0]) < 4 => MadeHand::Quads,
+        6 if improves => MadeHand::FullHouse,
+        5 if improves => MadeHand::Flush,
+        4 if improves => MadeHand::Straight,
+        _ => {
+            let mut ranks: Vec<Rank> = holding.iter().map(|card| card.rank).collect();
+            ranks.dedup();
+            let mut pairs: Vec<Rank> = vec![];
+            let mut three = None;
+            for rank in ranks.iter().copied() {
+                match count(&cards, rank) {
+                    2 => pairs.push(rank),
+                    n if n >= 3 => three = Some(rank),
+                    _ => {}
+                }
+            }
+            let pocket = holding[0].rank == holding[1].rank;
+            if three.is_some() {
+                if pocket {
+                    MadeHand::Set
+                } else {
+                    MadeHand::Trips
+                }
+            } else if pairs.len() >= 2 {
+                MadeHand::TwoPair
+            } else if let Some(pair) = pairs.first().copied() {
+                if pocket && pair > board_high {
+                    MadeHand::Overpair
+                } else if pocket && pair < board_low {
+                    MadeHand::Underpair
+                } else if pocket {
+                    MadeHand::MiddlePair
+                } else if pair == board_high {
+                    let kicker = holding.iter().map(|c| c.rank).find(|r| *r != pair);
+                    let best = (0..13)
+                        .rev()
+                        .map(Rank::from)
+                        .find(|rank| count(board, *rank) == 0);
+                    MadeHand::TopPair {
+                        top_kicker: kicker.is_some() && kicker == best,
+                    }
+                } else if pair == board_low {
+                    MadeHand::BottomPair
+                } else {
+                    MadeHand::MiddlePair
+                }
+            } else {
+                MadeHand::HighCard
+            }
+        }
+    };
+
+    let mut draws = vec![];
+    if board.len() == 5 {
+        return Ok(Classification {
+            made,
+            draws,
+            strength: made_strength,
+        });
+    }
+
+    let suits = [Suit::Clubs, Suit::Spades, Suit::Hearts, Suit::Diamonds];
+    if made_strength.category() < 5 {
+        for suit in suits.iter() {
+            let own = holding.iter().filter(|c| c.suit == *suit).count();
+            let total = own + board.iter().filter(|c| c.suit == *suit).count();
+            if own == 0 {
+                continue;
+            }
+            if total == 4 {
+                let nut = (0..13)
+                    .rev()
+                    .map(|rank| Card::new(Rank::from(rank), *suit))
+                    .find(|card| !board.contains(card));
+                if nut.is_some_and(|nut| holding.contains(&nut)) {
+                    draws.push(Draw::NutFlushDraw);
+                } else {
+                    draws.push(Draw::FlushDraw);
+                }
+            } else if total == 3 && board.len() == 3 {
+                draws.push(Draw::BackdoorFlush);
+            }
+        }
+    }
+
+    if made_strength.category() < 4 {
+        let (hole, board_ranks) = (rank_mask(holding), rank_mask(board));
+        let outs = straight_outs(hole, board_ranks);
+        // the Ace may also play low, e.g. 2345 is open ended
+        let positions = |rank: usize| {
+            if rank == 12 {
+                vec![-1, 12]
+            } else {
+                vec![rank as i32]
+            }
+        };
+        let open = outs.iter().any(|a| {
+            outs.iter().any(|b| {
+                positions(*a)
+                    .iter()
+                    .any(|x| positions(*b).iter().any(|y| y - x == 5))
+            })
+        });
+        if open {
+            draws.push(Draw::OpenEnder);
+        } else if outs.len() >= 2 {
+            draws.push(Draw::DoubleGutter);
+        } else if outs.len() == 1 {
+            draws.push(Draw::Gutshot);
+        } else if board.len() == 3
+            && (0..13).any(|rank| !straight_outs(hole, board_ranks | 1 << rank).is_empty())
+        {
+            draws.push(Draw::BackdoorStraight);
+        }
+    }
+
+    if made == MadeHand::HighCard {
+        let overcards = holding.iter().filter(|c| c.rank > board_high).count();
+        if overcards > 0 {
+            draws.push(Draw::Overcards(overcards));
+        }
+    }
+
+    Ok(Classification {
+        made,
+        draws,
+        strength: made_strength,
+    })
+}
+
+impl fmt::Display for MadeHand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MadeHand::HighCard => write!(f, "high card"),
+            MadeHand::Underpair => write!(f, "underpair"),
+            MadeHand::BottomPair => write!(f, "bottom pair"),
+            MadeHand::MiddlePair => write!(f, "middle pair"),
+            MadeHand::TopPair { top_kicker: true } => write!(f, "top pair, top kicker"),
+            MadeHand::TopPair { top_kicker: false } => write!(f, "top pair"),
+            MadeHand::Overpair => write!(f, "overpair"),
+            MadeHand::TwoPair => write!(f, "two pair"),
+            MadeHand::Set => write!(f, "set"),
+            MadeHand::Trips => write!(f, "trips"),
+            MadeHand::Straight => write!(f, "straight"),
+            MadeHand::Flush => write!(f, "flush"),
+            MadeHand::FullHouse => write!(f, "full house"),
+            MadeHand::Quads => write!(f, "quads"),
+            MadeHand::StraightFlush => write!(f, "straight flush"),
+        }
+    }
+}
+
+impl fmt::Display for Draw {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Draw::FlushDraw => write!(f, "flush draw"),
+            Draw::NutFlushDraw => write!(f, "nut flush draw"),
+            Draw::OpenEnder => write!(f, "open-ended straight draw"),
+            Draw::Gutshot => write!(f, "gutshot"),
+            Draw::DoubleGutter => write!(f, "double gutter"),
+            Draw::BackdoorFlush => write!(f, "backdoor flush draw"),
+            Draw::BackdoorStraight => write!(f, "backdoor straight draw"),
+            Draw::Overcards(1) => write!(f, "overcard"),
+            Draw::Overcards(n) => write!(f, "{} overcards", n),
+        }
+    }
+}
+
+impl fmt::Display for Classification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.made)?;
+        for draw in self.draws.iter() {
+            write!(f, " + {}", draw)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::parse_cards;
+
+    fn classified(holding: &str, board: &str) -> Classification {
+        classify(&parse_cards(holding).unwrap(), &parse_cards(board).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn made_hands() {
+        let made = |holding: &str, board: &str| classified(holding, board).made;
+        assert_eq!(
+            made("AhKd", "Kc7s2d"),
+            MadeHand::TopPair { top_kicker: true }
+        );
+        assert_eq!(
+            made("KhQd", "Kc7s2d"),
+            MadeHand::TopPair { top_kicker: false }
+        );
+        assert_eq!(
+            made("AhKd", "Ac7s2d"),
+            MadeHand::TopPair { top_kicker: true }
+        );
+        assert_eq!(made("QhQd", "Jc7s2d"), MadeHand::Overpair);
+        assert_eq!(made("9h9d", "Jc7s2d"), MadeHand::MiddlePair);
+        assert_eq!(made("3h3d", "Jc7s5d"), MadeHand::Underpair);
+        assert_eq!(made("8h7d", "Jc7s2d"), MadeHand::MiddlePair);
+        assert_eq!(made("Ah2h", "Jc7s2d"), MadeHand::BottomPair);
+        assert_eq!(made("Jh7d", "Jc7s2d"), MadeHand::TwoPair);
+        assert_eq!(made("7h7d", "Jc7s2d"), MadeHand::Set);
+        assert_eq!(made("Ah7d", "7c7s2d"), MadeHand::Trips);
+        assert!(MadeHand::Set > MadeHand::Trips);
+        assert_eq!(MadeHand::from("two-pair").unwrap(), MadeHand::TwoPair);
+        assert_eq!(
+            MadeHand::from("tptk").unwrap(),
+            MadeHand::TopPair { top_kicker: true }
+        );
+        assert!(MadeHand::from("two pair").is_err());
+        assert!(MadeHand::Trips > MadeHand::TwoPair);
+        // the board pair doesn't belong to the holding
+        assert_eq!(made("AhKd", "7c7s2d"), MadeHand::HighCard);
+        assert_eq!(made("Ah2h", "7c7s2d"), MadeHand::BottomPair);
+        assert_eq!(made("9h8d", "Tc7s6d"), MadeHand::Straight);
+        assert_eq!(made("AhKd", "Tc9s8d7c6c"), MadeHand::HighCard);
+        assert_eq!(made("Ah2h", "Kh7h3h"), MadeHand::Flush);
+        assert_eq!(made("2c2h", "2d7s7d"), MadeHand::FullHouse);
+        assert_eq!(made("7h7d", "7c7s2d"), MadeHand::Quads);
+        assert_eq!(made("9h8h", "Th7h6h"), MadeHand::StraightFlush);
+    }
+
+    #[test]
+    fn draws() {
+        let draws = |holding: &str, board: &str| classified(holding, board).draws;
+        assert_eq!(
+            draws("AhQh", "Kh7h2d"),
+            vec![
+                Draw::NutFlushDraw,
+                Draw::BackdoorStraight,
+                Draw::Overcards(1)
+            ]
+        );
+        assert_eq!(
+            draws("QhJh", "Kh7h2d"),
+            vec![Draw::FlushDraw, Draw::BackdoorStraight]
+        );
+        assert_eq!(
+            draws("9s8d", "7c6h2d"),
+            vec![Draw::OpenEnder, Draw::Overcards(2)]
+        );
+        assert_eq!(draws("5s4d", "3c2hKd"), vec![Draw::OpenEnder]);
+        assert_eq!(
+            draws("9s8d", "7c5h2d"),
+            vec![Draw::Gutshot, Draw::Overcards(2)]
+        );
+        assert_eq!(
+            draws("AsKd", "QcJh2d"),
+            vec![Draw::Gutshot, Draw::Overcards(2)]
+        );
+        assert_eq!(draws("7s5d", "9c8hJd"), vec![Draw::DoubleGutter]);
+        assert_eq!(
+            draws("AsKs", "Qs7h2d"),
+            vec![
+                Draw::BackdoorFlush,
+                Draw::BackdoorStraight,
+                Draw::Overcards(2)
+            ]
+        );
+        // the turn leaves no backdoor draws, the river no draws at all
+        assert_eq!(draws("AsKs", "Qs7h2d3c"), vec![Draw::Overcards(2)]);
+        assert!(draws("9s8d", "7c6h2dKcKh").is_empty());
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            classified("AhKh", "Kc7h2h").to_string(),
+            "top pair, top kicker + nut flush draw"
+        );
+        assert!(classify(
+            &parse_cards("AhKh").unwrap(),
+            &parse_cards("Ah7c2c").unwrap()
+        )
+        .is_err());
+        assert!(classify(&parse_cards("AhKh").unwrap(), &parse_cards("7c2c").unwrap()).is_err());
+    }
+}
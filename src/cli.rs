@@ -1,3 +1,7 @@
+use crate::card::Card;
+use crate::error::{Error, Result};
+use crate::game::Game;
+
 pub struct StringChunks<'a> {
     slice: &'a str,
     step: usize,
@@ -20,3 +24,120 @@ impl<'a> Iterator for StringChunks<'a> {
         Some(ret)
     }
 }
+
+/// Parse a string of concatenated cards like "Td9h2c"
+pub fn parse_cards(expr: &str) -> Result<Vec<Card>> {
+    if !expr.is_ascii() || expr.len() % 2 == 1 {
+        return Err(Error::ParseError);
+    }
+    let mut cards: Vec<Card> = Vec::with_capacity(expr.len() / 2);
+    for card_string in StringChunks::new(expr, 2) {
+        let card = Card::from(card_string)?;
+        if cards.contains(&card) {
+            return Err(Error::DuplicateCard);
+        }
+        cards.push(card);
+    }
+    Ok(cards)
+}
+
+/// Output format of the binary
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl Format {
+    pub fn from(expr: &str) -> Result<Self> {
+        match expr {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(Error::ParseError),
+        }
+    }
+}
+
+/// Remove `<name> <VALUE>` from `args` and return the value
+pub fn take_option(args: &mut Vec<String>, name: &str) -> Result<Option<String>> {
+    if let Some(idx) = args.iter().position(|arg| arg == name) {
+        if idx + 1 >= args.len() {
+            return Err(Error::ParseError);
+        }
+        let value = args.remove(idx + 1);
+        args.remove(idx);
+        return Ok(Some(value));
+    }
+    Ok(None)
+}
+
+/// Remove `--format <FORMAT>` from `args` and return the requested `Format`
+pub fn take_format(args: &mut Vec<String>) -> Result<Format> {
+    match take_option(args, "--format")? {
+        Some(expr) => Format::from(&expr),
+        None => Ok(Format::Text),
+    }
+}
+
+/// Remove `--game <GAME>` from `args` and return the requested `Game`
+pub fn take_game(args: &mut Vec<String>) -> Result<Game> {
+    match take_option(args, "--game")? {
+        Some(expr) => Game::from(&expr),
+        None => Ok(Game::Holdem),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(expr: &str) -> Vec<String> {
+        expr.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn cards() {
+        assert_eq!(
+            parse_cards("Td9h").unwrap(),
+            vec![Card::from("Td").unwrap(), Card::from("9h").unwrap()]
+        );
+        assert_eq!(parse_cards("").unwrap(), vec![]);
+        assert!(parse_cards("Td9").is_err());
+        assert!(parse_cards("TdTd").is_err());
+        assert!(parse_cards("Tx9h").is_err());
+    }
+
+    #[test]
+    fn format_from() {
+        assert_eq!(Format::from("text").unwrap(), Format::Text);
+        assert_eq!(Format::from("json").unwrap(), Format::Json);
+        assert_eq!(Format::from("csv").unwrap(), Format::Csv);
+        assert!(Format::from("xml").is_err());
+    }
+
+    #[test]
+    fn take_format_from_args() {
+        let mut a = args("pkr eval --format csv AhAd KcKs");
+        assert_eq!(take_format(&mut a).unwrap(), Format::Csv);
+        assert_eq!(a, args("pkr eval AhAd KcKs"));
+
+        let mut a = args("pkr eval AhAd KcKs");
+        assert_eq!(take_format(&mut a).unwrap(), Format::Text);
+        assert_eq!(a, args("pkr eval AhAd KcKs"));
+
+        assert!(take_format(&mut args("pkr eval AhAd KcKs --format")).is_err());
+    }
+
+    #[test]
+    fn take_game_from_args() {
+        let mut a = args("pkr eval --game omaha --format csv AhAdKcKs 2h3h4h5h");
+        assert_eq!(take_game(&mut a).unwrap(), Game::Omaha);
+        assert_eq!(take_format(&mut a).unwrap(), Format::Csv);
+        assert_eq!(a, args("pkr eval AhAdKcKs 2h3h4h5h"));
+
+        assert_eq!(take_game(&mut args("pkr eval")).unwrap(), Game::Holdem);
+        assert!(take_game(&mut args("pkr eval --game razz")).is_err());
+    }
+}
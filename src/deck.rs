@@ -1,9 +1,14 @@
 use crate::card::{Card, Rank, Suit};
 use crate::error::{Error, Result};
 use crate::prelude::StringChunks;
+use rand::seq::SliceRandom;
+use rand::Rng;
 use std::collections::HashSet;
 
-pub fn get_cards(args: &[String]) -> Result<(Vec<Card>, Vec<Card>, Vec<Card>, HashSet<Card>)> {
+/// hero, vilan, community cards and the remaining deck
+pub type Cards = (Vec<Card>, Vec<Card>, Vec<Card>, HashSet<Card>);
+
+pub fn get_cards(args: &[String]) -> Result<Cards> {
     let mut deck: HashSet<Card> = HashSet::new();
     deck.insert(Card::new(Rank::Ace, Suit::Clubs));
     deck.insert(Card::new(Rank::King, Suit::Clubs));
@@ -67,7 +72,7 @@ pub fn get_cards(args: &[String]) -> Result<(Vec<Card>, Vec<Card>, Vec<Card>, Ha
             continue;
         }
 
-        for card_string in StringChunks::new(&arg, 2) {
+        for card_string in StringChunks::new(arg, 2) {
             if let Ok(card) = Card::from(card_string) {
                 if i == 1 {
                     hero.push(deck.take(&card).ok_or(Error::DuplicateCard)?);
@@ -82,6 +87,31 @@ pub fn get_cards(args: &[String]) -> Result<(Vec<Card>, Vec<Card>, Vec<Card>, Ha
     Ok((hero, vilan, community_cards, deck))
 }
 
+/// Return all cards of a fresh `Deck` which aren't `known`
+pub fn remaining_cards(known: &[Card]) -> HashSet<Card> {
+    remaining_deck(known).into_iter().collect()
+}
+
+/// Return all cards of a fresh `Deck` which aren't `known`, in the order of the deck
+pub fn remaining_deck(known: &[Card]) -> Vec<Card> {
+    Deck::new()
+        .cards
+        .iter()
+        .filter(|card| !known.contains(card))
+        .copied()
+        .collect()
+}
+
+/// Fail with `Error::DuplicateCard` if a card occurs more than once in `cards`
+pub fn check_duplicates(cards: &[Card]) -> Result<()> {
+    for (i, card) in cards.iter().enumerate() {
+        if cards[..i].contains(card) {
+            return Err(Error::DuplicateCard);
+        }
+    }
+    Ok(())
+}
+
 #[derive(Debug)]
 pub struct Deck {
     pub cards: [Card; 52],
@@ -145,4 +175,17 @@ impl Deck {
         ];
         Deck { cards }
     }
+
+    /// All 52 cards in the order drawn from `rng`, e.g. a `StdRng` seeded to replay a session
+    pub fn shuffled<R: Rng>(rng: &mut R) -> Vec<Card> {
+        let mut cards = Deck::new().cards.to_vec();
+        cards.shuffle(rng);
+        cards
+    }
+}
+
+impl Default for Deck {
+    fn default() -> Self {
+        Self::new()
+    }
 }
@@ -0,0 +1,351 @@
+use crate::card::*;
+use crate::deck::remaining_cards;
+use crate::game::Game;
+use crate::hand_rank::*;
+use crate::range::*;
+
+use itertools::Itertools;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The outcome of enumerating every runout for hero vs. vilan
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Equity {
+    pub hero: Vec<Card>,
+    pub vilan: Vec<Card>,
+    pub community_cards: Vec<Card>,
+    /// number of evaluated runouts
+    pub combos: usize,
+    /// number of cards to come
+    pub k: usize,
+    /// number of cards left in the deck
+    pub deck: usize,
+    pub wins: usize,
+    pub ties: usize,
+    pub losses: usize,
+    /// hero's wins per `HandRank`, indexed by `usize::from(&HandRank)`
+    pub stats: [usize; 10],
+}
+
+impl Equity {
+    /// An empty `Equity` of a spot with `k` cards to come out of a `deck` of the given size
+    pub fn new(
+        hero: Vec<Card>,
+        vilan: Vec<Card>,
+        community_cards: Vec<Card>,
+        k: usize,
+        deck: usize,
+    ) -> Self {
+        Equity {
+            hero,
+            vilan,
+            community_cards,
+            combos: 0,
+            k,
+            deck,
+            wins: 0,
+            ties: 0,
+            losses: 0,
+            stats: [0; 10],
+        }
+    }
+
+    /// Count a single runout where hero ends up with `hero` and vilan with `vilan`
+    pub fn add(&mut self, hero: Strength, vilan: Strength) {
+        match hero.cmp(&vilan) {
+            Ordering::Greater => {
+                self.wins += 1;
+                self.stats[hero.category()] += 1;
+            }
+            Ordering::Less => self.losses += 1,
+            Ordering::Equal => self.ties += 1,
+        }
+        self.combos += 1;
+    }
+
+    /// percentage of runouts hero wins
+    pub fn win_pct(&self) -> f64 {
+        self.pct(self.wins)
+    }
+
+    /// percentage of runouts ending in a split pot
+    pub fn tie_pct(&self) -> f64 {
+        self.pct(self.ties)
+    }
+
+    /// percentage of runouts hero looses
+    pub fn loss_pct(&self) -> f64 {
+        self.pct(self.losses)
+    }
+
+    /// hero's share of the pot in percent, counting splits as half a win
+    pub fn equity(&self) -> f64 {
+        if self.combos == 0 {
+            return 0.0;
+        }
+        (self.wins as f64 + self.ties as f64 / 2.0) * 100.0 / self.combos as f64
+    }
+
+    /// percentage of runouts hero wins with the given `HandRank`
+    pub fn rank_pct(&self, rank: &HandRank) -> f64 {
+        self.pct(self.stats[usize::from(rank)])
+    }
+
+    /// Accumulate the counts of `other` into `self`
+    pub fn merge(&mut self, other: &Equity) {
+        self.combos += other.combos;
+        self.wins += other.wins;
+        self.ties += other.ties;
+        self.losses += other.losses;
+        for (n, m) in self.stats.iter_mut().zip(other.stats.iter()) {
+            *n += m;
+        }
+    }
+
+    fn pct(&self, n: usize) -> f64 {
+        if self.combos == 0 {
+            return 0.0;
+        }
+        n as f64 * 100.0 / self.combos as f64
+    }
+}
+
+/// Enumerate all runouts of the remaining `deck` and compare hero's and vilan's Hold'em hands
+pub fn combos(
+    hero: Vec<Card>,
+    vilan: Vec<Card>,
+    community_cards: Vec<Card>,
+    deck: HashSet<Card>,
+) -> Equity {
+    game_combos(Game::Holdem, hero, vilan, community_cards, deck)
+}
+
+/// Enumerate all runouts of the remaining `deck` and compare the `Strength` of hero's and
+/// vilan's best hand in `game`
+pub fn game_combos(
+    game: Game,
+    hero: Vec<Card>,
+    vilan: Vec<Card>,
+    community_cards: Vec<Card>,
+    deck: HashSet<Card>,
+) -> Equity {
+    let k = 5 - community_cards.len();
+    let mut equity = Equity::new(hero, vilan, community_cards, k, deck.len());
+
+    let mut board = equity.community_cards.clone();
+    for combo in deck.iter().combinations(equity.k) {
+        board.truncate(equity.community_cards.len());
+        board.extend(combo.into_iter().copied());
+
+        let h = game.strength(&equity.hero, &board);
+        let v = game.strength(&equity.vilan, &board);
+        equity.add(h, v);
+    }
+    equity
+}
+
+/// Enumerate hero against every combo of `range` which doesn't collide with a known card.
+///
+/// The counts of all combos are accumulated, hence `vilan` is empty and `deck` is the number of
+/// remaining cards before vilan's holding is removed.
+pub fn range_combos(
+    hero: &[Card],
+    range: &Range,
+    community_cards: &[Card],
+    dead: &[Card],
+) -> Equity {
+    let known: Vec<Card> = hero
+        .iter()
+        .chain(community_cards.iter())
+        .chain(dead.iter())
+        .copied()
+        .collect();
+
+    let mut equity = Equity {
+        hero: hero.to_vec(),
+        vilan: vec![],
+        community_cards: community_cards.to_vec(),
+        combos: 0,
+        k: 5 - community_cards.len(),
+        deck: 52 - known.len(),
+        wins: 0,
+        ties: 0,
+        losses: 0,
+        stats: [0; 10],
+    };
+
+    for combo in range.without(&known) {
+        let deck = remaining_cards(&[&known[..], &combo[..]].concat());
+        let result = combos(
+            hero.to_vec(),
+            combo.to_vec(),
+            community_cards.to_vec(),
+            deck,
+        );
+        equity.merge(&result);
+    }
+    equity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::parse_cards;
+    use crate::deck::{get_cards, Deck};
+    use crate::hand::Hand;
+    use crate::raw_data::RawData;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+
+    fn args(expr: &str) -> Vec<String> {
+        expr.split_whitespace().map(String::from).collect()
+    }
+
+    fn cards(expr: &str) -> Vec<Card> {
+        parse_cards(expr).unwrap()
+    }
+
+    #[test]
+    fn river() {
+        let (hero, vilan, community_cards, deck) =
+            get_cards(&args("eval AhAd KcKs 2c7d9hTs3c")).unwrap();
+        let equity = combos(hero, vilan, community_cards, deck);
+
+        assert_eq!(equity.k, 0);
+        assert_eq!(equity.combos, 1);
+        assert_eq!(equity.wins, 1);
+        assert_eq!(equity.stats[usize::from(&HandRank::Pair(Rank::Ace))], 1);
+        assert_eq!(equity.equity(), 100.0);
+    }
+
+    #[test]
+    fn turn() {
+        let (hero, vilan, community_cards, deck) =
+            get_cards(&args("eval AhAd KcKs 2c7d9hTs")).unwrap();
+        let equity = combos(hero, vilan, community_cards, deck);
+
+        assert_eq!(equity.k, 1);
+        assert_eq!(equity.deck, 44);
+        assert_eq!(equity.combos, 44);
+        // only the two remaining kings save vilan
+        assert_eq!(equity.losses, 2);
+        assert_eq!(equity.wins + equity.ties + equity.losses, equity.combos);
+    }
+
+    /// The best five of `cards` by brute force: the `HandRank` index and the ranks breaking ties
+    fn reference(cards: &[Card]) -> (usize, Vec<usize>) {
+        cards
+            .iter()
+            .combinations(5)
+            .map(|five| {
+                let mut ranks: Vec<usize> = five.iter().map(|c| c.rank as usize).collect();
+                ranks.sort_unstable_by(|a, b| b.cmp(a));
+                let flush = five.iter().all(|c| c.suit == five[0].suit);
+                let straight = match ranks[..] {
+                    [12, 3, 2, 1, 0] => Some(3),
+                    [a, b, c, d, e] if a == b + 1 && b == c + 1 && c == d + 1 && d == e + 1 => {
+                        Some(a)
+                    }
+                    _ => None,
+                };
+                let mut groups: Vec<(usize, usize)> = ranks
+                    .iter()
+                    .dedup()
+                    .map(|r| (ranks.iter().filter(|x| *x == r).count(), *r))
+                    .collect();
+                groups.sort_unstable_by(|a, b| b.cmp(a));
+                let counts: Vec<usize> = groups.iter().map(|g| g.0).collect();
+                let order: Vec<usize> = groups.iter().map(|g| g.1).collect();
+                match (straight, flush, &counts[..]) {
+                    (Some(12), true, _) => (9, vec![12]),
+                    (Some(high), true, _) => (8, vec![high]),
+                    (_, _, [4, 1]) => (7, order),
+                    (_, _, [3, 2]) => (6, order),
+                    (_, true, _) => (5, order),
+                    (Some(high), _, _) => (4, vec![high]),
+                    (_, _, [3, 1, 1]) => (3, order),
+                    (_, _, [2, 2, 1]) => (2, order),
+                    (_, _, [2, 1, 1, 1]) => (1, order),
+                    _ => (0, order),
+                }
+            })
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn same_as_reference() {
+        let mut rng = StdRng::seed_from_u64(29);
+        let cards = Deck::new().cards;
+        let game = Game::Holdem;
+        for _ in 0..2000 {
+            let deal: Vec<Card> = cards.choose_multiple(&mut rng, 9).copied().collect();
+            let (hero, vilan, board) = (&deal[..2], &deal[2..4], &deal[4..]);
+            let (hs, vs) = (game.strength(hero, board), game.strength(vilan, board));
+            let h = reference(&[hero, board].concat());
+            let v = reference(&[vilan, board].concat());
+            assert_eq!((hs.category(), vs.category()), (h.0, v.0), "{:?}", deal);
+            assert_eq!(hs.cmp(&vs), h.cmp(&v), "{:?}", deal);
+        }
+    }
+
+    #[test]
+    fn differs_from_hand() {
+        // `combos` compared `Hand`s before, which get some hands wrong: both have trips, QJ
+        // kickers beat 43
+        let (hero, vilan, board) = (cards("QcJs"), cards("4c3h"), cards("Th2h7dTcTd"));
+        let raw_hero = RawData::from_chain(hero.iter().chain(board.iter()));
+        let raw_vilan = RawData::from_chain(vilan.iter().chain(board.iter()));
+        let combo = vec![];
+        let (h, v) = (Hand::new(&raw_hero, &combo), Hand::new(&raw_vilan, &combo));
+        assert_eq!(h.partial_cmp(&v), Some(Ordering::Equal));
+        let game = Game::Holdem;
+        assert!(game.strength(&hero, &board) > game.strength(&vilan, &board));
+
+        // a flush, not the straight
+        let (holding, board) = (cards("JcQc"), cards("KcTc9s6c4c"));
+        let raw_data = RawData::from_chain(holding.iter().chain(board.iter()));
+        assert_eq!(
+            Hand::new(&raw_data, &combo).rank,
+            HandRank::Straight(Rank::King)
+        );
+        assert_eq!(game.strength(&holding, &board).category(), 5);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_roundtrip() {
+        let (hero, vilan, community_cards, deck) =
+            get_cards(&args("eval AhAd KcKs 2c7d9hTs")).unwrap();
+        let equity = combos(hero, vilan, community_cards, deck);
+
+        let json = serde_json::to_string(&equity).unwrap();
+        assert_eq!(serde_json::from_str::<Equity>(&json).unwrap(), equity);
+    }
+
+    #[test]
+    fn range() {
+        let hero = [Card::from("Ah").unwrap(), Card::from("Ad").unwrap()];
+        let board = [
+            Card::from("2c").unwrap(),
+            Card::from("7d").unwrap(),
+            Card::from("9h").unwrap(),
+            Card::from("Ts").unwrap(),
+            Card::from("3c").unwrap(),
+        ];
+        // AhAd blocks all but one combo of aces
+        let equity = range_combos(&hero, &Range::from("AA,KK").unwrap(), &board, &[]);
+        assert_eq!(equity.combos, 7);
+        assert_eq!(equity.wins, 6);
+        assert_eq!(equity.ties, 1);
+
+        let dead = [Card::from("Kc").unwrap()];
+        let equity = range_combos(&hero, &Range::from("KK").unwrap(), &board, &dead);
+        assert_eq!(equity.combos, 3);
+    }
+}
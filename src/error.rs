@@ -1,3 +1,5 @@
+use std::fmt;
+use std::io;
 use std::result;
 
 pub type Result<T> = result::Result<T, Error>;
@@ -8,4 +10,28 @@ pub enum Error {
     ParseError,
     /// occurs when a Holding is created with two equal `Card`s
     DuplicateCard,
+    /// occurs when an action isn't allowed in the current state of a hand, e.g. checking while
+    /// facing a bet
+    IllegalAction,
+    /// occurs when an output format needs a cargo feature pkr was built without
+    MissingFeature(&'static str),
+    /// occurs when a command can't handle the `Game` it's asked to play, e.g. a split pot game
+    UnsupportedGame,
+    /// occurs when reading input or writing output fails
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::ParseError => write!(f, "could not parse expression"),
+            Error::DuplicateCard => write!(f, "card is already in use"),
+            Error::IllegalAction => write!(f, "action is not legal"),
+            Error::MissingFeature(feature) => {
+                write!(f, "pkr was built without the `{}` feature", feature)
+            }
+            Error::UnsupportedGame => write!(f, "game is not supported by this command"),
+            Error::Io(err) => write!(f, "{}", err),
+        }
+    }
 }
@@ -0,0 +1,184 @@
+use crate::card::*;
+use crate::deck::Deck;
+use crate::error::{Error, Result};
+use crate::hand_rank::*;
+use crate::hilo::{eight_or_better, omaha_low, Low};
+use crate::omaha::omaha_strength;
+use crate::raw_data::*;
+
+use std::collections::HashSet;
+
+/// The poker variant a holding is evaluated in
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Game {
+    /// any five out of the holding and the board
+    Holdem,
+    /// exactly two cards of a 4, 5 or 6 card holding and three of the board
+    Omaha,
+    /// Omaha, the pot is split with the best eight-or-better low
+    OmahaHiLo,
+    /// Hold'em with a 36 card deck, see `Ranking::ShortDeck`
+    ShortDeck { trips_beat_straight: bool },
+    /// Seven Card Stud, the best five out of a player's own seven cards, see `stud`
+    Stud,
+    /// Seven Card Stud, the pot is split with the best eight-or-better low
+    StudHiLo,
+}
+
+impl Game {
+    pub fn from(expr: &str) -> Result<Self> {
+        match expr {
+            "holdem" => Ok(Game::Holdem),
+            "omaha" | "plo" => Ok(Game::Omaha),
+            "omaha8" | "plo8" => Ok(Game::OmahaHiLo),
+            "shortdeck" | "6plus" => Ok(Game::ShortDeck {
+                trips_beat_straight: false,
+            }),
+            "shortdeck-trips" | "6plus-trips" => Ok(Game::ShortDeck {
+                trips_beat_straight: true,
+            }),
+            "stud" => Ok(Game::Stud),
+            "stud8" => Ok(Game::StudHiLo),
+            _ => Err(Error::ParseError),
+        }
+    }
+
+    /// Return whether `size` is a valid number of cards for a holding
+    pub fn holding_size(&self, size: usize) -> bool {
+        match self {
+            Game::Holdem | Game::ShortDeck { .. } => size == 2,
+            Game::Omaha | Game::OmahaHiLo => (4..=6).contains(&size),
+            Game::Stud | Game::StudHiLo => (3..=7).contains(&size),
+        }
+    }
+
+    /// All cards the game is played with
+    pub fn deck(&self) -> Vec<Card> {
+        let deck = Deck::new().cards.to_vec();
+        match self {
+            Game::ShortDeck { .. } => deck.into_iter().filter(|c| c.rank >= Rank::Six).collect(),
+            _ => deck,
+        }
+    }
+
+    /// Return the cards of `deck` which aren't `known`. Fails if a known card is used twice or
+    /// isn't part of the game's deck at all.
+    pub fn remaining_cards(&self, known: &[Card]) -> Result<HashSet<Card>> {
+        let mut deck: HashSet<Card> = self.deck().into_iter().collect();
+        for card in known {
+            if !deck.remove(card) {
+                if self.deck().contains(card) {
+                    return Err(Error::DuplicateCard);
+                }
+                return Err(Error::ParseError);
+            }
+        }
+        Ok(deck)
+    }
+
+    /// Return whether the pot is split between the best high and the best low hand
+    pub fn is_split(&self) -> bool {
+        *self == Game::OmahaHiLo || *self == Game::StudHiLo
+    }
+
+    /// Return whether every player has own up and down cards instead of sharing a board
+    pub fn is_stud(&self) -> bool {
+        *self == Game::Stud || *self == Game::StudHiLo
+    }
+
+    /// The `Strength` of the best hand `holding` makes with `board`
+    pub fn strength(&self, holding: &[Card], board: &[Card]) -> Strength {
+        match self {
+            Game::Holdem | Game::Stud | Game::StudHiLo => {
+                strength(&RawData::from_cards(holding), &RawData::from_cards(board))
+            }
+            Game::Omaha | Game::OmahaHiLo => omaha_strength(holding, board),
+            Game::ShortDeck {
+                trips_beat_straight,
+            } => ranked_strength(
+                &RawData::from_cards(holding),
+                &RawData::from_cards(board),
+                Ranking::ShortDeck {
+                    trips_beat_straight: *trips_beat_straight,
+                },
+            ),
+        }
+    }
+
+    /// The best qualifying `Low` `holding` makes with `board`, if the game has a low half
+    pub fn low(&self, holding: &[Card], board: &[Card]) -> Option<Low> {
+        match self {
+            Game::OmahaHiLo => omaha_low(holding, board),
+            Game::StudHiLo => eight_or_better(&[holding, board].concat()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::parse_cards;
+
+    #[test]
+    fn game_from() {
+        assert_eq!(Game::from("holdem").unwrap(), Game::Holdem);
+        assert_eq!(Game::from("omaha").unwrap(), Game::Omaha);
+        assert_eq!(Game::from("plo").unwrap(), Game::Omaha);
+        assert_eq!(Game::from("plo8").unwrap(), Game::OmahaHiLo);
+        assert_eq!(
+            Game::from("6plus").unwrap(),
+            Game::ShortDeck {
+                trips_beat_straight: false
+            }
+        );
+        assert_eq!(
+            Game::from("shortdeck-trips").unwrap(),
+            Game::ShortDeck {
+                trips_beat_straight: true
+            }
+        );
+        assert_eq!(Game::from("stud").unwrap(), Game::Stud);
+        assert_eq!(Game::from("stud8").unwrap(), Game::StudHiLo);
+        assert!(Game::from("razz").is_err());
+    }
+
+    #[test]
+    fn decks() {
+        let short = Game::ShortDeck {
+            trips_beat_straight: false,
+        };
+        assert_eq!(Game::Holdem.deck().len(), 52);
+        assert_eq!(short.deck().len(), 36);
+
+        let known = parse_cards("AhKh6c").unwrap();
+        assert_eq!(short.remaining_cards(&known).unwrap().len(), 33);
+        assert!(matches!(
+            short.remaining_cards(&[Card::from("Ah").unwrap(), Card::from("Ah").unwrap()]),
+            Err(Error::DuplicateCard)
+        ));
+        assert!(matches!(
+            short.remaining_cards(&parse_cards("Ah5c").unwrap()),
+            Err(Error::ParseError)
+        ));
+    }
+
+    #[test]
+    fn holding_size() {
+        assert!(Game::Holdem.holding_size(2));
+        assert!(!Game::Holdem.holding_size(4));
+        assert!(!Game::Omaha.holding_size(3));
+        assert!(Game::Omaha.holding_size(4));
+        assert!(Game::Omaha.holding_size(6));
+        assert!(!Game::Omaha.holding_size(7));
+    }
+
+    #[test]
+    fn holdem_vs_omaha() {
+        // a single heart in hand makes a flush in Hold'em but not in Omaha
+        let holding = parse_cards("AhKsQsJc").unwrap();
+        let board = parse_cards("2h5h8h9h3c").unwrap();
+        assert_eq!(Game::Holdem.strength(&holding[..2], &board).category(), 5);
+        assert_eq!(Game::Omaha.strength(&holding, &board).category(), 0);
+    }
+}
@@ -13,10 +13,15 @@ pub struct Hand<'a> {
 impl<'a> Hand<'a> {
     pub fn new(own: &'a RawData, combo: &'a Vec<&Card>) -> Self {
         let combo = RawData::new(combo);
-        let rank = rank(&own, &combo);
+        let rank = rank(own, &combo);
         Hand { own, combo, rank }
     }
 
+    /// return the exact `Strength` of the best five cards
+    pub fn strength(&self) -> Strength {
+        strength(self.own, &self.combo)
+    }
+
     /// return the sum of 5 `Ranks` for a given `Suit`
     pub fn suit_rank(&self, suit: Suit) -> usize {
         let mut rank_sum = 0;
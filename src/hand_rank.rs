@@ -2,7 +2,11 @@ use crate::card::*;
 use crate::raw_data::*;
 use std::fmt;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 #[derive(Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum HandRank {
     HighCard,
     Pair(Rank),
@@ -273,6 +277,195 @@ pub fn rank(own: &RawData, other: &RawData) -> HandRank {
     HandRank::HighCard
 }
 
+/// A totally ordered strength of the best five cards, see `strength`.
+///
+/// The position of the category in the variant's ranking is stored in the upper bits, followed by
+/// the category itself (same numbering as `usize::from(&HandRank)`) and up to five `Rank`s
+/// deciding between hands of the same category.
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Strength(pub u32);
+
+impl Strength {
+    fn new(order: usize, category: usize, kickers: &[usize]) -> Self {
+        let mut value = (order << 4 | category) as u32;
+        for i in 0..5 {
+            value = (value << 4) | *kickers.get(i).unwrap_or(&0) as u32;
+        }
+        Strength(value)
+    }
+
+    /// the `HandRank` category, see `usize::from(&HandRank)`
+    pub fn category(&self) -> usize {
+        ((self.0 >> 20) & 0xf) as usize
+    }
+
+    /// the `Rank`s deciding between hands of the same category, most significant first
+    pub fn kickers(&self) -> [Rank; 5] {
+        let mut kickers = [Rank::Two; 5];
+        for (i, kicker) in kickers.iter_mut().enumerate() {
+            *kicker = Rank::from(((self.0 >> (16 - 4 * i)) & 0xf) as usize);
+        }
+        kickers
+    }
+}
+
+/// How the categories of a variant are ranked
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Ranking {
+    /// the standard 52 card ranking
+    Standard,
+    /// Six through Ace: A6789 is the lowest straight and a Flush beats a FullHouse
+    ShortDeck {
+        /// Trips beat a Straight, too
+        trips_beat_straight: bool,
+    },
+    /// the standard ranking, but Aces are always high: A2345 isn't a straight
+    AceHigh,
+}
+
+impl Ranking {
+    /// the position of `category` within this ranking
+    fn order(&self, category: usize) -> usize {
+        match (self, category) {
+            (Ranking::ShortDeck { .. }, 5) => 6,
+            (Ranking::ShortDeck { .. }, 6) => 5,
+            (
+                Ranking::ShortDeck {
+                    trips_beat_straight,
+                },
+                3,
+            ) if *trips_beat_straight => 4,
+            (
+                Ranking::ShortDeck {
+                    trips_beat_straight,
+                },
+                4,
+            ) if *trips_beat_straight => 3,
+            _ => category,
+        }
+    }
+
+    /// Return the highest card of a straight within the `Rank` bitmask `ranks`
+    fn straight_high(&self, ranks: u16) -> Option<usize> {
+        match self {
+            Ranking::Standard => straight_high(ranks),
+            Ranking::AceHigh => straight_high(ranks).filter(|high| *high != Rank::Five as usize),
+            Ranking::ShortDeck { .. } => {
+                let wheel = 1 << Rank::Ace as usize | 0b1111 << Rank::Six as usize;
+                match straight_high(ranks) {
+                    None if ranks & wheel == wheel => Some(Rank::Nine as usize),
+                    high => high,
+                }
+            }
+        }
+    }
+}
+
+/// Return the highest card of a straight within the `Rank` bitmask `ranks`; A2345 counts as a
+/// Five high straight
+pub fn straight_high(ranks: u16) -> Option<usize> {
+    for high in (4..13).rev() {
+        let mask = 0b11111 << (high - 4);
+        if ranks & mask == mask {
+            return Some(high);
+        }
+    }
+    let wheel = 1 << Rank::Ace as usize | 0b1111;
+    if ranks & wheel == wheel {
+        return Some(Rank::Five as usize);
+    }
+    None
+}
+
+/**
+ * Returns the `Strength` of the best five cards out of `own` and `other`.
+ *
+ * Unlike `rank`, which only determines the `HandRank`, the result also accounts for kickers and
+ * can be compared directly: the higher `Strength` wins, equal `Strength`s split the pot.
+ */
+pub fn strength(own: &RawData, other: &RawData) -> Strength {
+    ranked_strength(own, other, Ranking::Standard)
+}
+
+/// Returns the `Strength` of the best five cards out of `own` and `other` under `ranking`
+pub fn ranked_strength(own: &RawData, other: &RawData, ranking: Ranking) -> Strength {
+    let mut num_ranks = [0; 13];
+    let mut all_ranks: u16 = 0;
+    let mut suit_ranks: [u16; 4] = [0; 4];
+
+    for (rank, num) in num_ranks.iter_mut().enumerate() {
+        *num = own.num_ranks[rank] + other.num_ranks[rank];
+        if *num > 0 {
+            all_ranks |= 1 << rank;
+        }
+        for (suit, suit_rank) in suit_ranks.iter_mut().enumerate() {
+            if own.ranks[rank][suit] + other.ranks[rank][suit] > 0 {
+                *suit_rank |= 1 << rank;
+            }
+        }
+    }
+
+    // ranks ordered by how often they occur, then by rank; e.g. [K, K, K, 9, 9, A, 4]
+    let mut grouped: Vec<(usize, usize)> = (0..13)
+        .rev()
+        .filter(|rank| num_ranks[*rank] > 0)
+        .map(|rank| (num_ranks[rank], rank))
+        .collect();
+    grouped.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.cmp(&a.1)));
+
+    let (most, first) = grouped.first().copied().unwrap_or((0, 0));
+    let (second_most, second) = grouped.get(1).copied().unwrap_or((0, 0));
+
+    // highest ranks which aren't part of `exclude`
+    let kickers = |exclude: &[usize], amount: usize| -> Vec<usize> {
+        (0..13)
+            .rev()
+            .filter(|rank| num_ranks[*rank] > 0 && !exclude.contains(rank))
+            .take(amount)
+            .collect()
+    };
+    let made = |category: usize, kickers: &[usize]| {
+        Strength::new(ranking.order(category), category, kickers)
+    };
+
+    // collect every category the cards make, the best one according to `ranking` wins
+    let mut candidates = vec![made(0, &kickers(&[], 5))];
+    if most >= 2 {
+        candidates.push(made(1, &[&[first][..], &kickers(&[first], 3)].concat()));
+    }
+    if most >= 2 && second_most >= 2 {
+        let two_pair = [&[first, second][..], &kickers(&[first, second], 1)].concat();
+        candidates.push(made(2, &two_pair));
+    }
+    if most >= 3 {
+        candidates.push(made(3, &[&[first][..], &kickers(&[first], 2)].concat()));
+    }
+    if let Some(high) = ranking.straight_high(all_ranks) {
+        candidates.push(made(4, &[high]));
+    }
+    if let Some(flush) = suit_ranks.iter().find(|ranks| ranks.count_ones() >= 5) {
+        let ranks: Vec<usize> = (0..13)
+            .rev()
+            .filter(|r| flush & 1 << r > 0)
+            .take(5)
+            .collect();
+        candidates.push(made(5, &ranks));
+
+        if let Some(high) = ranking.straight_high(*flush) {
+            let category = if high == Rank::Ace as usize { 9 } else { 8 };
+            candidates.push(made(category, &[high]));
+        }
+    }
+    if most == 3 && second_most >= 2 {
+        candidates.push(made(6, &[first, second]));
+    }
+    if most >= 4 {
+        candidates.push(made(7, &[&[first][..], &kickers(&[first], 1)].concat()));
+    }
+    candidates.into_iter().max().unwrap()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -344,6 +537,85 @@ mod tests {
         );
     }
 
+    fn strength_of(expr: &str) -> Strength {
+        let cards = crate::cli::parse_cards(expr).unwrap();
+        strength(&RawData::from_cards(&cards), &RawData::new(&vec![]))
+    }
+
+    #[test]
+    fn straight_highs() {
+        assert_eq!(straight_high(0b1_1111_0000_0000), Some(12));
+        assert_eq!(straight_high(0b1_0000_0000_1111), Some(3));
+        assert_eq!(straight_high(0b0_0000_0111_1100), Some(6));
+        assert_eq!(straight_high(0b1_0000_0000_0111), None);
+    }
+
+    #[test]
+    fn strength_categories() {
+        assert_eq!(strength_of("AhKh2c7d9s").category(), 0);
+        assert_eq!(strength_of("AhAd2c7d9s").category(), 1);
+        assert_eq!(strength_of("AhAd2c2d9s").category(), 2);
+        assert_eq!(strength_of("AhAdAc2d9s").category(), 3);
+        assert_eq!(strength_of("Ah2d3c4d5s").category(), 4);
+        assert_eq!(strength_of("Ah2h8h4h5h").category(), 5);
+        assert_eq!(strength_of("AhAdAc2d2s").category(), 6);
+        assert_eq!(strength_of("AhAdAcAs2s").category(), 7);
+        assert_eq!(strength_of("Ah2h3h4h5h").category(), 8);
+        assert_eq!(strength_of("AhKhQhJhTh").category(), 9);
+        // two trips make a full house, six suited cards a flush
+        assert_eq!(strength_of("JhJdJc7d7s7h2c").category(), 6);
+        assert_eq!(strength_of("2hQh5h6hJhKh9c").category(), 5);
+    }
+
+    #[test]
+    fn strength_kickers() {
+        assert!(strength_of("AhAdKc7d9s") > strength_of("AhAdQc7d9s"));
+        assert!(strength_of("AhKdQc7d9s") > strength_of("AhKdQc7d8s"));
+        assert_eq!(strength_of("AhKdQc7d9s2c"), strength_of("AsKcQd7h9c3h"));
+        assert!(strength_of("2d3c4d5s6h") > strength_of("Ah2d3c4d5s"));
+        assert!(strength_of("KhKdKcKsQh") > strength_of("KhKdKcKsJh"));
+        // the third pair plays as kicker
+        assert!(strength_of("AhAdKcKsQhQd2c") > strength_of("AhAdKcKsJh3d2c"));
+        assert_eq!(
+            strength_of("AhAdKcKsQh").kickers(),
+            [Rank::Ace, Rank::King, Rank::Queen, Rank::Two, Rank::Two]
+        );
+    }
+
+    fn short_strength_of(expr: &str, trips_beat_straight: bool) -> Strength {
+        let cards = crate::cli::parse_cards(expr).unwrap();
+        ranked_strength(
+            &RawData::from_cards(&cards),
+            &RawData::new(&vec![]),
+            Ranking::ShortDeck {
+                trips_beat_straight,
+            },
+        )
+    }
+
+    #[test]
+    fn short_deck() {
+        // A6789 is a straight, A2345 can't happen
+        assert_eq!(short_strength_of("Ah6d7c8d9s", false).category(), 4);
+        assert_eq!(
+            short_strength_of("Ah6d7c8d9s", false).kickers()[0],
+            Rank::Nine
+        );
+        assert!(short_strength_of("6d7c8d9sTh", false) > short_strength_of("Ah6d7c8d9s", false));
+        assert_eq!(short_strength_of("Ah6h7h8h9h", false).category(), 8);
+
+        // Flush beats FullHouse
+        assert!(short_strength_of("Ah6h8hTh9h", false) > short_strength_of("AhAdAcKdKs", false));
+        assert!(strength_of("Ah6h8hTh9h") < strength_of("AhAdAcKdKs"));
+
+        // optionally Trips beat a Straight
+        let trips = "AhAdAcKd7s";
+        let straight = "6d7c8d9sTh";
+        assert!(short_strength_of(trips, false) < short_strength_of(straight, false));
+        assert!(short_strength_of(trips, true) > short_strength_of(straight, true));
+        assert_eq!(short_strength_of("9h9d9c8d7s6hTc", true).category(), 3);
+    }
+
     #[test]
     fn mem() {
         assert_eq!(std::mem::size_of::<HandRank>(), 3);
@@ -0,0 +1,344 @@
+use crate::card::*;
+use crate::game::Game;
+use crate::hand_rank::Strength;
+
+use itertools::Itertools;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The value of a low hand; a better (lower) low compares greater, like `Strength`.
+///
+/// `Low`s are only comparable if they were computed by the same evaluator.
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Low(pub u32);
+
+/// the upper bound of the value a `Low` is subtracted from
+const WORST: u32 = 0x0fff_ffff;
+
+impl Low {
+    /// Build an A-5 `Low` from the pairing `category` (see `usize::from(&HandRank)`) and five
+    /// `low_value`s, most significant first
+    pub fn new(category: usize, values: &[usize]) -> Self {
+        let mut raw = category as u32;
+        for i in 0..5 {
+            raw = (raw << 4) | *values.get(i).unwrap_or(&0) as u32;
+        }
+        Low(WORST - raw)
+    }
+
+    /// Build a `Low` from a high hand `Strength`, the weakest high hand being the best low
+    pub fn from_strength(strength: Strength) -> Self {
+        Low(WORST - strength.0)
+    }
+
+    /// the `Rank`s of an A-5 low, most significant first
+    pub fn ranks(&self) -> [Rank; 5] {
+        let raw = WORST - self.0;
+        let mut ranks = [Rank::Ace; 5];
+        for (i, rank) in ranks.iter_mut().enumerate() {
+            *rank = from_low_value(((raw >> (16 - 4 * i)) & 0xf) as usize);
+        }
+        ranks
+    }
+}
+
+/// The value of a `Rank` in A-5 lowball where Aces are low: Ace = 0, Two = 1, .., King = 12
+pub fn low_value(rank: Rank) -> usize {
+    if rank == Rank::Ace {
+        0
+    } else {
+        rank as usize + 1
+    }
+}
+
+fn from_low_value(value: usize) -> Rank {
+    if value == 0 {
+        Rank::Ace
+    } else {
+        Rank::from(value - 1)
+    }
+}
+
+/// Return the best eight-or-better low out of `cards`: five unpaired cards of Eight or lower,
+/// Aces low, straights and flushes don't count. `None` if the low doesn't qualify.
+pub fn eight_or_better(cards: &[Card]) -> Option<Low> {
+    let values: Vec<usize> = cards
+        .iter()
+        .map(|card| low_value(card.rank))
+        .filter(|value| *value <= low_value(Rank::Eight))
+        .sorted()
+        .dedup()
+        .take(5)
+        .collect();
+    if values.len() < 5 {
+        return None;
+    }
+    Some(Low::new(
+        0,
+        &values.into_iter().rev().collect::<Vec<usize>>(),
+    ))
+}
+
+/// Return the best eight-or-better low using exactly two cards of `holding` and three of `board`
+pub fn omaha_low(holding: &[Card], board: &[Card]) -> Option<Low> {
+    if board.len() < 3 {
+        return None;
+    }
+    let mut best: Option<Low> = None;
+    for own in holding.iter().combinations(2) {
+        for other in board.iter().combinations(3) {
+            let cards: Vec<Card> = own.iter().chain(other.iter()).map(|c| **c).collect();
+            let low = eight_or_better(&cards);
+            if low > best {
+                best = low;
+            }
+        }
+    }
+    best
+}
+
+/// Hero's share of a heads up pot which is split between the best high and the best qualifying
+/// low hand. Without a qualifying low the high hand takes it all.
+pub fn pot_share(high: Ordering, hero_low: Option<Low>, vilan_low: Option<Low>) -> f64 {
+    let high_share = match high {
+        Ordering::Greater => 1.0,
+        Ordering::Equal => 0.5,
+        Ordering::Less => 0.0,
+    };
+    if hero_low.is_none() && vilan_low.is_none() {
+        return high_share;
+    }
+    let low_share = match hero_low.cmp(&vilan_low) {
+        Ordering::Greater => 1.0,
+        Ordering::Equal => 0.5,
+        Ordering::Less => 0.0,
+    };
+    (high_share + low_share) / 2.0
+}
+
+/// The outcome of enumerating every runout of a hi/lo split game
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HiLoEquity {
+    pub hero: Vec<Card>,
+    pub vilan: Vec<Card>,
+    pub community_cards: Vec<Card>,
+    /// number of evaluated runouts
+    pub combos: usize,
+    /// number of cards to come
+    pub k: usize,
+    /// number of cards left in the deck
+    pub deck: usize,
+    /// sum of hero's pot shares over all runouts
+    pub share: f64,
+    /// hero wins the whole pot
+    pub hero_scoops: usize,
+    /// vilan wins the whole pot
+    pub vilan_scoops: usize,
+    /// hero only gets a quarter of the pot
+    pub hero_quartered: usize,
+    /// vilan only gets a quarter of the pot
+    pub vilan_quartered: usize,
+    pub high_wins: usize,
+    pub high_ties: usize,
+    pub low_wins: usize,
+    pub low_ties: usize,
+    /// runouts without a qualifying low
+    pub no_low: usize,
+}
+
+impl HiLoEquity {
+    /// An empty `HiLoEquity` of a spot with `k` cards to come out of a `deck` of the given size
+    pub fn new(
+        hero: Vec<Card>,
+        vilan: Vec<Card>,
+        community_cards: Vec<Card>,
+        k: usize,
+        deck: usize,
+    ) -> Self {
+        HiLoEquity {
+            hero,
+            vilan,
+            community_cards,
+            combos: 0,
+            k,
+            deck,
+            share: 0.0,
+            hero_scoops: 0,
+            vilan_scoops: 0,
+            hero_quartered: 0,
+            vilan_quartered: 0,
+            high_wins: 0,
+            high_ties: 0,
+            low_wins: 0,
+            low_ties: 0,
+            no_low: 0,
+        }
+    }
+
+    /// Count a single runout given the outcome of the high hands and both players' lows
+    pub fn add(&mut self, high: Ordering, hero_low: Option<Low>, vilan_low: Option<Low>) {
+        match high {
+            Ordering::Greater => self.high_wins += 1,
+            Ordering::Equal => self.high_ties += 1,
+            Ordering::Less => {}
+        }
+        if hero_low.is_none() && vilan_low.is_none() {
+            self.no_low += 1;
+        } else {
+            match hero_low.cmp(&vilan_low) {
+                Ordering::Greater => self.low_wins += 1,
+                Ordering::Equal => self.low_ties += 1,
+                Ordering::Less => {}
+            }
+        }
+
+        let share = pot_share(high, hero_low, vilan_low);
+        if share == 1.0 {
+            self.hero_scoops += 1;
+        } else if share == 0.0 {
+            self.vilan_scoops += 1;
+        } else if share == 0.25 {
+            self.hero_quartered += 1;
+        } else if share == 0.75 {
+            self.vilan_quartered += 1;
+        }
+        self.share += share;
+        self.combos += 1;
+    }
+
+    /// hero's share of the pot in percent
+    pub fn equity(&self) -> f64 {
+        self.pct(self.share)
+    }
+
+    /// percentage of runouts hero wins the whole pot
+    pub fn scoop_pct(&self) -> f64 {
+        self.pct(self.hero_scoops as f64)
+    }
+
+    /// percentage of runouts hero only gets a quarter of the pot
+    pub fn quartered_pct(&self) -> f64 {
+        self.pct(self.hero_quartered as f64)
+    }
+
+    /// percentage of runouts with a qualifying low
+    pub fn low_pct(&self) -> f64 {
+        self.pct((self.combos - self.no_low) as f64)
+    }
+
+    fn pct(&self, n: f64) -> f64 {
+        if self.combos == 0 {
+            return 0.0;
+        }
+        n * 100.0 / self.combos as f64
+    }
+}
+
+/// Enumerate all runouts of the remaining `deck` and split each pot between the best high and the
+/// best qualifying low hand of `game`
+pub fn hilo_combos(
+    game: Game,
+    hero: Vec<Card>,
+    vilan: Vec<Card>,
+    community_cards: Vec<Card>,
+    deck: HashSet<Card>,
+) -> HiLoEquity {
+    let k = 5 - community_cards.len();
+    let mut equity = HiLoEquity::new(hero, vilan, community_cards, k, deck.len());
+
+    let mut board = equity.community_cards.clone();
+    for combo in deck.iter().combinations(equity.k) {
+        board.truncate(equity.community_cards.len());
+        board.extend(combo.into_iter().copied());
+
+        let high = game
+            .strength(&equity.hero, &board)
+            .cmp(&game.strength(&equity.vilan, &board));
+        let hero_low = game.low(&equity.hero, &board);
+        let vilan_low = game.low(&equity.vilan, &board);
+        equity.add(high, hero_low, vilan_low);
+    }
+    equity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::parse_cards;
+    use crate::deck::remaining_cards;
+
+    fn low(expr: &str) -> Option<Low> {
+        eight_or_better(&parse_cards(expr).unwrap())
+    }
+
+    #[test]
+    fn qualifier() {
+        assert!(low("Ah2c3d4s5h").is_some());
+        assert!(low("Ah2c3d4s8h").is_some());
+        assert!(low("Ah2c3d4s9h").is_none());
+        // pairs don't count
+        assert!(low("Ah2c3d4s4h").is_none());
+        assert!(low("Ah2c3d4s4hKd8c").is_some());
+    }
+
+    #[test]
+    fn ordering() {
+        // the wheel is the nuts, straights and flushes don't count
+        assert!(low("Ah2h3h4h5h") > low("Ah2c3d4s6h"));
+        assert!(low("8h5c4d3s2h") > low("8h6c4d3s2h"));
+        assert!(low("7h6c4d3s2h") > low("8h5c4d3s2h"));
+        assert_eq!(low("Ah2c3d4s5h"), low("As2s3s4s5sKcQd"));
+        assert!(low("8h7c6d5s4h") > None);
+    }
+
+    #[test]
+    fn ranks() {
+        assert_eq!(
+            low("Ah7c4d3s2hKd").unwrap().ranks(),
+            [Rank::Seven, Rank::Four, Rank::Three, Rank::Two, Rank::Ace]
+        );
+    }
+
+    #[test]
+    fn omaha() {
+        let board = parse_cards("2c3d7hKsQs").unwrap();
+        // three low cards in hand, only two of them play
+        assert_eq!(
+            omaha_low(&parse_cards("Ah4s5c9d").unwrap(), &board),
+            low("Ah4s7h3d2c")
+        );
+        // a single low card in hand doesn't make a low
+        assert!(omaha_low(&parse_cards("AhKcQd9s").unwrap(), &board).is_none());
+        assert!(omaha_low(&parse_cards("Ah2s").unwrap(), &board[..2]).is_none());
+    }
+
+    #[test]
+    fn shares() {
+        assert_eq!(pot_share(Ordering::Greater, None, None), 1.0);
+        assert_eq!(pot_share(Ordering::Equal, None, None), 0.5);
+        assert_eq!(pot_share(Ordering::Greater, low("Ah2c3d4s5h"), None), 1.0);
+        assert_eq!(pot_share(Ordering::Less, low("Ah2c3d4s5h"), None), 0.5);
+        let wheel = low("Ah2c3d4s5h");
+        assert_eq!(pot_share(Ordering::Less, wheel, wheel), 0.25);
+        assert_eq!(pot_share(Ordering::Greater, wheel, wheel), 0.75);
+    }
+
+    #[test]
+    fn river() {
+        let hero = parse_cards("Ah2sKhKd").unwrap();
+        let vilan = parse_cards("As2dQcJc").unwrap();
+        let board = parse_cards("3c4h8dKs9s").unwrap();
+        let known = [&hero[..], &vilan[..], &board[..]].concat();
+
+        let equity = hilo_combos(Game::OmahaHiLo, hero, vilan, board, remaining_cards(&known));
+        // hero wins the high with a set and ties the low
+        assert_eq!(equity.combos, 1);
+        assert_eq!(equity.vilan_quartered, 1);
+        assert_eq!(equity.equity(), 75.0);
+    }
+}
@@ -0,0 +1,888 @@
+use crate::card::*;
+use crate::error::{Error, Result};
+use crate::table::{Action, HandState, PostKind, Status, Street, Table};
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::BufRead;
+
+/// A seat line of a hand history, e.g. `Seat 3: hero ($10.00 in chips)`
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistorySeat {
+    /// the seat number as printed, starting at 1
+    pub number: usize,
+    pub name: String,
+    pub stack: u64,
+    pub sitting_out: bool,
+}
+
+/// A forced bet, e.g. `player1: posts small blind $0.05`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Post {
+    pub player: String,
+    pub kind: PostKind,
+    pub amount: u64,
+}
+
+/// A voluntary action on one of the streets
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryAction {
+    pub street: Street,
+    pub player: String,
+    pub action: Action,
+    /// chips the action put into the pot
+    pub amount: u64,
+    pub all_in: bool,
+}
+
+/// A single hand of a PokerStars hand history.
+///
+/// All amounts are in hundredths, i.e. cents for cash games and a hundredth of a chip in
+/// tournaments, so both fit the `u64` chips of a `Table`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HandHistory {
+    pub id: String,
+    /// e.g. "Hold'em No Limit" or "Tournament #123, $1.00+$0.10 USD Hold'em No Limit"
+    pub game: String,
+    pub small_blind: u64,
+    pub big_blind: u64,
+    /// the blind level of a tournament, e.g. "I"
+    pub level: Option<String>,
+    pub date: String,
+    pub table: String,
+    pub max_seats: Option<usize>,
+    /// the seat number of the button
+    pub button: usize,
+    pub seats: Vec<HistorySeat>,
+    pub posts: Vec<Post>,
+    /// the player whose hole cards were dealt face up to the history's owner
+    pub hero: Option<String>,
+    pub hole_cards: Vec<Card>,
+    pub actions: Vec<HistoryAction>,
+    pub board: Vec<Card>,
+    /// holdings shown at showdown
+    pub shown: Vec<(String, Vec<Card>)>,
+    /// bets which were returned because nobody called them
+    pub uncalled: Vec<(String, u64)>,
+    /// chips collected from the pot, one entry per pot a player won
+    pub collected: Vec<(String, u64)>,
+    pub total_pot: u64,
+    pub rake: u64,
+}
+
+impl HandHistory {
+    /// Parse the text of a single hand
+    pub fn from(text: &str) -> Result<Self> {
+        let mut lines = text
+            .lines()
+            .map(|line| line.trim_start_matches('\u{feff}').trim())
+            .filter(|line| !line.is_empty());
+
+        let mut history = HandHistory::default();
+        parse_header(lines.next().ok_or(Error::ParseError)?, &mut history)?;
+        parse_table(lines.next().ok_or(Error::ParseError)?, &mut history)?;
+
+        let mut street = Street::Preflop;
+        let mut summary = false;
+        // chips each player put in on the current street, to tell how much a raise added
+        let mut street_bets: HashMap<String, u64> = HashMap::new();
+        for line in lines {
+            if let Some(marker) = line.strip_prefix("*** ") {
+                let (name, cards) = match marker.find(" ***") {
+                    Some(idx) => (&marker[..idx], &marker[idx + 4..]),
+                    None => return Err(Error::ParseError),
+                };
+                match name {
+                    "HOLE CARDS" => street = Street::Preflop,
+                    "FLOP" | "TURN" | "RIVER" => {
+                        street = match name {
+                            "FLOP" => Street::Flop,
+                            "TURN" => Street::Turn,
+                            _ => Street::River,
+                        };
+                        history.board = bracketed(cards)?;
+                        street_bets.clear();
+                    }
+                    "SHOW DOWN" => street = Street::Showdown,
+                    "SUMMARY" => summary = true,
+                    _ => {}
+                }
+                continue;
+            }
+
+            if summary {
+                parse_summary(line, &mut history)?;
+            } else if line.starts_with("Seat ") {
+                history.seats.push(parse_seat(line)?);
+            } else if let Some(rest) = line.strip_prefix("Dealt to ") {
+                let idx = rest.rfind(" [").ok_or(Error::ParseError)?;
+                history.hero = Some(String::from(&rest[..idx]));
+                history.hole_cards = bracketed(&rest[idx..])?;
+            } else if let Some(rest) = line.strip_prefix("Uncalled bet (") {
+                let idx = rest.find(") returned to ").ok_or(Error::ParseError)?;
+                let player = String::from(&rest[idx + 14..]);
+                history.uncalled.push((player, parse_amount(&rest[..idx])?));
+            } else if let Some(idx) = line.find(" collected ") {
+                let amount = line[idx + 11..]
+                    .split_whitespace()
+                    .next()
+                    .ok_or(Error::ParseError)?;
+                let player = String::from(&line[..idx]);
+                history.collected.push((player, parse_amount(amount)?));
+            } else if let Some(idx) = line.rfind(": ") {
+                let player = &line[..idx];
+                parse_action(
+                    player,
+                    &line[idx + 2..],
+                    street,
+                    &mut street_bets,
+                    &mut history,
+                )?;
+            }
+        }
+
+        if history.seats.is_empty() {
+            return Err(Error::ParseError);
+        }
+        Ok(history)
+    }
+
+    /// the name of the player on the button
+    pub fn button_player(&self) -> Option<&str> {
+        self.seats
+            .iter()
+            .find(|seat| seat.number == self.button)
+            .map(|seat| seat.name.as_str())
+    }
+
+    /// Convert a finished hand of the engine. The `Table`'s chips are written as the cents of a
+    /// cash game and every holding which reached the showdown is shown.
+    pub fn from_hand(table: &Table, hand: &HandState, id: &str, date: &str) -> Self {
+        let n = hand.players.len();
+        let name = |i: usize| table.seats[i].name.clone();
+        let initial: Vec<u64> = hand
+            .players
+            .iter()
+            .map(|p| p.stack + p.contributed)
+            .collect();
+
+        let mut stacks = initial.clone();
+        let mut bets = vec![0; n];
+        for (i, kind, amount) in &hand.posts {
+            stacks[*i] -= amount;
+            if *kind != PostKind::Ante {
+                bets[*i] += amount;
+            }
+        }
+        let mut street = Street::Preflop;
+        let mut actions = vec![];
+        for (action_street, i, action) in &hand.history {
+            if *action_street != street {
+                street = *action_street;
+                bets = vec![0; n];
+            }
+            let current = bets.iter().copied().max().unwrap_or(0);
+            let amount = match action {
+                Action::Call => (current - bets[*i]).min(stacks[*i]),
+                Action::Bet(to) | Action::Raise(to) => to - bets[*i],
+                Action::Fold | Action::Check => 0,
+            };
+            stacks[*i] -= amount;
+            bets[*i] += amount;
+            actions.push(HistoryAction {
+                street,
+                player: name(*i),
+                action: *action,
+                amount,
+                all_in: amount > 0 && stacks[*i] == 0,
+            });
+        }
+
+        // the part of the largest contribution nobody matched goes back
+        let mut contributed: Vec<(u64, usize)> = hand
+            .players
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (p.contributed, i))
+            .collect();
+        contributed.sort_unstable_by(|a, b| b.cmp(a));
+        let (top, bettor) = contributed[0];
+        let uncalled = top - contributed[1].0;
+
+        let payouts = hand.payouts();
+        let in_hand = hand.in_hand();
+        HandHistory {
+            id: String::from(id),
+            game: String::from("Hold'em No Limit"),
+            small_blind: table.small_blind,
+            big_blind: table.big_blind,
+            level: None,
+            date: String::from(date),
+            table: String::from("pkr"),
+            max_seats: Some(n),
+            button: hand.button + 1,
+            seats: (0..n)
+                .map(|i| HistorySeat {
+                    number: i + 1,
+                    name: name(i),
+                    stack: initial[i],
+                    sitting_out: hand.players[i].status == Status::SittingOut,
+                })
+                .collect(),
+            posts: hand
+                .posts
+                .iter()
+                .map(|(i, kind, amount)| Post {
+                    player: name(*i),
+                    kind: *kind,
+                    amount: *amount,
+                })
+                .collect(),
+            hero: None,
+            hole_cards: vec![],
+            actions,
+            board: hand.board.clone(),
+            shown: if in_hand.len() > 1 {
+                in_hand
+                    .iter()
+                    .map(|i| (name(*i), hand.players[*i].holding.clone()))
+                    .collect()
+            } else {
+                vec![]
+            },
+            uncalled: if uncalled > 0 {
+                vec![(name(bettor), uncalled)]
+            } else {
+                vec![]
+            },
+            collected: (0..n)
+                .filter_map(|i| {
+                    let won = payouts[i] - if i == bettor { uncalled } else { 0 };
+                    if won > 0 {
+                        Some((name(i), won))
+                    } else {
+                        None
+                    }
+                })
+                .collect(),
+            total_pot: hand.pot() - uncalled,
+            rake: 0,
+        }
+    }
+
+    /// Format an amount in hundredths like the hand history does, "$0.05" for cash games and
+    /// "20" for tournaments
+    pub fn format_amount(&self, amount: u64) -> String {
+        match (&self.level, amount % 100) {
+            (Some(_), 0) => format!("{}", amount / 100),
+            (Some(_), cents) => format!("{}.{:02}", amount / 100, cents),
+            (None, cents) => format!("${}.{:02}", amount / 100, cents),
+        }
+    }
+}
+
+/// Write the hand in PokerStars format, which `HandHistory::from` reads back
+impl fmt::Display for HandHistory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let amount = |n: u64| self.format_amount(n);
+        let (sb, bb) = (amount(self.small_blind), amount(self.big_blind));
+        match &self.level {
+            Some(level) => writeln!(
+                f,
+                "PokerStars Hand #{}: {} - Level {} ({}/{}) - {}",
+                self.id, self.game, level, sb, bb, self.date
+            )?,
+            None => writeln!(
+                f,
+                "PokerStars Hand #{}: {} ({}/{} USD) - {}",
+                self.id, self.game, sb, bb, self.date
+            )?,
+        }
+        match self.max_seats {
+            Some(n) => writeln!(
+                f,
+                "Table '{}' {}-max Seat #{} is the button",
+                self.table, n, self.button
+            )?,
+            None => writeln!(
+                f,
+                "Table '{}' Seat #{} is the button",
+                self.table, self.button
+            )?,
+        }
+        for seat in &self.seats {
+            let sitting_out = if seat.sitting_out {
+                " is sitting out"
+            } else {
+                ""
+            };
+            writeln!(
+                f,
+                "Seat {}: {} ({} in chips){}",
+                seat.number,
+                seat.name,
+                amount(seat.stack),
+                sitting_out
+            )?;
+        }
+
+        let mut bets: HashMap<&str, u64> = HashMap::new();
+        for post in &self.posts {
+            let (text, live) = match post.kind {
+                PostKind::SmallBlind => ("posts small blind", post.amount),
+                PostKind::BigBlind => ("posts big blind", post.amount),
+                PostKind::SmallAndBigBlind => {
+                    ("posts small & big blinds", self.big_blind.min(post.amount))
+                }
+                PostKind::Ante => ("posts the ante", 0),
+            };
+            *bets.entry(&post.player).or_insert(0) += live;
+            writeln!(f, "{}: {} {}", post.player, text, amount(post.amount))?;
+        }
+
+        writeln!(f, "*** HOLE CARDS ***")?;
+        if let Some(hero) = &self.hero {
+            writeln!(f, "Dealt to {} [{}]", hero, spaced(&self.hole_cards))?;
+        }
+        for (street, name, size) in [
+            (Street::Preflop, "", 0),
+            (Street::Flop, "FLOP", 3),
+            (Street::Turn, "TURN", 4),
+            (Street::River, "RIVER", 5),
+        ] {
+            if self.board.len() < size {
+                break;
+            }
+            match street {
+                Street::Preflop => {}
+                Street::Flop => writeln!(f, "*** FLOP *** [{}]", spaced(&self.board[..3]))?,
+                _ => writeln!(
+                    f,
+                    "*** {} *** [{}] [{}]",
+                    name,
+                    spaced(&self.board[..size - 1]),
+                    self.board[size - 1].notation()
+                )?,
+            }
+            if street != Street::Preflop {
+                bets.clear();
+            }
+
+            for action in self.actions.iter().filter(|a| a.street == street) {
+                let current = bets.values().copied().max().unwrap_or(0);
+                let text = match action.action {
+                    Action::Fold => String::from("folds"),
+                    Action::Check => String::from("checks"),
+                    Action::Call => format!("calls {}", amount(action.amount)),
+                    Action::Bet(to) => format!("bets {}", amount(to)),
+                    Action::Raise(to) => {
+                        format!("raises {} to {}", amount(to - current), amount(to))
+                    }
+                };
+                let all_in = if action.all_in { " and is all-in" } else { "" };
+                *bets.entry(&action.player).or_insert(0) += action.amount;
+                writeln!(f, "{}: {}{}", action.player, text, all_in)?;
+            }
+        }
+
+        for (player, chips) in &self.uncalled {
+            writeln!(
+                f,
+                "Uncalled bet ({}) returned to {}",
+                amount(*chips),
+                player
+            )?;
+        }
+        if !self.shown.is_empty() {
+            writeln!(f, "*** SHOW DOWN ***")?;
+            for (player, cards) in &self.shown {
+                writeln!(f, "{}: shows [{}]", player, spaced(cards))?;
+            }
+        }
+        for (player, chips) in &self.collected {
+            writeln!(f, "{} collected {} from pot", player, amount(*chips))?;
+        }
+        writeln!(f, "*** SUMMARY ***")?;
+        writeln!(
+            f,
+            "Total pot {} | Rake {}",
+            amount(self.total_pot),
+            amount(self.rake)
+        )?;
+        if !self.board.is_empty() {
+            writeln!(f, "Board [{}]", spaced(&self.board))?;
+        }
+        Ok(())
+    }
+}
+
+fn spaced(cards: &[Card]) -> String {
+    cards
+        .iter()
+        .map(|card| card.notation())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Iterator over the hands of a hand history file. Every item is the line number the hand starts
+/// at and the parsed hand or the error which occured parsing it; a broken hand doesn't stop the
+/// iteration.
+pub struct HandHistories<R: BufRead> {
+    lines: std::iter::Enumerate<std::io::Lines<R>>,
+    /// the header of the next hand, which was read while looking for the end of the current one
+    next: Option<(usize, String)>,
+}
+
+impl<R: BufRead> HandHistories<R> {
+    pub fn new(input: R) -> Self {
+        HandHistories {
+            lines: input.lines().enumerate(),
+            next: None,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for HandHistories<R> {
+    type Item = (usize, Result<HandHistory>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (start, mut text) = match self.next.take() {
+            Some(header) => header,
+            None => loop {
+                let (idx, line) = self.lines.next()?;
+                match line {
+                    Ok(line) if is_header(&line) => break (idx + 1, line),
+                    Ok(_) => continue,
+                    Err(err) => return Some((idx + 1, Err(Error::Io(err)))),
+                }
+            },
+        };
+
+        for (idx, line) in self.lines.by_ref() {
+            match line {
+                Ok(line) if is_header(&line) => {
+                    self.next = Some((idx + 1, line));
+                    break;
+                }
+                Ok(line) => {
+                    text.push('\n');
+                    text.push_str(&line);
+                }
+                Err(err) => return Some((start, Err(Error::Io(err)))),
+            }
+        }
+        Some((start, HandHistory::from(&text)))
+    }
+}
+
+/// Parse every hand of a hand history text, see `HandHistories`
+pub fn parse_histories(text: &str) -> Vec<(usize, Result<HandHistory>)> {
+    HandHistories::new(text.as_bytes()).collect()
+}
+
+fn is_header(line: &str) -> bool {
+    let line = line.trim_start_matches('\u{feff}');
+    line.starts_with("PokerStars ") && line.contains(" #")
+}
+
+/// `PokerStars Hand #123: Hold'em No Limit ($0.05/$0.10 USD) - 2020/01/01 12:00:00 ET`
+fn parse_header(line: &str, history: &mut HandHistory) -> Result<()> {
+    if !is_header(line) {
+        return Err(Error::ParseError);
+    }
+    let start = line.find('#').ok_or(Error::ParseError)? + 1;
+    let colon = start + line[start..].find(": ").ok_or(Error::ParseError)?;
+    history.id = String::from(&line[start..colon]);
+
+    let rest = &line[colon + 2..];
+    let dash = rest.rfind(" - ").ok_or(Error::ParseError)?;
+    history.date = String::from(&rest[dash + 3..]);
+
+    // the stakes are the parenthesized part containing a '/', e.g. "($0.05/$0.10 USD)" or the
+    // blinds of a tournament level "Level I (10/20)"
+    let description = &rest[..dash];
+    let open = description.rfind('(').ok_or(Error::ParseError)?;
+    let close = open + description[open..].find(')').ok_or(Error::ParseError)?;
+    let stakes = description[open + 1..close]
+        .split_whitespace()
+        .next()
+        .ok_or(Error::ParseError)?;
+    let mut blinds = stakes.split('/');
+    history.small_blind = parse_amount(blinds.next().ok_or(Error::ParseError)?)?;
+    history.big_blind = parse_amount(blinds.next().ok_or(Error::ParseError)?)?;
+
+    let game = match description.find(" - Level ") {
+        Some(idx) => {
+            history.level = Some(String::from(description[idx + 9..open].trim()));
+            &description[..idx]
+        }
+        None => &description[..open],
+    };
+    history.game = String::from(game.trim());
+    Ok(())
+}
+
+/// `Table 'Alpha II' 6-max Seat #3 is the button`
+fn parse_table(line: &str, history: &mut HandHistory) -> Result<()> {
+    let rest = line.strip_prefix("Table '").ok_or(Error::ParseError)?;
+    let quote = rest.rfind('\'').ok_or(Error::ParseError)?;
+    history.table = String::from(&rest[..quote]);
+
+    let rest = &rest[quote + 1..];
+    history.max_seats = rest
+        .split_whitespace()
+        .find_map(|word| word.strip_suffix("-max"))
+        .and_then(|n| n.parse().ok());
+    let idx = rest.find("Seat #").ok_or(Error::ParseError)?;
+    history.button = rest[idx + 6..]
+        .split_whitespace()
+        .next()
+        .and_then(|n| n.parse().ok())
+        .ok_or(Error::ParseError)?;
+    Ok(())
+}
+
+/// `Seat 3: hero ($10.00 in chips) is sitting out`
+fn parse_seat(line: &str) -> Result<HistorySeat> {
+    let rest = &line[5..];
+    let colon = rest.find(": ").ok_or(Error::ParseError)?;
+    let number = rest[..colon].parse().map_err(|_| Error::ParseError)?;
+
+    let rest = &rest[colon + 2..];
+    let open = rest.rfind(" (").ok_or(Error::ParseError)?;
+    let stack = rest[open + 2..]
+        .split_whitespace()
+        .next()
+        .ok_or(Error::ParseError)?;
+    Ok(HistorySeat {
+        number,
+        name: String::from(&rest[..open]),
+        stack: parse_amount(stack)?,
+        sitting_out: rest.ends_with("is sitting out"),
+    })
+}
+
+fn parse_action(
+    player: &str,
+    text: &str,
+    street: Street,
+    street_bets: &mut HashMap<String, u64>,
+    history: &mut HandHistory,
+) -> Result<()> {
+    let all_in = text.ends_with("and is all-in");
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let amount = |idx: usize| parse_amount(words.get(idx).ok_or(Error::ParseError)?);
+    let bet = street_bets.get(player).copied().unwrap_or(0);
+
+    let (action, added) = match words.first() {
+        Some(&"posts") => {
+            let (kind, idx) = match words.get(1..3) {
+                Some(["small", "blind"]) => (PostKind::SmallBlind, 3),
+                Some(["big", "blind"]) => (PostKind::BigBlind, 3),
+                Some(["small", "&"]) => (PostKind::SmallAndBigBlind, 4),
+                Some(["the", "ante"]) => (PostKind::Ante, 3),
+                _ => return Err(Error::ParseError),
+            };
+            let amount = amount(idx)?;
+            // antes and dead small blinds don't count towards the player's bet
+            let live = match kind {
+                PostKind::Ante => 0,
+                PostKind::SmallAndBigBlind => history.big_blind.min(amount),
+                _ => amount,
+            };
+            street_bets.insert(String::from(player), bet + live);
+            history.posts.push(Post {
+                player: String::from(player),
+                kind,
+                amount,
+            });
+            return Ok(());
+        }
+        Some(&"shows") => {
+            let cards = bracketed(text)?;
+            history.shown.push((String::from(player), cards));
+            return Ok(());
+        }
+        Some(&"folds") => (Action::Fold, 0),
+        Some(&"checks") => (Action::Check, 0),
+        Some(&"calls") => (Action::Call, amount(1)?),
+        Some(&"bets") => {
+            let to = amount(1)?;
+            (Action::Bet(bet + to), to)
+        }
+        Some(&"raises") => {
+            if words.get(2) != Some(&"to") {
+                return Err(Error::ParseError);
+            }
+            let to = amount(3)?;
+            (Action::Raise(to), to.saturating_sub(bet))
+        }
+        // chat, "mucks hand", "is sitting out", ..
+        _ => return Ok(()),
+    };
+
+    street_bets.insert(String::from(player), bet + added);
+    history.actions.push(HistoryAction {
+        street,
+        player: String::from(player),
+        action,
+        amount: added,
+        all_in,
+    });
+    Ok(())
+}
+
+/// `Total pot $2.65 | Rake $0.10` and `Board [2c 7d 9h Ts 3c]`, the seat summaries repeat what
+/// was already parsed
+fn parse_summary(line: &str, history: &mut HandHistory) -> Result<()> {
+    if let Some(rest) = line.strip_prefix("Total pot ") {
+        let mut parts = rest.split(" | ");
+        let pot = parts.next().ok_or(Error::ParseError)?;
+        history.total_pot = parse_amount(pot.split_whitespace().next().ok_or(Error::ParseError)?)?;
+        for part in parts {
+            if let Some(rake) = part.strip_prefix("Rake ") {
+                history.rake = parse_amount(rake.trim())?;
+            }
+        }
+    } else if let Some(rest) = line.strip_prefix("Board ") {
+        history.board = bracketed(rest)?;
+    }
+    Ok(())
+}
+
+/// All cards in the square brackets of `text`, e.g. "[2c 7d 9h] [Ts]"
+fn bracketed(text: &str) -> Result<Vec<Card>> {
+    let mut cards = vec![];
+    let mut rest = text;
+    while let Some(open) = rest.find('[') {
+        let close = open + rest[open..].find(']').ok_or(Error::ParseError)?;
+        for card in rest[open + 1..close].split_whitespace() {
+            cards.push(Card::from(card)?);
+        }
+        rest = &rest[close + 1..];
+    }
+    Ok(cards)
+}
+
+/// Parse an amount like "$0.05", "€1,000.50" or "20" into hundredths
+pub fn parse_amount(expr: &str) -> Result<u64> {
+    let digits: String = expr
+        .trim_start_matches(['$', '€', '£'])
+        .chars()
+        .filter(|c| *c != ',')
+        .collect();
+    let (int, frac) = match digits.find('.') {
+        Some(idx) => (&digits[..idx], &digits[idx + 1..]),
+        None => (&digits[..], ""),
+    };
+    if int.is_empty()
+        || frac.len() > 2
+        || !(int.chars().chain(frac.chars())).all(|c| c.is_ascii_digit())
+    {
+        return Err(Error::ParseError);
+    }
+    let int: u64 = int.parse().map_err(|_| Error::ParseError)?;
+    let frac: u64 = format!("{:0<2}", frac)
+        .parse()
+        .map_err(|_| Error::ParseError)?;
+    Ok(int * 100 + frac)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::parse_cards;
+
+    const HAND: &str = "\
+PokerStars Hand #208452345112: Hold'em No Limit ($0.05/$0.10 USD) - 2020/01/01 12:00:00 ET
+Table 'Alpha II' 6-max Seat #3 is the button
+Seat 1: player1 ($10.00 in chips)
+Seat 2: player 2 ($4.05 in chips)
+Seat 3: hero ($10 in chips)
+player1: posts small blind $0.05
+player 2: posts big blind $0.10
+*** HOLE CARDS ***
+Dealt to hero [Ah Kd]
+hero: raises $0.20 to $0.30
+player1: folds
+player 2: calls $0.20
+*** FLOP *** [2c 7d 9h]
+player 2: checks
+hero: bets $0.40
+player 2: raises $3.35 to $3.75 and is all-in
+hero: calls $3.35
+*** TURN *** [2c 7d 9h] [Ts]
+*** RIVER *** [2c 7d 9h Ts] [3c]
+*** SHOW DOWN ***
+player 2: shows [Qh Qd] (a pair of Queens)
+hero: shows [Ah Kd] (high card Ace)
+player 2 collected $7.95 from pot
+*** SUMMARY ***
+Total pot $8.15 | Rake $0.20
+Board [2c 7d 9h Ts 3c]
+Seat 1: player1 (small blind) folded before Flop
+Seat 2: player 2 (big blind) showed [Qh Qd] and won ($7.95) with a pair of Queens
+Seat 3: hero (button) showed [Ah Kd] and lost with high card Ace
+";
+
+    const TOURNAMENT: &str = "\
+PokerStars Hand #208452345113: Tournament #2954, $1.00+$0.10 USD Hold'em No Limit - Level I (10/20) - 2020/01/01 12:05:00 ET
+Table '2954 1' 9-max Seat #1 is the button
+Seat 1: alice (1500 in chips)
+Seat 2: bob (1500 in chips)
+alice: posts the ante 5
+bob: posts the ante 5
+alice: posts small blind 10
+bob: posts big blind 20
+*** HOLE CARDS ***
+alice: raises 40 to 60
+bob: folds
+Uncalled bet (40) returned to alice
+alice collected 50 from pot
+*** SUMMARY ***
+Total pot 50 | Rake 0
+Seat 1: alice (button) (small blind) collected (50)
+Seat 2: bob (big blind) folded before Flop
+";
+
+    #[test]
+    fn cash_game() {
+        let history = HandHistory::from(HAND).unwrap();
+        assert_eq!(history.id, "208452345112");
+        assert_eq!(history.game, "Hold'em No Limit");
+        assert_eq!((history.small_blind, history.big_blind), (5, 10));
+        assert_eq!(history.date, "2020/01/01 12:00:00 ET");
+        assert_eq!(history.table, "Alpha II");
+        assert_eq!(history.max_seats, Some(6));
+        assert_eq!(history.button_player(), Some("hero"));
+
+        assert_eq!(history.seats.len(), 3);
+        assert_eq!(history.seats[1].name, "player 2");
+        assert_eq!(history.seats[1].stack, 405);
+        assert_eq!(history.seats[2].stack, 1000);
+        assert_eq!(history.posts[1].kind, PostKind::BigBlind);
+
+        assert_eq!(history.hero.as_deref(), Some("hero"));
+        assert_eq!(history.hole_cards, parse_cards("AhKd").unwrap());
+        assert_eq!(history.board, parse_cards("2c7d9hTs3c").unwrap());
+
+        assert_eq!(history.actions.len(), 7);
+        assert_eq!(history.actions[0].action, Action::Raise(30));
+        assert_eq!(history.actions[0].amount, 30);
+        assert_eq!(history.actions[2].amount, 20);
+        let shove = &history.actions[5];
+        assert_eq!(shove.street, Street::Flop);
+        assert_eq!(shove.action, Action::Raise(375));
+        assert_eq!(shove.amount, 375);
+        assert!(shove.all_in);
+
+        assert_eq!(history.shown.len(), 2);
+        assert_eq!(history.shown[0].1, parse_cards("QhQd").unwrap());
+        assert_eq!(history.collected, vec![(String::from("player 2"), 795)]);
+        assert_eq!((history.total_pot, history.rake), (815, 20));
+    }
+
+    #[test]
+    fn tournament() {
+        let history = HandHistory::from(TOURNAMENT).unwrap();
+        assert_eq!(
+            history.game,
+            "Tournament #2954, $1.00+$0.10 USD Hold'em No Limit"
+        );
+        assert_eq!((history.small_blind, history.big_blind), (1000, 2000));
+        assert_eq!(history.level.as_deref(), Some("I"));
+        assert_eq!(history.posts.len(), 4);
+        assert_eq!(history.posts[0].kind, PostKind::Ante);
+        // the small blind's raise to 60 adds 50
+        assert_eq!(history.actions[0].amount, 5000);
+        assert_eq!(history.uncalled, vec![(String::from("alice"), 4000)]);
+        assert!(history.board.is_empty());
+    }
+
+    #[test]
+    fn amounts() {
+        assert_eq!(parse_amount("$0.05").unwrap(), 5);
+        assert_eq!(parse_amount("$1.5").unwrap(), 150);
+        assert_eq!(parse_amount("€1,000").unwrap(), 100_000);
+        assert_eq!(parse_amount("20").unwrap(), 2000);
+        assert!(parse_amount("$").is_err());
+        assert!(parse_amount("0.005").is_err());
+        assert!(parse_amount("1.x").is_err());
+    }
+
+    #[test]
+    fn multiple_hands() {
+        let broken = HAND.replace("[Ah Kd]", "[Ah Kx]");
+        let text = format!("\u{feff}{}\n\n\n{}\n\n{}", HAND, broken, TOURNAMENT);
+        let hands = parse_histories(&text);
+        assert_eq!(hands.len(), 3);
+        assert_eq!(hands[0].0, 1);
+        assert!(hands[0].1.is_ok());
+        // the broken hand doesn't take the next one down with it
+        assert!(hands[1].1.is_err());
+        assert_eq!(hands[2].1.as_ref().unwrap().id, "208452345113");
+        assert_eq!(hands[2].0, 2 * (HAND.lines().count() + 2) + 2);
+    }
+
+    #[test]
+    fn roundtrip() {
+        for text in &[HAND, TOURNAMENT] {
+            let history = HandHistory::from(text).unwrap();
+            let written = history.to_string();
+            assert_eq!(HandHistory::from(&written).unwrap(), history);
+        }
+        let written = HandHistory::from(HAND).unwrap().to_string();
+        assert!(written.contains("player 2: raises $3.35 to $3.75 and is all-in\n"));
+        assert!(written.contains("*** TURN *** [2c 7d 9h] [Ts]\n"));
+    }
+
+    #[test]
+    fn from_hand() {
+        let mut table = Table::new(1, 2, 0);
+        table.sit("alice", 100);
+        table.sit("bob", 50);
+        table.sit("carol", 100);
+        let mut deck = parse_cards("AhKcQhAdKsQd2c7d9hTs3c").unwrap();
+        let rest: Vec<Card> = crate::deck::Deck::default()
+            .cards
+            .iter()
+            .filter(|card| !deck.contains(card))
+            .copied()
+            .collect();
+        deck.extend(rest);
+
+        let mut hand = table.start_hand(deck).unwrap();
+        hand.act(Action::Raise(6)).unwrap();
+        hand.act(Action::Raise(50)).unwrap();
+        hand.act(Action::Fold).unwrap();
+        hand.act(Action::Call).unwrap();
+        assert!(hand.is_over());
+
+        let history = HandHistory::from_hand(&table, &hand, "1", "2020/01/01 12:00:00 ET");
+        assert_eq!(history.button_player(), Some("alice"));
+        assert_eq!(history.actions[1].amount, 49);
+        assert!(history.actions[1].all_in);
+        assert_eq!(history.actions[3].amount, 44);
+        assert_eq!(history.board.len(), 5);
+        // alice's Queens lose to bob's Aces
+        assert_eq!(history.shown.len(), 2);
+        assert_eq!(history.collected, vec![(String::from("bob"), 102)]);
+        assert_eq!(history.total_pot, 102);
+
+        let written = history.to_string();
+        assert!(written.contains("bob: raises $0.44 to $0.50 and is all-in\n"));
+        assert_eq!(HandHistory::from(&written).unwrap(), history);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(HandHistory::from("").is_err());
+        assert!(HandHistory::from("PokerStars Hand #1: Hold'em No Limit - 2020").is_err());
+        let no_seats = HAND
+            .lines()
+            .filter(|line| !line.starts_with("Seat "))
+            .collect::<Vec<&str>>()
+            .join("\n");
+        assert!(HandHistory::from(&no_seats).is_err());
+    }
+}
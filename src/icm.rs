@@ -0,0 +1,314 @@
+use crate::equity::Equity;
+use crate::error::{Error, Result};
+
+use rand::Rng;
+use std::collections::BTreeMap;
+
+/// The prize equity of every stack under the Malmuth-Harville model: the chance to finish first
+/// is proportional to the stack, and so on for the remaining places among the remaining players.
+/// Busted players finish last and share those places evenly.
+///
+/// Exact, but it enumerates every set of players who could take the paid places but the last,
+/// about `n^(paid - 1) / (paid - 1)!` of them; use `sample_icm` for large fields paying many
+/// places.
+pub fn icm(stacks: &[f64], payouts: &[f64]) -> Vec<f64> {
+    let n = stacks.len();
+    let mut equity = vec![0.0; n];
+    // chance that exactly the players in the set took the first places
+    let mut reached: BTreeMap<Vec<usize>, f64> = BTreeMap::new();
+    reached.insert(vec![], 1.0);
+    for payout in payouts.iter().take(n) {
+        let mut next: BTreeMap<Vec<usize>, f64> = BTreeMap::new();
+        for (finished, p) in reached.iter() {
+            let left: Vec<usize> = (0..n).filter(|i| !finished.contains(i)).collect();
+            let rest: f64 = left.iter().map(|i| stacks[*i].max(0.0)).sum();
+            for i in left.iter().copied() {
+                let chance = if rest > 0.0 {
+                    stacks[i].max(0.0) / rest
+                } else {
+                    1.0 / left.len() as f64
+                };
+                if chance == 0.0 {
+                    continue;
+                }
+                equity[i] += p * chance * payout;
+                let mut finished = finished.clone();
+                let at = finished.binary_search(&i).unwrap_err();
+                finished.insert(at, i);
+                *next.entry(finished).or_insert(0.0) += p * chance;
+            }
+        }
+        reached = next;
+    }
+    equity
+}
+
+/// Estimate the Malmuth-Harville prize equities from `samples` random finishing orders.
+///
+/// Each order sorts the players by `-ln(u) / stack` for a uniform `u`: the smallest key is
+/// proportional to the stack and the exponential distribution is memoryless, so the remaining
+/// places follow the same model. Linear in the number of players per sample.
+pub fn sample_icm<R: Rng>(
+    stacks: &[f64],
+    payouts: &[f64],
+    samples: usize,
+    rng: &mut R,
+) -> Vec<f64> {
+    let n = stacks.len();
+    let mut equity = vec![0.0; n];
+    if samples == 0 {
+        return equity;
+    }
+    let mut order: Vec<(f64, usize)> = Vec::with_capacity(n);
+    for _ in 0..samples {
+        order.clear();
+        for (i, stack) in stacks.iter().enumerate() {
+            let u: f64 = rng.gen_range(f64::MIN_POSITIVE, 1.0);
+            // busted players sort behind everybody, in random order
+            let key = if *stack > 0.0 {
+                -u.ln() / stack
+            } else {
+                f64::INFINITY
+            };
+            order.push((key, i));
+        }
+        order.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        let busted = order.iter().filter(|(key, _)| key.is_infinite()).count();
+        let (alive, last) = order.split_at_mut(n - busted);
+        // share the places of the busted players evenly instead of picking an order
+        let shared: f64 = payouts.iter().skip(alive.len()).take(busted).sum();
+        for (place, (_, i)) in alive.iter().enumerate() {
+            equity[*i] += payouts.get(place).copied().unwrap_or(0.0);
+        }
+        for (_, i) in last.iter() {
+            equity[*i] += shared / busted as f64;
+        }
+    }
+    for e in equity.iter_mut() {
+        *e /= samples as f64;
+    }
+    equity
+}
+
+/// A player facing an all-in, e.g. on the bubble of a tournament
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AllInCall {
+    /// every player's chips behind
+    pub stacks: Vec<f64>,
+    /// the prizes still to be paid, first place first
+    pub payouts: Vec<f64>,
+    /// index of the player deciding
+    pub hero: usize,
+    /// index of the player who is all-in
+    pub vilan: usize,
+    /// chips in the middle, including the all-in and whatever hero already put in
+    pub pot: f64,
+    /// chips hero needs to call; the part hero can't cover goes back to vilan
+    pub to_call: f64,
+}
+
+/// Hero's prize equity after each decision
+#[derive(Clone, Debug, PartialEq)]
+pub struct CallEv {
+    pub fold: f64,
+    pub call: f64,
+    /// the equity in percent at which calling and folding are worth the same
+    pub required_equity: f64,
+}
+
+impl AllInCall {
+    /// Hero's prize equity after folding, winning, splitting and losing the all-in
+    fn outcomes(&self) -> Result<[f64; 4]> {
+        let n = self.stacks.len();
+        if self.hero >= n || self.vilan >= n || self.hero == self.vilan {
+            return Err(Error::IllegalAction);
+        }
+        let call = self.to_call.min(self.stacks[self.hero]);
+        let refund = self.to_call - call;
+        let pot = self.pot - refund + call;
+        let value = |hero: f64, vilan: f64| {
+            let mut stacks = self.stacks.clone();
+            stacks[self.hero] = hero;
+            stacks[self.vilan] = vilan;
+            icm(&stacks, &self.payouts)[self.hero]
+        };
+        let (hero, vilan) = (self.stacks[self.hero], self.stacks[self.vilan] + refund);
+        Ok([
+            value(hero, self.stacks[self.vilan] + self.pot),
+            value(hero - call + pot, vilan),
+            value(hero - call + pot / 2.0, vilan + pot / 2.0),
+            value(hero - call, vilan + pot),
+        ])
+    }
+
+    /// Evaluate calling with hero's share of the pot `equity` in percent, split pots count as half
+    /// a win
+    pub fn evaluate_pct(&self, equity: f64) -> Result<CallEv> {
+        let [fold, win, _, lose] = self.outcomes()?;
+        let p = equity / 100.0;
+        Ok(CallEv {
+            fold,
+            call: p * win + (1.0 - p) * lose,
+            required_equity: required_equity(fold, win, lose),
+        })
+    }
+
+    /// Evaluate calling with the win, tie and loss percentages of `equity`, e.g. from
+    /// `game_combos` with hero's and vilan's holdings
+    pub fn evaluate(&self, equity: &Equity) -> Result<CallEv> {
+        let [fold, win, tie, lose] = self.outcomes()?;
+        Ok(CallEv {
+            fold,
+            call: (equity.win_pct() * win + equity.tie_pct() * tie + equity.loss_pct() * lose)
+                / 100.0,
+            required_equity: required_equity(fold, win, lose),
+        })
+    }
+}
+
+fn required_equity(fold: f64, win: f64, lose: f64) -> f64 {
+    if win <= lose {
+        return 100.0;
+    }
+    ((fold - lose) / (win - lose) * 100.0).clamp(0.0, 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Card;
+    use crate::deck::remaining_cards;
+    use crate::equity::game_combos;
+    use crate::game::Game;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn malmuth_harville() {
+        let equity = icm(&[50.0, 30.0, 20.0], &[50.0, 30.0, 20.0]);
+        assert!((equity.iter().sum::<f64>() - 100.0).abs() < 1e-9);
+        // first place 50% * 50, second 33.9% * 30, third 16.1% * 20
+        assert!((equity[0] - 38.392857142857146).abs() < 1e-9);
+        // chips are worth less the bigger the stack
+        assert!(equity[0] / 50.0 < equity[2] / 20.0);
+        // heads-up prize equity is linear in chips
+        let equity = icm(&[3.0, 1.0], &[10.0, 6.0]);
+        assert!((equity[0] - 9.0).abs() < 1e-9);
+        // a busted player finishes last
+        let equity = icm(&[0.0, 5.0, 5.0], &[50.0, 30.0, 20.0]);
+        assert!((equity[0] - 20.0).abs() < 1e-9);
+        // more payouts than players
+        let equity = icm(&[1.0, 1.0], &[50.0, 30.0, 20.0]);
+        assert_eq!(equity, vec![40.0, 40.0]);
+    }
+
+    #[test]
+    fn many_players() {
+        // only the sets of players taking the first two places are enumerated
+        let stacks = vec![100.0; 64];
+        let equity = icm(&stacks, &[50.0, 30.0, 20.0]);
+        assert_eq!(equity.len(), 64);
+        assert!(equity.iter().all(|e| (e - 100.0 / 64.0).abs() < 1e-9));
+
+        let mut stacks = stacks;
+        stacks[0] = 1000.0;
+        let equity = icm(&stacks, &[50.0, 30.0, 20.0]);
+        assert!((equity.iter().sum::<f64>() - 100.0).abs() < 1e-9);
+        assert!(equity[0] > 3.0 * equity[1]);
+    }
+
+    #[test]
+    fn sampled() {
+        let stacks = [50.0, 30.0, 20.0, 0.0];
+        let payouts = [50.0, 30.0, 15.0, 5.0];
+        let exact = icm(&stacks, &payouts);
+        let mut rng = StdRng::seed_from_u64(40);
+        let sampled = sample_icm(&stacks, &payouts, 20000, &mut rng);
+        for (e, s) in exact.iter().zip(sampled.iter()) {
+            assert!((e - s).abs() < 0.5, "{} {}", e, s);
+        }
+        assert!((sampled[3] - 5.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn large_field() {
+        // 200 equal stacks split the prizes evenly
+        let stacks = vec![1000.0; 200];
+        let payouts: Vec<f64> = (1..=20).map(|i| 100.0 / i as f64).collect();
+        let mut rng = StdRng::seed_from_u64(1);
+        let equity = sample_icm(&stacks, &payouts, 2000, &mut rng);
+        let total: f64 = payouts.iter().sum();
+        assert!((equity.iter().sum::<f64>() - total).abs() < 1e-6);
+        assert!(equity.iter().all(|e| *e < 3.0 * total / 200.0));
+    }
+
+    fn bubble() -> AllInCall {
+        // four left, three paid, hero covers the short stack's shove
+        AllInCall {
+            stacks: vec![40.0, 30.0, 20.0, 0.0],
+            payouts: vec![50.0, 30.0, 20.0],
+            hero: 0,
+            vilan: 3,
+            pot: 10.0,
+            to_call: 10.0,
+        }
+    }
+
+    #[test]
+    fn bubble_call() {
+        let spot = bubble();
+        let ev = spot.evaluate_pct(52.0).unwrap();
+        // even money needs 50% in chips, but busting costs more than doubling up gains
+        assert!(ev.required_equity > 50.0);
+        assert!(ev.call < ev.fold);
+        let ev = spot.evaluate_pct(80.0).unwrap();
+        assert!(ev.call > ev.fold);
+
+        // without payouts beyond first, prize equity is proportional to chips
+        let chips = AllInCall {
+            payouts: vec![100.0],
+            ..bubble()
+        };
+        let ev = chips.evaluate_pct(50.0).unwrap();
+        assert!((ev.required_equity - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn short_call() {
+        // hero can only call 5 of the 10, the rest goes back to vilan
+        let spot = AllInCall {
+            stacks: vec![5.0, 30.0, 20.0, 0.0],
+            pot: 10.0,
+            to_call: 10.0,
+            ..bubble()
+        };
+        let ev = spot.evaluate_pct(100.0).unwrap();
+        let expected = icm(&[10.0, 30.0, 20.0, 5.0], &spot.payouts)[0];
+        assert!((ev.call - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn with_equity() {
+        let hero = vec![Card::from("Ah").unwrap(), Card::from("Ad").unwrap()];
+        let vilan = vec![Card::from("Kh").unwrap(), Card::from("Kd").unwrap()];
+        let board: Vec<Card> = ["2c", "7s", "9h"]
+            .iter()
+            .map(|c| Card::from(c).unwrap())
+            .collect();
+        let deck = remaining_cards(&[&hero[..], &vilan[..], &board[..]].concat());
+        let equity = game_combos(Game::Holdem, hero, vilan, board, deck);
+
+        let spot = bubble();
+        let ev = spot.evaluate(&equity).unwrap();
+        let pct = spot.evaluate_pct(equity.equity()).unwrap();
+        // no ties between the two pairs on this board
+        assert!((ev.call - pct.call).abs() < 1e-9);
+        assert!(ev.call > ev.fold);
+
+        let wrong = AllInCall {
+            vilan: 0,
+            ..bubble()
+        };
+        assert!(wrong.evaluate(&equity).is_err());
+    }
+}
@@ -1,11 +1,37 @@
+pub mod batch;
+pub mod blockers;
+pub mod buckets;
 pub mod card;
+pub mod cfr;
+pub mod classify;
 pub mod cli;
 pub mod deck;
+pub mod equity;
 pub mod error;
+pub mod game;
 pub mod hand;
 pub mod hand_rank;
+pub mod hilo;
+pub mod history;
+pub mod icm;
+pub mod lowball;
+pub mod nuts;
+pub mod omaha;
+pub mod player;
+pub mod pot;
+pub mod potential;
 pub mod prelude;
+pub mod pushfold;
+pub mod range;
 pub mod raw_data;
+pub mod repl;
+pub mod replay;
+pub mod river;
+pub mod stats;
+pub mod stud;
+pub mod table;
+pub mod texture;
+pub mod tournament;
 
 use std::collections::HashMap;
 
@@ -20,14 +46,23 @@ pub trait Beats<Rhs: ?Sized = Self> {
     }
 }
 
+#[deprecated(note = "use `print_equity`, which also prints the equity")]
 pub fn print_combos(stats: HashMap<usize, usize>, num: usize, k: usize, len: usize) {
+    let mut wins = [0; 10];
+    for (rank, n) in stats {
+        if rank < wins.len() {
+            wins[rank] = n;
+        }
+    }
+    print_wins(&wins, num, k, len);
+}
+
+fn print_wins(stats: &[usize], num: usize, k: usize, len: usize) {
     println!("evaluated {} combinations for {}/{} cards", num, k, len);
     println!("-> hero wins with:");
 
-    for i in 0..10 {
-        let mut wins = 0;
-        if let Some((_rank, n)) = stats.get_key_value(&i) {
-            wins += n;
+    for (i, n) in stats.iter().enumerate() {
+        if *n > 0 {
             println!(
                 "{:>11}: {:>6.2}% ({})",
                 format!("{}", hand_rank::HandRank::from(i)),
@@ -38,6 +73,119 @@ pub fn print_combos(stats: HashMap<usize, usize>, num: usize, k: usize, len: usi
     }
 }
 
+/// Print the hands hero wins with and hero's equity
+pub fn print_equity(equity: &equity::Equity) {
+    print_wins(&equity.stats, equity.combos, equity.k, equity.deck);
+    println!(
+        "-> equity: {:.2}% (win {:.2}%, tie {:.2}%)",
+        equity.equity(),
+        equity.win_pct(),
+        equity.tie_pct()
+    );
+}
+
+/// The header line matching the records of `print_csv`
+pub const CSV_HEADER: &str = "hero,vilan,community_cards,combos,wins,ties,losses,equity,\
+HighCard,Pair,TwoPair,Trips,Straight,Flush,FullHouse,Quads,StraightFlush,RoyalFlush";
+
+/// Return a single csv record for `equity`; cards are written as in `Card::from`
+pub fn csv_record(equity: &equity::Equity) -> String {
+    let cards = |cards: &[card::Card]| cards.iter().map(|c| c.notation()).collect::<String>();
+    let mut record = format!(
+        "{},{},{},{},{},{},{},{:.4}",
+        cards(&equity.hero),
+        cards(&equity.vilan),
+        cards(&equity.community_cards),
+        equity.combos,
+        equity.wins,
+        equity.ties,
+        equity.losses,
+        equity.equity()
+    );
+    for n in equity.stats.iter() {
+        record.push_str(&format!(",{}", n));
+    }
+    record
+}
+
+pub fn print_csv(equity: &equity::Equity, header: bool) {
+    if header {
+        println!("{}", CSV_HEADER);
+    }
+    println!("{}", csv_record(equity));
+}
+
+#[cfg(feature = "serde")]
+pub fn print_json(equity: &equity::Equity) {
+    // serializing plain structs and arrays can't fail
+    println!("{}", serde_json::to_string(equity).unwrap());
+}
+
+pub fn print_hilo(equity: &hilo::HiLoEquity) {
+    println!(
+        "evaluated {} combinations for {}/{} cards",
+        equity.combos, equity.k, equity.deck
+    );
+    let pct = |n: usize| n as f64 * 100.0 / equity.combos.max(1) as f64;
+    println!(
+        "-> high: win {:.2}%, tie {:.2}%",
+        pct(equity.high_wins),
+        pct(equity.high_ties)
+    );
+    println!(
+        "->  low: win {:.2}%, tie {:.2}%, no low {:.2}%",
+        pct(equity.low_wins),
+        pct(equity.low_ties),
+        pct(equity.no_low)
+    );
+    println!(
+        "-> scoops {:.2}% (vilan {:.2}%), quartered {:.2}% (vilan {:.2}%)",
+        equity.scoop_pct(),
+        pct(equity.vilan_scoops),
+        equity.quartered_pct(),
+        pct(equity.vilan_quartered)
+    );
+    println!("-> equity: {:.2}%", equity.equity());
+}
+
+/// The header line matching the records of `print_hilo_csv`
+pub const HILO_CSV_HEADER: &str = "hero,vilan,community_cards,combos,equity,hero_scoops,\
+vilan_scoops,hero_quartered,vilan_quartered,high_wins,high_ties,low_wins,low_ties,no_low";
+
+/// Return a single csv record for a hi/lo `equity`
+pub fn hilo_csv_record(equity: &hilo::HiLoEquity) -> String {
+    let cards = |cards: &[card::Card]| cards.iter().map(|c| c.notation()).collect::<String>();
+    format!(
+        "{},{},{},{},{:.4},{},{},{},{},{},{},{},{},{}",
+        cards(&equity.hero),
+        cards(&equity.vilan),
+        cards(&equity.community_cards),
+        equity.combos,
+        equity.equity(),
+        equity.hero_scoops,
+        equity.vilan_scoops,
+        equity.hero_quartered,
+        equity.vilan_quartered,
+        equity.high_wins,
+        equity.high_ties,
+        equity.low_wins,
+        equity.low_ties,
+        equity.no_low
+    )
+}
+
+pub fn print_hilo_csv(equity: &hilo::HiLoEquity, header: bool) {
+    if header {
+        println!("{}", HILO_CSV_HEADER);
+    }
+    println!("{}", hilo_csv_record(equity));
+}
+
+#[cfg(feature = "serde")]
+pub fn print_hilo_json(equity: &hilo::HiLoEquity) {
+    println!("{}", serde_json::to_string(equity).unwrap());
+}
+
 pub fn print_result(
     ranks: &[[[usize; 4]; 13]; 2],
     hand_rank: &hand_rank::HandRank,
@@ -46,7 +194,7 @@ pub fn print_result(
     println!("{:?} vs {:?} ", hand_rank, other);
     for (rank, card_array) in ranks[0].iter().rev().enumerate() {
         for (idx, suit) in card_array.iter().enumerate() {
-            if *suit == 1 as usize {
+            if *suit == 1 {
                 print!(
                     "{} ",
                     card::Card::new(card::Rank::from(12 - rank), card::Suit::from(idx))
@@ -57,7 +205,7 @@ pub fn print_result(
     print!(" vs ");
     for (rank, card_array) in ranks[1].iter().rev().enumerate() {
         for (idx, suit) in card_array.iter().enumerate() {
-            if *suit == 1 as usize {
+            if *suit == 1 {
                 print!(
                     "{} ",
                     card::Card::new(card::Rank::from(12 - rank), card::Suit::from(idx))
@@ -65,5 +213,5 @@ pub fn print_result(
             }
         }
     }
-    println!("");
+    println!();
 }
@@ -0,0 +1,119 @@
+use crate::card::*;
+use crate::hand_rank::*;
+use crate::hilo::{low_value, Low};
+use crate::raw_data::*;
+
+use itertools::Itertools;
+
+/// Return the best A-5 lowball hand (as in Razz) out of 5 to 7 `cards`: Aces are low, straights
+/// and flushes don't count, pairs do.
+pub fn ace_to_five(cards: &[Card]) -> Low {
+    best_of_five(cards, |five| {
+        let mut num_values = [0; 13];
+        for card in five {
+            num_values[low_value(card.rank)] += 1;
+        }
+
+        // values ordered by how often they occur, then by value, e.g. [9, 9, 5, 3, 1]
+        let mut grouped: Vec<(usize, usize)> = (0..13)
+            .filter(|value| num_values[*value] > 0)
+            .map(|value| (num_values[value], value))
+            .collect();
+        grouped.sort_by(|a, b| b.cmp(a));
+
+        let category = match (grouped[0].0, grouped.get(1).map(|g| g.0)) {
+            (4, _) => 7,
+            (3, Some(2)) => 6,
+            (3, _) => 3,
+            (2, Some(2)) => 2,
+            (2, _) => 1,
+            _ => 0,
+        };
+        let values: Vec<usize> = grouped.iter().map(|(_, value)| *value).collect();
+        Low::new(category, &values)
+    })
+}
+
+/// Return the best 2-7 lowball hand (as in Triple Draw) out of 5 to 7 `cards`: Aces are high,
+/// straights, flushes and pairs count against the hand.
+pub fn deuce_to_seven(cards: &[Card]) -> Low {
+    best_of_five(cards, |five| {
+        let own = RawData::from_cards(five);
+        Low::from_strength(ranked_strength(
+            &own,
+            &RawData::new(&vec![]),
+            Ranking::AceHigh,
+        ))
+    })
+}
+
+/// the best `Low` of all five card combinations of `cards`
+fn best_of_five<F: Fn(&[Card]) -> Low>(cards: &[Card], eval: F) -> Low {
+    cards
+        .iter()
+        .copied()
+        .combinations(5.min(cards.len()))
+        .map(|five| eval(&five))
+        .max()
+        .unwrap_or(Low(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::parse_cards;
+
+    fn a5(expr: &str) -> Low {
+        ace_to_five(&parse_cards(expr).unwrap())
+    }
+
+    fn d27(expr: &str) -> Low {
+        deuce_to_seven(&parse_cards(expr).unwrap())
+    }
+
+    #[test]
+    fn ace_to_five_order() {
+        // the wheel is the nuts, even suited
+        assert!(a5("Ah2h3h4h5h") > a5("Ah2c3d4s6h"));
+        assert!(a5("6h4c3d2sAh") > a5("6h5c3d2sAh"));
+        assert!(a5("KhQcJd9s8h") > a5("AhAc2d3s4h"));
+        // pairs, two pairs, trips, full houses and quads get worse
+        assert!(a5("AhAc2d3s4h") > a5("AhAc2d2s4h"));
+        assert!(a5("AhAc2d2s4h") > a5("AhAcAd2s4h"));
+        assert!(a5("AhAcAd2s4h") > a5("AhAcAd2s2h"));
+        assert!(a5("AhAcAd2s2h") > a5("AhAcAdAs2h"));
+        assert!(a5("2h2c3d4s5h") > a5("3h3c2d4s5h"));
+    }
+
+    #[test]
+    fn razz() {
+        // best five out of seven
+        assert_eq!(a5("Ah2c3d4s5hKcKd"), a5("Ah2c3d4s5h"));
+        assert_eq!(a5("KhKcQdQs7h7c2d"), a5("KhQd7h7c2d"));
+        assert_eq!(
+            a5("8h7c4d3s2hKdQc").ranks(),
+            [Rank::Eight, Rank::Seven, Rank::Four, Rank::Three, Rank::Two]
+        );
+    }
+
+    #[test]
+    fn deuce_to_seven_order() {
+        // 75432 is the nuts, A2345 is ace high
+        assert!(d27("7h5c4d3s2h") > d27("7h6c4d3s2h"));
+        assert!(d27("7h5c4d3s2h") > d27("Ah2c3d4s5h"));
+        assert!(d27("Kh5c4d3s2h") > d27("Ah5c4d3s2h"));
+        // straights, flushes and pairs count against the hand
+        assert!(d27("7h6c5d4s3h") < d27("8h7c6d4s3h"));
+        assert!(d27("8h7h5h4h2h") < d27("Kh7c5d4s2h"));
+        assert!(d27("2h2c3d4s5h") < d27("Ah7c5d4s2h"));
+        // straights are worse than pairs, the wheel isn't a straight
+        assert!(d27("7h6c5d4s3h") < d27("2h2c3d4s5h"));
+        assert!(d27("Ah2c3d4s5h") > d27("2h2c3d4s5h"));
+    }
+
+    #[test]
+    fn seven_cards() {
+        assert_eq!(d27("7h5c4d3s2hAhAd"), d27("7h5c4d3s2h"));
+        assert_eq!(d27("8h7c6d5s4h3d2c"), d27("7c5s4h3d2c"));
+    }
+}
@@ -1,45 +1,604 @@
 use pkr::error::Result;
 use pkr::prelude::*;
 
-use itertools::Itertools;
-use std::collections::{HashMap, HashSet};
+use pkr::repl::Session;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
 use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
 use std::process;
 
-fn combos(hero: Vec<Card>, vilan: Vec<Card>, community_cards: Vec<Card>, deck: HashSet<Card>) {
-    // Stats
-    let mut stats: HashMap<usize, usize> = HashMap::with_capacity(10);
-    let mut num_combos = 0;
-    let k = 5 - community_cards.len();
-
-    let raw_hero = RawData::from_chain(hero.iter().chain(community_cards.iter()));
-    let raw_vilan = RawData::from_chain(vilan.iter().chain(community_cards.iter()));
-
-    for combo in deck.iter().combinations(k) {
-        let h = Hand::new(&raw_hero, &combo);
-        let v = Hand::new(&raw_vilan, &combo);
-
-        if h.rank > v.rank {
-            // print_result(&ranks, &hero.hand_rank, &vilan.hand_rank);
-            if let Some(count) = stats.get_mut(&usize::from(&h.rank)) {
-                *count += 1;
-            } else {
-                stats.insert(usize::from(&h.rank), 1);
-            }
-        } else if h.rank == v.rank {
-            // println!("{:?} vs {:?}", h.rank, v.rank)
+fn print_usage() {
+    println!("usage: <cmd> [NUM_ITERATIONS] <Holding> <Holding> [COMMUNITY_CARDS..] [OPTIONS]");
+    println!("       repl");
+    println!("       batch [FILE] [OPTIONS]");
+    println!(
+        "       stats [FILE] [--position sb,bb,ep,mp,co,btn] [--stake BB] [--from DAY] [--to DAY]"
+    );
+    println!("       icm <STACK,..> <PRIZE,..>");
+    println!("       texture <BOARD>");
+    println!("       classify <HOLDING> <BOARD>");
+    println!("       nuts <BOARD> [HOLDING]");
+    println!("       potential <HOLDING> <BOARD> [RANGE]");
+    println!("       distribution <HOLDING> <BOARD> [RANGE] [--bins N]");
+    println!("       buckets <BOARD> <K> [RANGE] [--bins N]");
+    println!("       blockers <HOLDING> <BOARD> <RANGE> [--value HAND]    value: HAND or better");
+    println!("       river <BOARD> <OOP_RANGE> <IP_RANGE> [--pot N] [--stack N] [--iterations N]");
+    println!("       simulate <AGENT,..> [--hands N] [--seed N]    agents: call|random|tag|equity");
+    println!(
+        "       tournament <AGENT,..> [--tournaments N] [--seed N] [--table-size N] [--level HANDS]"
+    );
+    println!(
+        "       pushfold <STACK_BB> [--ante BB] [--others STACK,..] [--payouts PRIZE,..] [--seed N]"
+    );
+    println!("       eval <DOWN/UP[/DOWN]> <DOWN/UP[/DOWN]> [DEAD_CARDS] --game stud|stud8");
+    println!();
+    println!("options: --format text|json|csv");
+    println!("         --game holdem|omaha|omaha8|shortdeck|shortdeck-trips|stud|stud8");
+    println!("         --value HAND   high-card|underpair|bottom-pair|middle-pair|top-pair|tptk|");
+    println!("                        overpair|two-pair|trips|set|straight|flush|full-house|");
+    println!("                        quads|straight-flush (default two-pair)");
+    println!("         --samples N    evaluate N random stud runouts instead of all of them");
+    println!("                        (default: all, or 100000 before fifth street),");
+    println!("                        or deal N boards per heads-up push/fold matchup instead");
+    println!("                        of using the precomputed equities,");
+    println!("                        or estimate ICM from N finishing orders (default: exact,");
+    println!("                        or 100000 for more than 20 players and 3 prizes),");
+    println!("                        or deal N random next cards for the hand potential,");
+    println!("                        or N runouts shared by all hands to bucket (default 1000),");
+    println!("                        or N runouts per equity agent decision (default 100)");
+}
+
+fn repl() -> Result<()> {
+    let mut session = Session::new();
+    let stdin = io::stdin();
+    print!("pkr> ");
+    io::stdout().flush().ok();
+    for line in stdin.lock().lines() {
+        let line = line.map_err(pkr::error::Error::Io)?;
+        match session.execute(&line) {
+            Ok(Some(out)) => println!("{}", out),
+            Ok(None) => break,
+            Err(err) => println!("error: {}", err),
         }
-        num_combos += 1;
+        print!("pkr> ");
+        io::stdout().flush().ok();
     }
-    print_combos(stats, num_combos, k, deck.len());
+    Ok(())
 }
 
-fn print_usage() {
-    println!("usage: <cmd> [NUM_ITERATIONS] <Holding> <Holding> [COMMUNITY_CARDS..]");
+fn print_formatted(equity: &Equity, format: Format) -> Result<()> {
+    match format {
+        Format::Text => print_equity(equity),
+        Format::Csv => print_csv(equity, true),
+        #[cfg(feature = "serde")]
+        Format::Json => print_json(equity),
+        #[cfg(not(feature = "serde"))]
+        Format::Json => return Err(pkr::error::Error::MissingFeature("serde")),
+    }
+    Ok(())
+}
+
+fn print_hilo_equity(equity: &HiLoEquity, format: Format) -> Result<()> {
+    match format {
+        Format::Text => print_hilo(equity),
+        Format::Csv => print_hilo_csv(equity, true),
+        #[cfg(feature = "serde")]
+        Format::Json => print_hilo_json(equity),
+        #[cfg(not(feature = "serde"))]
+        Format::Json => return Err(pkr::error::Error::MissingFeature("serde")),
+    }
+    Ok(())
+}
+
+/// Evaluate `<HERO> <VILAN> [DEAD_CARDS]` where both hands are given as `StudHand`s
+fn stud(game: Game, args: &[String], samples: Option<usize>, format: Format) -> Result<()> {
+    if args.len() < 2 || args.len() > 3 {
+        print_usage();
+        process::exit(1);
+    }
+    let table = StudTable {
+        hands: vec![StudHand::from(&args[0])?, StudHand::from(&args[1])?],
+        dead: match args.get(2) {
+            Some(expr) => parse_cards(expr)?,
+            None => vec![],
+        },
+    };
+    let deck = game.remaining_cards(&table.known())?;
+    let hero = table.hands[0].cards();
+    let vilan = table.hands[1].cards();
+    // before fifth street there are far too many runouts to evaluate all of them
+    let samples = match samples {
+        None if table.hands.iter().any(|hand| hand.len() < 5) => Some(100_000),
+        samples => samples,
+    };
+
+    let mut rng = rand::thread_rng();
+    match (game.is_split(), samples) {
+        (true, Some(n)) => print_hilo_equity(
+            &sample_stud_hilo_combos(game, hero, vilan, deck, n, &mut rng),
+            format,
+        ),
+        (true, None) => print_hilo_equity(&stud_hilo_combos(game, hero, vilan, deck), format),
+        (false, Some(n)) => print_formatted(
+            &sample_stud_combos(game, hero, vilan, deck, n, &mut rng),
+            format,
+        ),
+        (false, None) => print_formatted(&stud_combos(game, hero, vilan, deck), format),
+    }
+}
+
+/// Print the HUD stats of every player in a PokerStars hand history file, hands which don't
+/// parse are reported on stderr and skipped
+fn stats<R: BufRead>(input: R, filter: &Filter, format: Format) -> Result<()> {
+    let mut stats = Stats::new();
+    for (line, history) in HandHistories::new(input) {
+        match history.and_then(|history| stats.add(&history, filter)) {
+            Ok(()) => {}
+            Err(err) => eprintln!("line {}: {}", line, err),
+        }
+    }
+    match format {
+        Format::Text => println!("{}", stats),
+        Format::Csv => stats.write_csv(&mut io::stdout().lock())?,
+        #[cfg(feature = "serde")]
+        Format::Json => println!("{}", serde_json::to_string(&stats).unwrap()),
+        #[cfg(not(feature = "serde"))]
+        Format::Json => return Err(pkr::error::Error::MissingFeature("serde")),
+    }
+    Ok(())
+}
+
+fn take_filter(args: &mut Vec<String>) -> Result<Filter> {
+    let positions = match take_option(args, "--position")? {
+        Some(expr) => expr
+            .split(',')
+            .map(Position::from)
+            .collect::<Result<Vec<Position>>>()?,
+        None => vec![],
+    };
+    let big_blind = match take_option(args, "--stake")? {
+        Some(expr) => Some(parse_amount(&expr)?),
+        None => None,
+    };
+    Ok(Filter {
+        positions,
+        big_blind,
+        from: take_option(args, "--from")?,
+        to: take_option(args, "--to")?,
+    })
+}
+
+/// Parse a comma separated list of numbers, e.g. "50,30,20"
+fn parse_numbers(expr: &str) -> Result<Vec<f64>> {
+    expr.split(',')
+        .map(|n| n.trim().parse().map_err(|_| pkr::error::Error::ParseError))
+        .collect()
+}
+
+/// Solve the push/fold spot of two `stack` big blind stacks and print the ranges
+fn pushfold(args: &mut Vec<String>, samples: Option<usize>) -> Result<()> {
+    let ante = match take_option(args, "--ante")? {
+        Some(expr) => parse_numbers(&expr)?[0],
+        None => 0.0,
+    };
+    let others = match take_option(args, "--others")? {
+        Some(expr) => parse_numbers(&expr)?,
+        None => vec![],
+    };
+    let payouts = match take_option(args, "--payouts")? {
+        Some(expr) => parse_numbers(&expr)?,
+        None => vec![],
+    };
+    let seed = take_number(args, "--seed", 0)?;
+    if args.len() != 3 {
+        print_usage();
+        process::exit(1);
+    }
+    let stack: f64 = args[2].parse().map_err(|_| pkr::error::Error::ParseError)?;
+    if stack <= 1.0 + ante {
+        return Err(pkr::error::Error::ParseError);
+    }
+
+    let equities = match samples {
+        Some(samples) => PreflopEquities::sample(samples, &mut StdRng::seed_from_u64(seed)),
+        None => PreflopEquities::precomputed(),
+    };
+    let spot = PushFold {
+        small_blind: stack,
+        big_blind: stack,
+        ante,
+        others,
+        payouts,
+    };
+    let ranges = spot.solve(&equities, 1000);
+    println!("small blind push (%):");
+    println!("{}", grid(&ranges.push));
+    println!();
+    println!("big blind call (%):");
+    println!("{}", grid(&ranges.call));
+    Ok(())
+}
+
+/// Solve a river spot and print the out of position player's first decision per combo
+fn river(args: &mut Vec<String>) -> Result<()> {
+    let take = |args: &mut Vec<String>, name: &str, default: f64| -> Result<f64> {
+        match take_option(args, name)? {
+            Some(expr) => expr.parse().map_err(|_| pkr::error::Error::ParseError),
+            None => Ok(default),
+        }
+    };
+    let pot = take(args, "--pot", 100.0)?;
+    let stack = take(args, "--stack", 100.0)?;
+    let iterations = take(args, "--iterations", 500.0)? as usize;
+    if args.len() != 5 {
+        print_usage();
+        process::exit(1);
+    }
+    let board = parse_cards(&args[2])?;
+    let spot = RiverSpot::new(
+        board,
+        &Range::from(&args[3])?,
+        &Range::from(&args[4])?,
+        pot,
+        stack,
+    );
+    let mut solver = RiverSolver::new(spot)?;
+    solver.solve(iterations);
+
+    let [oop, ip] = solver.ev();
+    println!(
+        "exploitability: {:.3} ({:.2}% of the pot)",
+        solver.exploitability(),
+        solver.exploitability() * 100.0 / pot
+    );
+    println!("ev: oop {:.2}, ip {:.2}", oop, ip);
+    let actions: Vec<String> = solver.tree[0]
+        .actions
+        .iter()
+        .map(|action| match action {
+            RiverAction::Bet(to) => format!("bet {}", to),
+            action => format!("{:?}", action).to_lowercase(),
+        })
+        .collect();
+    println!();
+    println!("{:<6}{}", "", actions.join(" | "));
+    let frequencies: Vec<String> = solver
+        .frequencies(0)
+        .iter()
+        .map(|f| format!("{:.1}%", f * 100.0))
+        .collect();
+    println!("{:<6}{}", "all", frequencies.join(" | "));
+    let strategy = solver.strategy(0);
+    for (c, combo) in solver.combos(0).iter().enumerate() {
+        let line: Vec<String> = strategy
+            .iter()
+            .map(|p| format!("{:.1}%", p[c] * 100.0))
+            .collect();
+        println!(
+            "{:<6}{}",
+            format!("{}{}", combo[0].notation(), combo[1].notation()),
+            line.join(" | ")
+        );
+    }
+    Ok(())
+}
+
+/// Print the ten best hands on a board and where `holding` ranks
+fn nuts(board: &str, holding: Option<&String>) -> Result<()> {
+    let ranking = NutRanking::new(&parse_cards(board)?)?;
+    let cards = |cards: &[Card]| cards.iter().map(|c| c.notation()).collect::<String>();
+    for (i, (value, combos)) in ranking.tiers.iter().take(10).enumerate() {
+        let combos: Vec<String> = combos.iter().map(|c| cards(c)).collect();
+        println!(
+            "{:>2}. {:<13} {}",
+            i + 1,
+            HandRank::from(value.category()).to_string(),
+            combos.join(",")
+        );
+    }
+    if let Some(holding) = holding {
+        match ranking.position(&parse_cards(holding)?) {
+            Some(position) => println!("{}: {}", holding, position),
+            None => return Err(pkr::error::Error::DuplicateCard),
+        }
+    }
+    Ok(())
+}
+
+/// Print the hand strength and the potential over the next card
+fn potential(args: &[String], samples: Option<usize>) -> Result<()> {
+    let holding = parse_cards(&args[2])?;
+    let board = parse_cards(&args[3])?;
+    let range = match args.get(4) {
+        Some(expr) => Some(Range::from(expr)?),
+        None => None,
+    };
+    let cards = if board.len() < 5 { 1 } else { 0 };
+    let potential = match samples {
+        Some(samples) => sample_hand_potential(
+            &holding,
+            &board,
+            range.as_ref(),
+            cards,
+            samples,
+            &mut rand::thread_rng(),
+        )?,
+        None => hand_potential(&holding, &board, range.as_ref(), cards)?,
+    };
+    println!("HS:   {:.4}", potential.hs);
+    println!("PPot: {:.4}", potential.ppot);
+    println!("NPot: {:.4}", potential.npot);
+    println!("EHS:  {:.4}", potential.ehs());
+    println!("EHS2: {:.4}", potential.ehs2);
+    Ok(())
+}
+
+/// Write the equity histogram of a holding, or the buckets of every holding, as csv
+fn distribution(args: &mut Vec<String>, samples: Option<usize>) -> Result<()> {
+    let bins = match take_option(args, "--bins")? {
+        Some(n) => n.parse().map_err(|_| pkr::error::Error::ParseError)?,
+        None => 10,
+    };
+    if args.len() < 4 || args.len() > 5 || bins == 0 {
+        print_usage();
+        process::exit(1);
+    }
+    let range = match args.get(4) {
+        Some(expr) => Some(Range::from(expr)?),
+        None => None,
+    };
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+    let mut rng = rand::thread_rng();
+    if args[1] == "buckets" {
+        let k = args[3].parse().map_err(|_| pkr::error::Error::ParseError)?;
+        let board = parse_cards(&args[2])?;
+        let samples = samples.unwrap_or(1000);
+        let buckets = bucket_hands(&board, range.as_ref(), bins, k, samples, &mut rng)?;
+        return buckets.write_csv(&mut output);
+    }
+    let holding = parse_cards(&args[2])?;
+    let board = parse_cards(&args[3])?;
+    let histogram = match samples {
+        Some(samples) => {
+            sample_equity_distribution(&holding, &board, range.as_ref(), bins, samples, &mut rng)?
+        }
+        None => equity_distribution(&holding, &board, range.as_ref(), bins)?,
+    };
+    histogram.write_csv(&mut output)
+}
+
+/// The baseline agents named in `expr`, e.g. "tag,call,call"
+fn agents(expr: &str, seed: u64, samples: Option<usize>) -> Result<Vec<Box<dyn Player>>> {
+    let mut players: Vec<Box<dyn Player>> = vec![];
+    for (i, agent) in expr.split(',').enumerate() {
+        let seed = seed + i as u64 + 1;
+        players.push(match agent {
+            "call" => Box::new(CallingStation),
+            "random" => Box::new(RandomPlayer::new(seed)),
+            "tag" => Box::new(TightAggressive::default()),
+            "equity" => Box::new(EquityPlayer::new(65.0, samples.unwrap_or(100), seed)),
+            _ => return Err(pkr::error::Error::ParseError),
+        });
+    }
+    if players.len() < 2 {
+        return Err(pkr::error::Error::ParseError);
+    }
+    Ok(players)
+}
+
+fn take_number(args: &mut Vec<String>, name: &str, default: u64) -> Result<u64> {
+    match take_option(args, name)? {
+        Some(expr) => expr.parse().map_err(|_| pkr::error::Error::ParseError),
+        None => Ok(default),
+    }
+}
+
+/// Play hands between baseline agents and print their win rates
+fn simulate(args: &mut Vec<String>, samples: Option<usize>) -> Result<()> {
+    let hands = take_number(args, "--hands", 10000)? as usize;
+    let seed = take_number(args, "--seed", 0)?;
+    if args.len() != 3 {
+        print_usage();
+        process::exit(1);
+    }
+    let mut players = agents(&args[2], seed, samples)?;
+    let simulation = Simulation {
+        seed,
+        ..Simulation::default()
+    };
+    for (seat, result) in simulation.run(&mut players, hands)?.iter().enumerate() {
+        let (low, high) = result.confidence_interval(1.96);
+        println!(
+            "{:>2} {:<17} {:>9.2} bb/100  95% CI [{:.2}, {:.2}]",
+            seat,
+            result.name,
+            result.bb_per_100(),
+            low,
+            high
+        );
+    }
+    Ok(())
+}
+
+/// Play sit and gos, or multi-table tournaments for more than one table of entrants, and print
+/// every agent's return on investment and finishing places
+fn tournament(args: &mut Vec<String>, samples: Option<usize>) -> Result<()> {
+    let tournaments = take_number(args, "--tournaments", 100)? as usize;
+    let seed = take_number(args, "--seed", 0)?;
+    let table_size = take_number(args, "--table-size", 9)? as usize;
+    let level = take_number(args, "--level", 0)? as usize;
+    if args.len() != 3 {
+        print_usage();
+        process::exit(1);
+    }
+    let mut players = agents(&args[2], seed, samples)?;
+    let mut structure = if players.len() > table_size {
+        Structure::multi_table(players.len())
+    } else {
+        Structure::sit_and_go()
+    };
+    structure.table_size = table_size;
+    if level > 0 {
+        structure.levels = blind_schedule(level);
+    }
+    let paid = structure.payouts.len();
+    let tournament = Tournament { structure, seed };
+    for result in tournament.run(&mut players, tournaments)? {
+        let (low, high) = result.confidence_interval(1.96);
+        let places: Vec<String> = result
+            .distribution()
+            .iter()
+            .map(|p| format!("{:.1}%", p * 100.0))
+            .collect();
+        println!(
+            "{:<17} roi {:>7.2}%  95% CI [{:.2}, {:.2}]  itm {:.1}%  average place {:.2}",
+            result.name,
+            result.roi(),
+            low,
+            high,
+            result.in_the_money(paid) * 100.0,
+            result.average_place()
+        );
+        println!("{:<17} places {}", "", places.join(" "));
+    }
+    Ok(())
 }
 
 fn main() -> Result<()> {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+    let format = take_format(&mut args)?;
+    let game = take_game(&mut args)?;
+    let samples = match take_option(&mut args, "--samples")? {
+        Some(n) => Some(n.parse().map_err(|_| pkr::error::Error::ParseError)?),
+        None => None,
+    };
+    if args.get(1).map(String::as_str) == Some("repl") {
+        if args.len() != 2 {
+            print_usage();
+            process::exit(1);
+        }
+        return repl();
+    }
+    if args.get(1).map(String::as_str) == Some("batch") {
+        if args.len() > 3 {
+            print_usage();
+            process::exit(1);
+        }
+        let stdout = io::stdout();
+        let mut output = stdout.lock();
+        return match args.get(2) {
+            Some(path) => {
+                let file = File::open(path).map_err(pkr::error::Error::Io)?;
+                pkr::batch::run(BufReader::new(file), &mut output, format, game)
+            }
+            None => pkr::batch::run(io::stdin().lock(), &mut output, format, game),
+        };
+    }
+    if args.get(1).map(String::as_str) == Some("icm") {
+        if args.len() != 4 {
+            print_usage();
+            process::exit(1);
+        }
+        let stacks = parse_numbers(&args[2])?;
+        let payouts = parse_numbers(&args[3])?;
+        // the exact model takes too long for large fields paying many places
+        let samples = match samples {
+            None if stacks.len() > 20 && payouts.len() > 3 => Some(100_000),
+            samples => samples,
+        };
+        let equity = match samples {
+            Some(samples) => sample_icm(&stacks, &payouts, samples, &mut rand::thread_rng()),
+            None => icm(&stacks, &payouts),
+        };
+        for (stack, equity) in stacks.iter().zip(equity) {
+            println!("{:>12.2} {:>12.4}", stack, equity);
+        }
+        return Ok(());
+    }
+    if args.get(1).map(String::as_str) == Some("texture") {
+        if args.len() != 3 {
+            print_usage();
+            process::exit(1);
+        }
+        println!("{}", Texture::new(&parse_cards(&args[2])?)?);
+        return Ok(());
+    }
+    if args.get(1).map(String::as_str) == Some("nuts") {
+        if args.len() < 3 || args.len() > 4 {
+            print_usage();
+            process::exit(1);
+        }
+        return nuts(&args[2], args.get(3));
+    }
+    if matches!(
+        args.get(1).map(String::as_str),
+        Some("distribution" | "buckets")
+    ) {
+        return distribution(&mut args, samples);
+    }
+    if args.get(1).map(String::as_str) == Some("potential") {
+        if args.len() < 4 || args.len() > 5 {
+            print_usage();
+            process::exit(1);
+        }
+        return potential(&args, samples);
+    }
+    if args.get(1).map(String::as_str) == Some("blockers") {
+        let value = match take_option(&mut args, "--value")? {
+            Some(expr) => MadeHand::from(&expr)?,
+            None => MadeHand::TwoPair,
+        };
+        if args.len() != 5 {
+            print_usage();
+            process::exit(1);
+        }
+        let report = blockers(
+            &parse_cards(&args[2])?,
+            &parse_cards(&args[3])?,
+            &Range::from(&args[4])?,
+            value,
+        )?;
+        println!("{}", report);
+        return Ok(());
+    }
+    if args.get(1).map(String::as_str) == Some("classify") {
+        if args.len() != 4 {
+            print_usage();
+            process::exit(1);
+        }
+        println!(
+            "{}",
+            classify(&parse_cards(&args[2])?, &parse_cards(&args[3])?)?
+        );
+        return Ok(());
+    }
+    if args.get(1).map(String::as_str) == Some("river") {
+        return river(&mut args);
+    }
+    if args.get(1).map(String::as_str) == Some("simulate") {
+        return simulate(&mut args, samples);
+    }
+    if args.get(1).map(String::as_str) == Some("tournament") {
+        return tournament(&mut args, samples);
+    }
+    if args.get(1).map(String::as_str) == Some("pushfold") {
+        return pushfold(&mut args, samples);
+    }
+    if args.get(1).map(String::as_str) == Some("stats") {
+        let filter = take_filter(&mut args)?;
+        return match args.get(2) {
+            Some(path) => {
+                let file = File::open(path).map_err(pkr::error::Error::Io)?;
+                stats(BufReader::new(file), &filter, format)
+            }
+            None => stats(io::stdin().lock(), &filter, format),
+        };
+    }
     if args.len() < 4 {
         print_usage();
         process::exit(1);
@@ -48,11 +607,25 @@ fn main() -> Result<()> {
     let cmd = &args[1];
     let offset = if cmd == "rnd" { 2 } else { 1 };
 
-    let (hero, vilan, community_cards, deck) = get_cards(&args[offset..])?;
+    if cmd == "eval" && game.is_stud() {
+        return stud(game, &args[offset + 1..], samples, format);
+    }
+
+    let (hero, vilan, community_cards, _) = get_cards(&args[offset..])?;
+    let deck = game.remaining_cards(&[&hero[..], &vilan[..], &community_cards[..]].concat())?;
+
+    if !game.holding_size(hero.len()) || !game.holding_size(vilan.len()) {
+        print_usage();
+        process::exit(1);
+    }
 
     // println!("{:?} vs. {:?} {:?}", hero, vilan, community_cards);
-    if cmd == "eval" {
-        combos(hero, vilan, community_cards, deck);
+    if cmd == "eval" && game.is_split() {
+        let equity = hilo_combos(game, hero, vilan, community_cards, deck);
+        print_hilo_equity(&equity, format)?;
+    } else if cmd == "eval" {
+        let equity = game_combos(game, hero, vilan, community_cards, deck);
+        print_formatted(&equity, format)?;
     }
 
     // if cmd == "test" {
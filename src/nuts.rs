@@ -0,0 +1,192 @@
+use crate::card::*;
+use crate::deck::{check_duplicates, remaining_deck};
+use crate::error::{Error, Result};
+use crate::hand_rank::{strength, Strength};
+use crate::range::Combo;
+use crate::raw_data::RawData;
+use std::fmt;
+
+/// Every Hold'em holding possible on a board, ranked from the nuts downward
+#[derive(Clone, Debug, PartialEq)]
+pub struct NutRanking {
+    pub board: Vec<Card>,
+    /// holdings making the same hand grouped together, the strongest group first and the
+    /// holdings of a group higher card first
+    pub tiers: Vec<(Strength, Vec<Combo>)>,
+}
+
+/// Where a holding sits within a `NutRanking`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RelativeStrength {
+    /// 1 for the nuts, 2 for the second nuts and so on
+    pub tier: usize,
+    /// holdings which beat it, split with it or lose to it, leaving out the ones sharing a card
+    pub better: usize,
+    pub equal: usize,
+    pub worse: usize,
+}
+
+impl NutRanking {
+    /// Rank all holdings on a board of three to five cards
+    pub fn new(board: &[Card]) -> Result<Self> {
+        if board.len() < 3 || board.len() > 5 {
+            return Err(Error::ParseError);
+        }
+        check_duplicates(board)?;
+
+        let board_data = RawData::from_cards(board);
+        let deck = remaining_deck(board);
+        let mut made: Vec<(Strength, Combo)> = vec![];
+        for (i, first) in deck.iter().enumerate() {
+            for second in deck[i + 1..].iter() {
+                let combo = if first > second {
+                    [*first, *second]
+                } else {
+                    [*second, *first]
+                };
+                let value = strength(&RawData::from_cards(&combo), &board_data);
+                made.push((value, combo));
+            }
+        }
+        made.sort_unstable_by(|a, b| b.cmp(a));
+
+        let mut tiers: Vec<(Strength, Vec<Combo>)> = vec![];
+        for (value, combo) in made {
+            match tiers.last_mut() {
+                Some((last, combos)) if *last == value => combos.push(combo),
+                _ => tiers.push((value, vec![combo])),
+            }
+        }
+        Ok(NutRanking {
+            board: board.to_vec(),
+            tiers,
+        })
+    }
+
+    /// The number of holdings ranked
+    pub fn len(&self) -> usize {
+        self.tiers.iter().map(|(_, combos)| combos.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiers.is_empty()
+    }
+
+    /// The best hand and every holding making it
+    pub fn nuts(&self) -> (Strength, &[Combo]) {
+        let (value, combos) = &self.tiers[0];
+        (*value, combos)
+    }
+
+    /// Locate `holding` in either card order, `None` if it uses a board card
+    pub fn position(&self, holding: &[Card]) -> Option<RelativeStrength> {
+        if holding.len() != 2 || holding[0] == holding[1] {
+            return None;
+        }
+        let tier = self.tiers.iter().position(|(_, combos)| {
+            combos
+                .iter()
+                .any(|c| c.contains(&holding[0]) && c.contains(&holding[1]))
+        })?;
+        let live = |combos: &[Combo]| {
+            combos
+                .iter()
+                .filter(|c| !holding.contains(&c[0]) && !holding.contains(&c[1]))
+                .count()
+        };
+        Some(RelativeStrength {
+            tier: tier + 1,
+            better: self.tiers[..tier].iter().map(|(_, c)| live(c)).sum(),
+            equal: live(&self.tiers[tier].1),
+            worse: self.tiers[tier + 1..].iter().map(|(_, c)| live(c)).sum(),
+        })
+    }
+}
+
+impl RelativeStrength {
+    /// The share of the other holdings it beats in percent
+    pub fn beats_pct(&self) -> f64 {
+        let total = self.better + self.equal + self.worse;
+        if total == 0 {
+            return 0.0;
+        }
+        self.worse as f64 * 100.0 / total as f64
+    }
+}
+
+impl fmt::Display for RelativeStrength {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.tier == 1 {
+            write!(f, "the nuts")?;
+        } else {
+            let suffix = match (self.tier % 10, self.tier % 100) {
+                (_, 11..=13) => "th",
+                (1, _) => "st",
+                (2, _) => "nd",
+                (3, _) => "rd",
+                _ => "th",
+            };
+            write!(f, "{}{} nuts", self.tier, suffix)?;
+        }
+        write!(
+            f,
+            ", beats {:.1}% of combos ({} better, {} split)",
+            self.beats_pct(),
+            self.better,
+            self.equal
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::parse_cards;
+
+    #[test]
+    fn ranking() {
+        let ranking = NutRanking::new(&parse_cards("Kc7d2h").unwrap()).unwrap();
+        assert_eq!(ranking.len(), 49 * 48 / 2);
+        let (nuts, combos) = ranking.nuts();
+        assert_eq!(nuts.category(), 3);
+        assert_eq!(combos.len(), 3);
+        // the strongest first, no two tiers alike
+        assert!(ranking.tiers.windows(2).all(|w| w[0].0 > w[1].0));
+
+        let turn = NutRanking::new(&parse_cards("Kc7d2h5s").unwrap()).unwrap();
+        assert_eq!(turn.len(), 48 * 47 / 2);
+        let cards = parse_cards("Kc7d").unwrap();
+        assert!(NutRanking::new(&cards).is_err());
+        assert!(NutRanking::new(&[cards[0], cards[1], cards[0]]).is_err());
+    }
+
+    #[test]
+    fn position() {
+        let ranking = NutRanking::new(&parse_cards("Kc7d2h").unwrap()).unwrap();
+        let position = |holding: &str| ranking.position(&parse_cards(holding).unwrap());
+
+        let kings = position("KhKs").unwrap();
+        assert_eq!(kings.tier, 1);
+        // the other two sets of Kings share a King with this one
+        assert_eq!((kings.better, kings.equal), (0, 0));
+        assert!((kings.beats_pct() - 100.0).abs() < 1e-9);
+        assert_eq!(
+            kings.to_string(),
+            "the nuts, beats 100.0% of combos (0 better, 0 split)"
+        );
+
+        // behind the sets of Kings, Sevens and Deuces
+        let two_pair = position("7hKd").unwrap();
+        assert_eq!(two_pair.tier, 4);
+        assert!(two_pair.to_string().starts_with("4th nuts"));
+        assert_eq!(two_pair, position("Kd7h").unwrap());
+        // KhKs, 7s7c and all three sets of Deuces don't share a card
+        assert_eq!(two_pair.better, 1 + 1 + 3);
+
+        let total = 47 * 46 / 2;
+        let ace_king = position("AsKh").unwrap();
+        assert_eq!(ace_king.better + ace_king.equal + ace_king.worse, total);
+        assert!(ace_king.beats_pct() > 80.0);
+        assert!(position("KcAh").is_none());
+    }
+}
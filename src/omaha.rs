@@ -0,0 +1,64 @@
+use crate::card::*;
+use crate::hand_rank::*;
+use crate::raw_data::*;
+
+use itertools::Itertools;
+
+/// Return the `Strength` of the best hand using exactly two cards of `holding` and exactly three
+/// cards of `board`. Boards with less than three cards are evaluated with all of their cards.
+pub fn omaha_strength(holding: &[Card], board: &[Card]) -> Strength {
+    let mut best = Strength(0);
+    for own in holding.iter().combinations(2) {
+        let own = RawData::new(&own);
+        for other in board.iter().combinations(board.len().min(3)) {
+            let other = RawData::new(&other);
+            let strength = strength(&own, &other);
+            if strength > best {
+                best = strength;
+            }
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::parse_cards;
+
+    fn category(holding: &str, board: &str) -> usize {
+        omaha_strength(&parse_cards(holding).unwrap(), &parse_cards(board).unwrap()).category()
+    }
+
+    #[test]
+    fn two_from_hand() {
+        // four aces in hand are only a pair
+        assert_eq!(category("AhAdAcAs", "2c7d9hTsKc"), 1);
+        // one spade in hand doesn't make a flush
+        assert_eq!(category("AsKdQcJh", "2s7s9sTs3c"), 0);
+        // board straight doesn't count without two matching cards
+        assert_eq!(category("2c2d3h3s", "9hTsJcQdKc"), 1);
+    }
+
+    #[test]
+    fn three_from_board() {
+        // trips on board and a pair in hand make a full house
+        assert_eq!(category("QhQd4c5s", "7c7d7hTsKc"), 6);
+        // quads on board don't play
+        assert_eq!(category("AhKd4c5s", "7c7d7h7s2c"), 3);
+    }
+
+    #[test]
+    fn plo5_and_plo6() {
+        assert_eq!(category("AsKs2c3d4h", "QsJsTs5c6c"), 9);
+        assert_eq!(category("AdKc2c3d4h8h", "QsJsTh5c6c"), 4);
+    }
+
+    #[test]
+    fn kickers() {
+        let board = parse_cards("AcAd7h8s2c").unwrap();
+        let kings = omaha_strength(&parse_cards("KhKs3c4d").unwrap(), &board);
+        let queens = omaha_strength(&parse_cards("QhQs3c4d").unwrap(), &board);
+        assert!(kings > queens);
+    }
+}
@@ -0,0 +1,608 @@
+use crate::card::*;
+use crate::classify::{classify, Draw, MadeHand};
+use crate::deck::{remaining_deck, Deck};
+use crate::error::{Error, Result};
+use crate::game::Game;
+use crate::range::{HandClass, Range};
+use crate::table::{Action, HandState, LegalActions, Status, Street, Table};
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+
+/// What a player knows when it's their turn: everything but the other players' hole cards
+#[derive(Clone, Debug, PartialEq)]
+pub struct Observation<'a> {
+    pub seat: usize,
+    pub holding: &'a [Card],
+    pub board: &'a [Card],
+    pub street: Street,
+    pub button: usize,
+    pub big_blind: u64,
+    /// chips in the middle, bets of the current street included
+    pub pot: u64,
+    /// every seat's chips behind
+    pub stacks: Vec<u64>,
+    /// every seat's bet on the current street
+    pub street_bets: Vec<u64>,
+    /// the seats which didn't fold, this one included
+    pub in_hand: Vec<usize>,
+    pub history: &'a [(Street, usize, Action)],
+    pub legal: LegalActions,
+}
+
+impl<'a> Observation<'a> {
+    /// The view of the player to act, `None` once the hand is over
+    pub fn new(hand: &'a HandState) -> Option<Self> {
+        let seat = hand.to_act?;
+        Some(Observation {
+            seat,
+            holding: &hand.players[seat].holding,
+            board: &hand.board,
+            street: hand.street,
+            button: hand.button,
+            big_blind: hand.big_blind_size(),
+            pot: hand.pot(),
+            stacks: hand.players.iter().map(|p| p.stack).collect(),
+            street_bets: hand.players.iter().map(|p| p.street_bet).collect(),
+            in_hand: hand.in_hand(),
+            history: &hand.history,
+            legal: hand.legal_actions()?,
+        })
+    }
+
+    /// The chips a call adds, 0 if checking is possible
+    pub fn to_call(&self) -> u64 {
+        self.legal.call.unwrap_or(0)
+    }
+
+    /// Check if possible, fold otherwise
+    pub fn check_or_fold(&self) -> Action {
+        if self.legal.check {
+            Action::Check
+        } else {
+            Action::Fold
+        }
+    }
+
+    /// Check if possible, call otherwise
+    pub fn check_or_call(&self) -> Action {
+        if self.legal.check {
+            Action::Check
+        } else {
+            Action::Call
+        }
+    }
+
+    /// Bet or raise to `to`, moved into the legal limits, or just check or call if raising isn't
+    /// possible
+    pub fn bet_or_raise(&self, to: u64) -> Action {
+        match self.legal.raise {
+            Some((min, max)) if self.legal.is_bet => Action::Bet(to.clamp(min, max)),
+            Some((min, max)) => Action::Raise(to.clamp(min, max)),
+            None => self.check_or_call(),
+        }
+    }
+
+    /// Bet or raise the size of the pot after calling
+    pub fn pot_sized(&self) -> Action {
+        let current = self.street_bets.iter().copied().max().unwrap_or(0);
+        self.bet_or_raise(current + self.pot + self.to_call())
+    }
+}
+
+/// What a player learns once a hand is over: their own holding and the holdings shown down, but
+/// none of the folded or mucked ones
+#[derive(Clone, Debug, PartialEq)]
+pub struct HandSummary<'a> {
+    pub seat: usize,
+    pub holding: &'a [Card],
+    pub board: &'a [Card],
+    pub button: usize,
+    pub history: &'a [(Street, usize, Action)],
+    /// every seat's holding if it reached the showdown against another one
+    pub shown: Vec<Option<&'a [Card]>>,
+    /// the chips every seat won
+    pub payouts: Vec<u64>,
+    /// every seat's chips after the payouts
+    pub stacks: Vec<u64>,
+}
+
+impl<'a> HandSummary<'a> {
+    /// The view of the player in `seat`, `None` until the hand is over
+    pub fn new(hand: &'a HandState, seat: usize) -> Option<Self> {
+        if !hand.is_over() || seat >= hand.players.len() {
+            return None;
+        }
+        let in_hand = hand.in_hand();
+        let shown = (0..hand.players.len())
+            .map(|i| {
+                if in_hand.len() > 1 && in_hand.contains(&i) {
+                    Some(&hand.players[i].holding[..])
+                } else {
+                    None
+                }
+            })
+            .collect();
+        Some(HandSummary {
+            seat,
+            holding: &hand.players[seat].holding,
+            board: &hand.board,
+            button: hand.button,
+            history: &hand.history,
+            shown,
+            payouts: hand.payouts(),
+            stacks: hand.final_stacks(),
+        })
+    }
+}
+
+/// An agent playing No-Limit Hold'em
+pub trait Player {
+    fn name(&self) -> &str;
+
+    /// Choose one of the `observation.legal` actions
+    fn act(&mut self, observation: &Observation) -> Action;
+
+    /// Look at a finished hand, including the cards shown down
+    fn observe(&mut self, _summary: &HandSummary) {}
+}
+
+/// Never folds and never raises
+#[derive(Clone, Debug, Default)]
+pub struct CallingStation;
+
+impl Player for CallingStation {
+    fn name(&self) -> &str {
+        "calling station"
+    }
+
+    fn act(&mut self, observation: &Observation) -> Action {
+        observation.check_or_call()
+    }
+}
+
+/// Picks folds, checks, calls and raises of random size uniformly among the legal ones; never
+/// folds when it can check
+#[derive(Clone, Debug)]
+pub struct RandomPlayer {
+    rng: StdRng,
+}
+
+impl RandomPlayer {
+    pub fn new(seed: u64) -> Self {
+        RandomPlayer {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Player for RandomPlayer {
+    fn name(&self) -> &str {
+        "random"
+    }
+
+    fn act(&mut self, observation: &Observation) -> Action {
+        let legal = &observation.legal;
+        let mut actions = vec![observation.check_or_fold()];
+        if legal.call.is_some() {
+            actions.push(Action::Call);
+        }
+        if let Some((min, max)) = legal.raise {
+            let to = self.rng.gen_range(min, max + 1);
+            actions.push(observation.bet_or_raise(to));
+        }
+        *actions.choose(&mut self.rng).unwrap()
+    }
+}
+
+/// Raises a tight range of starting hand classes and calls a somewhat wider one preflop, then
+/// bets top pair or better, calls with middle pair or a strong draw and gives up otherwise
+#[derive(Clone, Debug)]
+pub struct TightAggressive {
+    pub raise: HashSet<HandClass>,
+    pub call: HashSet<HandClass>,
+}
+
+impl TightAggressive {
+    /// The classes of the `raise` and `call` ranges, e.g. "QQ+,AKs"
+    pub fn new(raise: &str, call: &str) -> Result<Self> {
+        let classes = |expr: &str| -> Result<HashSet<HandClass>> {
+            Ok(Range::from(expr)?
+                .combos
+                .iter()
+                .map(HandClass::of)
+                .collect())
+        };
+        Ok(TightAggressive {
+            raise: classes(raise)?,
+            call: classes(call)?,
+        })
+    }
+}
+
+impl Default for TightAggressive {
+    fn default() -> Self {
+        TightAggressive::new(
+            "77+,ATs+,KJs+,QJs,AQo+",
+            "22+,A2s+,KTs+,QTs+,JTs,T9s,98s,AJo+,KQo",
+        )
+        .unwrap()
+    }
+}
+
+impl Player for TightAggressive {
+    fn name(&self) -> &str {
+        "tight aggressive"
+    }
+
+    fn act(&mut self, observation: &Observation) -> Action {
+        let holding = observation.holding;
+        if observation.street == Street::Preflop {
+            let class = HandClass::of(&[holding[0], holding[1]]);
+            let raised = observation.to_call() + observation.street_bets[observation.seat]
+                > observation.big_blind;
+            return if self.raise.contains(&class) {
+                observation.bet_or_raise(3 * observation.street_bets.iter().max().unwrap())
+            } else if self.call.contains(&class) && !raised {
+                observation.check_or_call()
+            } else {
+                observation.check_or_fold()
+            };
+        }
+
+        let classification = match classify(holding, observation.board) {
+            Ok(classification) => classification,
+            Err(_) => return observation.check_or_fold(),
+        };
+        let strong_draw = classification.draws.iter().any(|draw| {
+            matches!(
+                draw,
+                Draw::FlushDraw | Draw::NutFlushDraw | Draw::OpenEnder | Draw::DoubleGutter
+            )
+        });
+        if classification.made >= (MadeHand::TopPair { top_kicker: false }) {
+            let current = observation.street_bets.iter().copied().max().unwrap_or(0);
+            observation.bet_or_raise(current + 2 * (observation.pot + observation.to_call()) / 3)
+        } else if classification.made >= MadeHand::MiddlePair || strong_draw {
+            observation.check_or_call()
+        } else {
+            observation.check_or_fold()
+        }
+    }
+}
+
+/// Estimates its equity against random hands of the remaining players and raises pot when it's
+/// above `raise`, calls when it's above the pot odds and folds otherwise
+#[derive(Clone, Debug)]
+pub struct EquityPlayer {
+    /// the equity in percent to raise with
+    pub raise: f64,
+    /// the runouts to estimate the equity from
+    pub samples: usize,
+    rng: StdRng,
+}
+
+impl EquityPlayer {
+    pub fn new(raise: f64, samples: usize, seed: u64) -> Self {
+        EquityPlayer {
+            raise,
+            samples,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Player for EquityPlayer {
+    fn name(&self) -> &str {
+        "equity"
+    }
+
+    fn act(&mut self, observation: &Observation) -> Action {
+        let opponents = observation.in_hand.len() - 1;
+        let equity = sample_equity(
+            observation.holding,
+            observation.board,
+            opponents,
+            self.samples,
+            &mut self.rng,
+        ) * 100.0;
+        let to_call = observation.to_call() as f64;
+        let pot_odds = to_call * 100.0 / (observation.pot as f64 + to_call);
+        if equity >= self.raise {
+            observation.pot_sized()
+        } else if equity >= pot_odds {
+            observation.check_or_call()
+        } else {
+            observation.check_or_fold()
+        }
+    }
+}
+
+/// Estimate the share of the pot `holding` wins against `opponents` random holdings from
+/// `samples` random deals of their cards and the rest of the board
+pub fn sample_equity<R: Rng>(
+    holding: &[Card],
+    board: &[Card],
+    opponents: usize,
+    samples: usize,
+    rng: &mut R,
+) -> f64 {
+    if samples == 0 {
+        return 0.0;
+    }
+    let deck = remaining_deck(&[holding, board].concat());
+    let game = Game::Holdem;
+    let mut won = 0.0;
+    let mut runout = board.to_vec();
+    for _ in 0..samples {
+        let cards: Vec<Card> = deck
+            .choose_multiple(rng, 2 * opponents + 5 - board.len())
+            .copied()
+            .collect();
+        runout.truncate(board.len());
+        runout.extend_from_slice(&cards[2 * opponents..]);
+        let hero = game.strength(holding, &runout);
+        let mut tied = 1;
+        let mut beaten = false;
+        for vilan in cards[..2 * opponents].chunks(2) {
+            let vilan = game.strength(vilan, &runout);
+            if vilan > hero {
+                beaten = true;
+                break;
+            }
+            if vilan == hero {
+                tied += 1;
+            }
+        }
+        if !beaten {
+            won += 1.0 / tied as f64;
+        }
+    }
+    won / samples as f64
+}
+
+/// Play out a hand at `table`, the player of seat `i` being `players[seats[i]]`, dealing from a
+/// deck shuffled with `rng`. Every player who was dealt in observes the `HandSummary` of their
+/// seat.
+pub fn play_hand<R: Rng>(
+    table: &Table,
+    players: &mut [Box<dyn Player>],
+    seats: &[usize],
+    rng: &mut R,
+) -> Result<HandState> {
+    if seats.len() != table.seats.len() || seats.iter().any(|p| *p >= players.len()) {
+        return Err(Error::IllegalAction);
+    }
+    let mut hand = table.start_hand(Deck::shuffled(rng))?;
+    while let Some(observation) = Observation::new(&hand) {
+        let action = players[seats[observation.seat]].act(&observation);
+        hand.act(action)?;
+    }
+    for (seat, player) in hand.players.iter().enumerate() {
+        if player.status != Status::SittingOut {
+            if let Some(summary) = HandSummary::new(&hand, seat) {
+                players[seats[seat]].observe(&summary);
+            }
+        }
+    }
+    Ok(hand)
+}
+
+/// A player's results over a simulation
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlayerResult {
+    pub name: String,
+    pub hands: usize,
+    /// big blinds won
+    pub won: f64,
+    /// the sum of the squared big blinds won per hand
+    pub won_squared: f64,
+}
+
+impl PlayerResult {
+    pub fn bb_per_100(&self) -> f64 {
+        if self.hands == 0 {
+            return 0.0;
+        }
+        self.won * 100.0 / self.hands as f64
+    }
+
+    /// The standard error of the win rate in big blinds per 100 hands
+    pub fn std_error(&self) -> f64 {
+        if self.hands < 2 {
+            return 0.0;
+        }
+        let n = self.hands as f64;
+        let mean = self.won / n;
+        let variance = (self.won_squared / n - mean * mean) * n / (n - 1.0);
+        100.0 * (variance.max(0.0) / n).sqrt()
+    }
+
+    /// The interval around the win rate within `z` standard errors, e.g. 1.96 for 95%
+    pub fn confidence_interval(&self, z: f64) -> (f64, f64) {
+        let (rate, error) = (self.bb_per_100(), self.std_error());
+        (rate - z * error, rate + z * error)
+    }
+}
+
+/// Hands between agents who start every hand with the same stack
+#[derive(Clone, Debug, PartialEq)]
+pub struct Simulation {
+    pub small_blind: u64,
+    pub big_blind: u64,
+    pub ante: u64,
+    pub stack: u64,
+    /// seeds the deck, the same seed deals the same cards
+    pub seed: u64,
+}
+
+impl Default for Simulation {
+    fn default() -> Self {
+        Simulation {
+            small_blind: 1,
+            big_blind: 2,
+            ante: 0,
+            stack: 200,
+            seed: 0,
+        }
+    }
+}
+
+impl Simulation {
+    /// Play `hands` hands with the button moving around, every player in their own seat
+    pub fn run(&self, players: &mut [Box<dyn Player>], hands: usize) -> Result<Vec<PlayerResult>> {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut table = Table::new(self.small_blind, self.big_blind, self.ante);
+        for player in players.iter() {
+            table.sit(player.name(), self.stack);
+        }
+        let seats: Vec<usize> = (0..players.len()).collect();
+        let mut results: Vec<PlayerResult> = players
+            .iter()
+            .map(|player| PlayerResult {
+                name: player.name().to_string(),
+                ..PlayerResult::default()
+            })
+            .collect();
+
+        for i in 0..hands {
+            for seat in table.seats.iter_mut() {
+                seat.stack = self.stack;
+            }
+            table.button = i % players.len();
+            let hand = play_hand(&table, players, &seats, &mut rng)?;
+            for (result, stack) in results.iter_mut().zip(hand.final_stacks()) {
+                let won = (stack as f64 - self.stack as f64) / self.big_blind as f64;
+                result.hands += 1;
+                result.won += won;
+                result.won_squared += won * won;
+            }
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn players() -> Vec<Box<dyn Player>> {
+        vec![
+            Box::new(CallingStation),
+            Box::new(RandomPlayer::new(1)),
+            Box::new(TightAggressive::default()),
+            Box::new(EquityPlayer::new(70.0, 50, 2)),
+        ]
+    }
+
+    #[test]
+    fn zero_sum() {
+        let simulation = Simulation::default();
+        let mut players = players();
+        let results = simulation.run(&mut players, 200).unwrap();
+        assert_eq!(results.len(), 4);
+        assert!(results.iter().all(|r| r.hands == 200));
+        let total: f64 = results.iter().map(|r| r.won).sum();
+        assert!(total.abs() < 1e-9);
+        assert_eq!(results[2].name, "tight aggressive");
+
+        // the same seed replays the same hands
+        let again = simulation.run(&mut self::players(), 200).unwrap();
+        assert_eq!(results, again);
+    }
+
+    #[test]
+    fn equity_beats_calling_station() {
+        let simulation = Simulation {
+            seed: 49,
+            ..Simulation::default()
+        };
+        let mut players: Vec<Box<dyn Player>> = vec![
+            Box::new(EquityPlayer::new(65.0, 100, 3)),
+            Box::new(CallingStation),
+        ];
+        let results = simulation.run(&mut players, 300).unwrap();
+        assert!(results[0].bb_per_100() > 0.0);
+        let (low, high) = results[0].confidence_interval(1.96);
+        assert!(low < results[0].bb_per_100() && results[0].bb_per_100() < high);
+    }
+
+    #[test]
+    fn confidence_interval() {
+        // won 1 and lost 1 big blind alternately
+        let result = PlayerResult {
+            name: String::from("p"),
+            hands: 100,
+            won: 0.0,
+            won_squared: 100.0,
+        };
+        assert_eq!(result.bb_per_100(), 0.0);
+        // the sample standard deviation is sqrt(100 / 99) per hand
+        assert!((result.std_error() - 100.0 * (1.0f64 / 99.0).sqrt()).abs() < 1e-9);
+        let (low, high) = result.confidence_interval(2.0);
+        assert!((low + high).abs() < 1e-9 && high > 20.0);
+    }
+
+    #[test]
+    fn equity_estimate() {
+        let mut rng = StdRng::seed_from_u64(49);
+        let aces = [Card::from("Ah").unwrap(), Card::from("As").unwrap()];
+        let equity = sample_equity(&aces, &[], 1, 2000, &mut rng);
+        // about 85% against a random hand
+        assert!((equity - 0.85).abs() < 0.03);
+        assert!(sample_equity(&aces, &[], 3, 2000, &mut rng) < equity);
+    }
+
+    #[test]
+    fn observation() {
+        let mut table = Table::new(1, 2, 0);
+        table.sit("a", 100);
+        table.sit("b", 100);
+        let mut rng = StdRng::seed_from_u64(49);
+        let hand = table.start_hand(Deck::shuffled(&mut rng)).unwrap();
+        let observation = Observation::new(&hand).unwrap();
+        assert_eq!(observation.seat, 0);
+        assert_eq!(observation.to_call(), 1);
+        assert_eq!(observation.pot_sized(), Action::Raise(6));
+        assert_eq!(observation.bet_or_raise(1000), Action::Raise(100));
+        assert_eq!(observation.check_or_fold(), Action::Fold);
+
+        let mut players: Vec<Box<dyn Player>> = vec![Box::new(CallingStation)];
+        assert!(play_hand(&table, &mut players, &[0, 1], &mut rng).is_err());
+    }
+
+    #[test]
+    fn summary() {
+        let mut table = Table::new(1, 2, 0);
+        table.sit("a", 100);
+        table.sit("b", 100);
+        table.sit("c", 100);
+        let mut rng = StdRng::seed_from_u64(49);
+        let mut hand = table.start_hand(Deck::shuffled(&mut rng)).unwrap();
+        assert!(HandSummary::new(&hand, 0).is_none());
+        // the button folds, the blinds check it down
+        hand.act(Action::Fold).unwrap();
+        while !hand.is_over() {
+            hand.act(Action::Check)
+                .or_else(|_| hand.act(Action::Call))
+                .unwrap();
+        }
+
+        let summary = HandSummary::new(&hand, 0).unwrap();
+        assert_eq!(summary.holding, &hand.players[0].holding[..]);
+        assert_eq!(summary.shown[0], None);
+        assert_eq!(summary.shown[1], Some(&hand.players[1].holding[..]));
+        assert_eq!(summary.shown[2], Some(&hand.players[2].holding[..]));
+        assert_eq!(summary.stacks.iter().sum::<u64>(), 300);
+
+        // a lone winner doesn't show
+        let mut hand = table.start_hand(Deck::shuffled(&mut rng)).unwrap();
+        hand.act(Action::Fold).unwrap();
+        hand.act(Action::Fold).unwrap();
+        let summary = HandSummary::new(&hand, 2).unwrap();
+        assert!(summary.shown.iter().all(Option::is_none));
+        assert_eq!(summary.payouts, vec![0, 0, 3]);
+    }
+}
@@ -0,0 +1,203 @@
+use crate::hand_rank::Strength;
+
+/// A main or side pot and the players who can win it
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pot {
+    pub amount: u64,
+    /// indices of the players who contributed the pot's full level and didn't fold
+    pub eligible: Vec<usize>,
+}
+
+/// Split the chips every player put into the middle into the main pot and the side pots.
+///
+/// A new pot starts above every level a player who didn't fold is all-in for, so `contributed`
+/// and `folded` are enough to tell them apart. Chips folded players put in above the highest
+/// level of a remaining player go into the last pot, or into a single pot if the remaining
+/// players didn't put in any chips. A pot with a single eligible player holds a bet nobody
+/// called.
+pub fn pots(contributed: &[u64], folded: &[bool]) -> Vec<Pot> {
+    let mut levels: Vec<u64> = (0..contributed.len())
+        .filter(|i| !folded[*i] && contributed[*i] > 0)
+        .map(|i| contributed[i])
+        .collect();
+    levels.sort_unstable();
+    levels.dedup();
+
+    let mut pots: Vec<Pot> = vec![];
+    let mut previous = 0;
+    for level in levels {
+        let amount = contributed
+            .iter()
+            .map(|c| (*c).min(level) - (*c).min(previous))
+            .sum();
+        let eligible = (0..contributed.len())
+            .filter(|i| !folded[*i] && contributed[*i] >= level)
+            .collect();
+        pots.push(Pot { amount, eligible });
+        previous = level;
+    }
+
+    let rest: u64 = contributed.iter().map(|c| c.saturating_sub(previous)).sum();
+    let remaining: Vec<usize> = (0..contributed.len()).filter(|i| !folded[*i]).collect();
+    match pots.last_mut() {
+        Some(pot) => pot.amount += rest,
+        None if rest > 0 && !remaining.is_empty() => pots.push(Pot {
+            amount: rest,
+            eligible: remaining,
+        }),
+        None => {}
+    }
+    pots
+}
+
+/// Award every pot to the eligible players with the best `Strength`.
+///
+/// A tied pot is split evenly, the odd chips go one at a time to the tied players closest to the
+/// left of the `button`. `strengths` of players who aren't eligible for any pot are ignored.
+pub fn payouts(pots: &[Pot], strengths: &[Strength], button: usize) -> Vec<u64> {
+    let n = strengths.len();
+    let mut payouts = vec![0; n];
+    for pot in pots {
+        let best = pot.eligible.iter().map(|i| strengths[*i]).max();
+        let winners: Vec<usize> = (1..=n)
+            .map(|i| (button + i) % n)
+            .filter(|i| pot.eligible.contains(i) && Some(strengths[*i]) == best)
+            .collect();
+        if winners.is_empty() {
+            continue;
+        }
+
+        let share = pot.amount / winners.len() as u64;
+        let odd = (pot.amount % winners.len() as u64) as usize;
+        for (i, winner) in winners.iter().enumerate() {
+            payouts[*winner] += share + if i < odd { 1 } else { 0 };
+        }
+    }
+    payouts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn single_pot() {
+        let pots = pots(&[10, 10, 10], &[false, false, false]);
+        assert_eq!(
+            pots,
+            vec![Pot {
+                amount: 30,
+                eligible: vec![0, 1, 2]
+            }]
+        );
+    }
+
+    #[test]
+    fn side_pots() {
+        // player 0 is all-in for 20, player 1 for 50, player 2 covers, player 3 folded after 30
+        let pots = pots(&[20, 50, 80, 30], &[false, false, false, true]);
+        assert_eq!(pots.len(), 3);
+        assert_eq!(pots[0].amount, 80);
+        assert_eq!(pots[0].eligible, vec![0, 1, 2]);
+        assert_eq!(pots[1].amount, 70);
+        assert_eq!(pots[1].eligible, vec![1, 2]);
+        // the uncalled 30 go back to player 2
+        assert_eq!(pots[2].amount, 30);
+        assert_eq!(pots[2].eligible, vec![2]);
+
+        // the short stack has the best hand, then player 2
+        let payouts = payouts(
+            &pots,
+            &[Strength(3), Strength(1), Strength(2), Strength(9)],
+            0,
+        );
+        assert_eq!(payouts, vec![80, 0, 100, 0]);
+    }
+
+    #[test]
+    fn odd_chips() {
+        let pots = pots(&[5, 5, 5, 2], &[false, false, false, true]);
+        // 17 chips split three ways, the extra chips go to the first winners left of the button
+        let payouts = payouts(&pots, &[Strength(1); 4], 1);
+        assert_eq!(payouts, vec![6, 5, 6, 0]);
+    }
+
+    #[test]
+    fn everybody_folded() {
+        let pots = pots(&[1, 2, 6], &[true, true, false]);
+        assert_eq!(pots.len(), 1);
+        assert_eq!(payouts(&pots, &[Strength(0); 3], 0), vec![0, 0, 9]);
+
+        // the player left didn't put in a chip
+        assert_eq!(
+            super::pots(&[0, 2, 1], &[false, true, true]),
+            vec![Pot {
+                amount: 3,
+                eligible: vec![0]
+            }]
+        );
+    }
+
+    fn spot() -> impl Strategy<Value = (Vec<u64>, Vec<bool>, Vec<u32>, usize)> {
+        (2..10usize).prop_flat_map(|n| {
+            (
+                prop::collection::vec(0..1000u64, n),
+                prop::collection::vec(any::<bool>(), n),
+                prop::collection::vec(0..4u32, n),
+                0..n,
+            )
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn chips_are_conserved((mut contributed, mut folded, strengths, button) in spot()) {
+            // somebody has to be left to win the pot
+            folded[button] = false;
+            contributed[button] = contributed[button].max(1);
+            let strengths: Vec<Strength> = strengths.into_iter().map(Strength).collect();
+            let pots = pots(&contributed, &folded);
+            let payouts = payouts(&pots, &strengths, button);
+
+            prop_assert_eq!(
+                pots.iter().map(|pot| pot.amount).sum::<u64>(),
+                contributed.iter().sum::<u64>()
+            );
+            prop_assert_eq!(payouts.iter().sum::<u64>(), contributed.iter().sum::<u64>());
+            let top = (0..contributed.len())
+                .filter(|i| !folded[*i])
+                .map(|i| contributed[i])
+                .max()
+                .unwrap();
+            for (i, payout) in payouts.iter().enumerate() {
+                // a covered player wins at most what they matched from every other player
+                let matched: u64 = contributed.iter().map(|c| (*c).min(contributed[i])).sum();
+                if folded[i] {
+                    prop_assert_eq!(*payout, 0);
+                } else if contributed[i] < top {
+                    prop_assert!(*payout <= matched);
+                }
+            }
+        }
+
+        #[test]
+        fn pots_hold_every_chip((contributed, mut folded, _, button) in spot()) {
+            // the players left may not have put in any chips
+            folded[button] = false;
+            let pots = pots(&contributed, &folded);
+            prop_assert_eq!(
+                pots.iter().map(|pot| pot.amount).sum::<u64>(),
+                contributed.iter().sum::<u64>()
+            );
+        }
+
+        #[test]
+        fn pots_are_contested((contributed, folded, _, _) in spot()) {
+            for pot in pots(&contributed, &folded) {
+                prop_assert!(!pot.eligible.is_empty());
+                prop_assert!(pot.eligible.iter().all(|i| !folded[*i]));
+            }
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use crate::card::*;
+use crate::deck::{check_duplicates, remaining_deck};
+use crate::error::{Error, Result};
+use crate::game::Game;
+use crate::range::{Combo, Range};
+
+use itertools::Itertools;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+const AHEAD: usize = 0;
+const TIED: usize = 1;
+const BEHIND: usize = 2;
+
+/// The classic hand strength metrics of a Hold'em holding against an opponent's range
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HandPotential {
+    /// hand strength: the share of opponent combos hero is ahead of now, ties counting half
+    pub hs: f64,
+    /// positive potential: the chance to get ahead when behind (or tied) now
+    pub ppot: f64,
+    /// negative potential: the chance to fall behind when ahead (or tied) now
+    pub npot: f64,
+    /// the expected square of the hand strength after the next cards
+    pub ehs2: f64,
+}
+
+impl HandPotential {
+    /// Effective hand strength: the chance to be ahead after the next cards,
+    /// `HS * (1 - NPot) + (1 - HS) * PPot`
+    pub fn ehs(&self) -> f64 {
+        self.hs * (1.0 - self.npot) + (1.0 - self.hs) * self.ppot
+    }
+}
+
+fn validate(holding: &[Card], board: &[Card], cards: usize) -> Result<()> {
+    if holding.len() != 2 || board.len() < 3 || board.len() + cards > 5 {
+        return Err(Error::ParseError);
+    }
+    check_duplicates(&[holding, board].concat())
+}
+
+/// The opponent's combos which don't collide with hero's holding or the board, every possible
+/// one if there is no `range`
+fn opponents(holding: &[Card], board: &[Card], range: Option<&Range>) -> Vec<Combo> {
+    let dead = [holding, board].concat();
+    match range {
+        Some(range) => range.without(&dead),
+        None => {
+            let deck = remaining_deck(&dead);
+            deck.iter()
+                .tuple_combinations()
+                .map(|(a, b)| [*a, *b])
+                .collect()
+        }
+    }
+}
+
+fn status(hero: &[Card], vilan: &[Card], board: &[Card]) -> usize {
+    let game = Game::Holdem;
+    match game.strength(hero, board).cmp(&game.strength(vilan, board)) {
+        std::cmp::Ordering::Greater => AHEAD,
+        std::cmp::Ordering::Equal => TIED,
+        std::cmp::Ordering::Less => BEHIND,
+    }
+}
+
+/// The share of `range` (every combo if `None`) which `holding` beats on a flop, turn or river,
+/// ties counting half
+pub fn hand_strength(holding: &[Card], board: &[Card], range: Option<&Range>) -> Result<f64> {
+    validate(holding, board, 0)?;
+    let mut counts = [0.0; 3];
+    for vilan in opponents(holding, board, range).iter() {
+        counts[status(holding, vilan, board)] += 1.0;
+    }
+    Ok(strength_of(&counts))
+}
+
+fn strength_of(counts: &[f64; 3]) -> f64 {
+    let total: f64 = counts.iter().sum();
+    if total == 0.0 {
+        return 0.0;
+    }
+    (counts[AHEAD] + counts[TIED] / 2.0) / total
+}
+
+/// Accumulates the transitions between being ahead, tied and behind now and after a runout
+struct Potential {
+    current: Vec<(Combo, usize)>,
+    transitions: [[f64; 3]; 3],
+    ehs2: f64,
+    runouts: usize,
+}
+
+impl Potential {
+    fn new(holding: &[Card], board: &[Card], range: Option<&Range>) -> Self {
+        let current = opponents(holding, board, range)
+            .into_iter()
+            .map(|vilan| (vilan, status(holding, &vilan, board)))
+            .collect();
+        Potential {
+            current,
+            transitions: [[0.0; 3]; 3],
+            ehs2: 0.0,
+            runouts: 0,
+        }
+    }
+
+    fn add(&mut self, holding: &[Card], board: &[Card]) {
+        let mut counts = [0.0; 3];
+        for (vilan, now) in self.current.iter() {
+            if board.contains(&vilan[0]) || board.contains(&vilan[1]) {
+                continue;
+            }
+            let after = status(holding, vilan, board);
+            self.transitions[*now][after] += 1.0;
+            counts[after] += 1.0;
+        }
+        // runouts which remove every combo of the range can't happen
+        if counts.iter().sum::<f64>() > 0.0 {
+            self.ehs2 += strength_of(&counts).powi(2);
+            self.runouts += 1;
+        }
+    }
+
+    fn finish(&self) -> HandPotential {
+        let t = &self.transitions;
+        let total = |now: usize| t[now].iter().sum::<f64>();
+        let ratio = |numerator: f64, denominator: f64| {
+            if denominator > 0.0 {
+                numerator / denominator
+            } else {
+                0.0
+            }
+        };
+        let mut counts = [0.0; 3];
+        for (_, now) in self.current.iter() {
+            counts[*now] += 1.0;
+        }
+        HandPotential {
+            hs: strength_of(&counts),
+            ppot: ratio(
+                t[BEHIND][AHEAD] + t[BEHIND][TIED] / 2.0 + t[TIED][AHEAD] / 2.0,
+                total(BEHIND) + total(TIED) / 2.0,
+            ),
+            npot: ratio(
+                t[AHEAD][BEHIND] + t[TIED][BEHIND] / 2.0 + t[AHEAD][TIED] / 2.0,
+                total(AHEAD) + total(TIED) / 2.0,
+            ),
+            ehs2: ratio(self.ehs2, self.runouts as f64),
+        }
+    }
+}
+
+/// Enumerate every runout of `cards` cards and every combo of `range` (every possible one if
+/// `None`) to compute `holding`'s hand strength and potential on the `board`.
+///
+/// With two cards to come on the flop this takes about a million evaluations, see
+/// `sample_hand_potential` for an approximation.
+pub fn hand_potential(
+    holding: &[Card],
+    board: &[Card],
+    range: Option<&Range>,
+    cards: usize,
+) -> Result<HandPotential> {
+    validate(holding, board, cards)?;
+    let mut potential = Potential::new(holding, board, range);
+    let deck = remaining_deck(&[holding, board].concat());
+    let mut runout = board.to_vec();
+    for cards in deck.iter().combinations(cards) {
+        runout.truncate(board.len());
+        runout.extend(cards.into_iter().copied());
+        potential.add(holding, &runout);
+    }
+    Ok(potential.finish())
+}
+
+/// Like `hand_potential`, but only evaluate `samples` random runouts of `cards` cards. The
+/// current hand strength is still exact.
+pub fn sample_hand_potential<R: Rng>(
+    holding: &[Card],
+    board: &[Card],
+    range: Option<&Range>,
+    cards: usize,
+    samples: usize,
+    rng: &mut R,
+) -> Result<HandPotential> {
+    validate(holding, board, cards)?;
+    let mut potential = Potential::new(holding, board, range);
+    let deck = remaining_deck(&[holding, board].concat());
+    let mut runout = board.to_vec();
+    for _ in 0..samples {
+        runout.truncate(board.len());
+        runout.extend(deck.choose_multiple(rng, cards).copied());
+        potential.add(holding, &runout);
+    }
+    Ok(potential.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::parse_cards;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn cards(expr: &str) -> Vec<Card> {
+        parse_cards(expr).unwrap()
+    }
+
+    #[test]
+    fn strength() {
+        // the nuts and the worst hand on the river
+        let board = cards("AhKd8c7s3h");
+        assert!((hand_strength(&cards("AsAd"), &board, None).unwrap() - 1.0).abs() < 1e-9);
+        let range = Range::from("QQ,JJ").unwrap();
+        let hs = hand_strength(&cards("AsQd"), &board, Some(&range)).unwrap();
+        assert!((hs - 1.0).abs() < 1e-9);
+        let hs = hand_strength(&cards("3c5d"), &cards("AhKd7c"), Some(&range)).unwrap();
+        assert!(hs.abs() < 1e-9);
+        assert!(hand_strength(&cards("AhQd"), &board, None).is_err());
+    }
+
+    #[test]
+    fn potential() {
+        // a flush draw behind an overpair on the turn: 9 of 44 cards win
+        let range = Range::from("AdAc").unwrap();
+        let potential =
+            hand_potential(&cards("8h7h"), &cards("KhTh2c3d"), Some(&range), 1).unwrap();
+        assert!(potential.hs.abs() < 1e-9);
+        assert!((potential.ppot - 9.0 / 44.0).abs() < 1e-9);
+        assert!((potential.ehs() - 9.0 / 44.0).abs() < 1e-9);
+        assert!((potential.ehs2 - 9.0 / 44.0).abs() < 1e-9);
+
+        // the overpair's view
+        let range = Range::from("8h7h").unwrap();
+        let potential =
+            hand_potential(&cards("AdAc"), &cards("KhTh2c3d"), Some(&range), 1).unwrap();
+        assert!((potential.hs - 1.0).abs() < 1e-9);
+        assert!((potential.npot - 9.0 / 44.0).abs() < 1e-9);
+        assert!(potential.ppot.abs() < 1e-9);
+
+        // nothing to come on the river
+        let potential = hand_potential(&cards("AdAc"), &cards("KhTh2c3d4s"), None, 0).unwrap();
+        assert_eq!(potential.ppot, 0.0);
+        assert!((potential.ehs2 - potential.hs.powi(2)).abs() < 1e-9);
+        assert!(hand_potential(&cards("AdAc"), &cards("KhTh2c3d"), None, 2).is_err());
+    }
+
+    #[test]
+    fn sampled() {
+        let (holding, board) = (cards("9h8h"), cards("Th7c2h"));
+        let range = Range::from("TT+,AK,AT,KT").unwrap();
+        let exact = hand_potential(&holding, &board, Some(&range), 1).unwrap();
+        let mut rng = StdRng::seed_from_u64(47);
+        let sampled =
+            sample_hand_potential(&holding, &board, Some(&range), 1, 2000, &mut rng).unwrap();
+        assert_eq!(exact.hs, sampled.hs);
+        assert!((exact.ppot - sampled.ppot).abs() < 0.03);
+        assert!((exact.ehs2 - sampled.ehs2).abs() < 0.03);
+        // an open ender with a flush draw improves a lot
+        assert!(exact.ehs() > exact.hs + 0.2);
+    }
+}
@@ -1,9 +1,48 @@
+pub use crate::blockers::*;
+pub use crate::buckets::*;
 pub use crate::card::*;
+pub use crate::cfr::kuhn::*;
+pub use crate::cfr::leduc::*;
+pub use crate::cfr::*;
+pub use crate::classify::*;
 pub use crate::cli::*;
+pub use crate::csv_record;
 pub use crate::deck::*;
+pub use crate::equity::*;
+pub use crate::game::*;
 pub use crate::hand::*;
 pub use crate::hand_rank::*;
+pub use crate::hilo::*;
+pub use crate::hilo_csv_record;
+pub use crate::history::*;
+pub use crate::icm::*;
+pub use crate::lowball::*;
+pub use crate::nuts::*;
+pub use crate::omaha::*;
+pub use crate::player::*;
+pub use crate::pot::*;
+pub use crate::potential::*;
+#[allow(deprecated)]
 pub use crate::print_combos;
+pub use crate::print_csv;
+pub use crate::print_equity;
+pub use crate::print_hilo;
+pub use crate::print_hilo_csv;
+#[cfg(feature = "serde")]
+pub use crate::print_hilo_json;
+#[cfg(feature = "serde")]
+pub use crate::print_json;
 pub use crate::print_result;
+pub use crate::pushfold::*;
+pub use crate::range::*;
 pub use crate::raw_data::*;
+pub use crate::replay::*;
+pub use crate::river::*;
+pub use crate::stats::*;
+pub use crate::stud::*;
+pub use crate::table::*;
+pub use crate::texture::*;
+pub use crate::tournament::*;
 pub use crate::Beats;
+pub use crate::CSV_HEADER;
+pub use crate::HILO_CSV_HEADER;
@@ -0,0 +1,470 @@
+use crate::card::*;
+use crate::deck::Deck;
+use crate::error::{Error, Result};
+use crate::hand_rank::strength;
+use crate::icm::icm;
+use crate::range::{Combo, HandClass};
+use crate::raw_data::RawData;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use std::cmp::Ordering;
+use std::io::{BufRead, Write};
+
+/// The equities of `PreflopEquities::precomputed`
+const PRECOMPUTED: &str = include_str!("preflop_equities.csv");
+
+/// All-in equities of every starting hand class against every other before the flop
+#[derive(Clone, Debug, PartialEq)]
+pub struct PreflopEquities {
+    /// equity of the row class against the column class, 169x169 indexed by `HandClass::index`
+    equity: Vec<f64>,
+    /// number of combo pairs of the two classes which don't share a card
+    weight: Vec<f64>,
+}
+
+impl PreflopEquities {
+    /// Estimate every matchup from `samples` deals, see `sample_matchup`
+    pub fn sample<R: Rng>(samples: usize, rng: &mut R) -> Self {
+        let mut equity = vec![0.5; 169 * 169];
+        let mut weight = vec![0.0; 169 * 169];
+        let classes = HandClass::all();
+        for (a, hero) in classes.iter().enumerate() {
+            for (b, vilan) in classes.iter().enumerate().skip(a) {
+                let (eq, pairs) = sample_matchup(*hero, *vilan, samples, rng);
+                weight[a * 169 + b] = pairs as f64;
+                weight[b * 169 + a] = pairs as f64;
+                equity[a * 169 + b] = eq;
+                equity[b * 169 + a] = 1.0 - eq;
+            }
+        }
+        PreflopEquities { equity, weight }
+    }
+
+    /// Every matchup estimated from 20000 deals, sampled with
+    /// `PreflopEquities::sample(20000, &mut StdRng::seed_from_u64(39))`; the standard error is
+    /// below 0.4% equity
+    pub fn precomputed() -> Self {
+        // the table is part of the crate and always parses
+        Self::read(PRECOMPUTED.as_bytes()).unwrap()
+    }
+
+    /// Read equities written by `write`; the weights are counted again
+    pub fn read<R: BufRead>(input: R) -> Result<Self> {
+        let mut equity = vec![0.5; 169 * 169];
+        let mut weight = vec![0.0; 169 * 169];
+        let classes = HandClass::all();
+        let mut lines = input.lines();
+        for (a, hero) in classes.iter().enumerate() {
+            let line = lines.next().ok_or(Error::ParseError)?.map_err(Error::Io)?;
+            let row: Vec<&str> = line.trim().split(',').collect();
+            if row.len() != 169 - a {
+                return Err(Error::ParseError);
+            }
+            for ((b, vilan), expr) in classes.iter().enumerate().skip(a).zip(row) {
+                let eq: f64 = expr.parse().map_err(|_| Error::ParseError)?;
+                if !(0.0..=1.0).contains(&eq) {
+                    return Err(Error::ParseError);
+                }
+                let pairs = pairs(*hero, *vilan).len() as f64;
+                weight[a * 169 + b] = pairs;
+                weight[b * 169 + a] = pairs;
+                equity[b * 169 + a] = 1.0 - eq;
+                equity[a * 169 + b] = eq;
+            }
+        }
+        Ok(PreflopEquities { equity, weight })
+    }
+
+    /// Write the equities as 169 lines of comma separated values, the line of every class holding
+    /// its equity against itself and every class after it in `HandClass::index` order
+    pub fn write<W: Write>(&self, output: &mut W) -> Result<()> {
+        for a in 0..169 {
+            let row: Vec<String> = (a..169)
+                .map(|b| format!("{:.4}", self.equity[a * 169 + b]))
+                .collect();
+            writeln!(output, "{}", row.join(",")).map_err(Error::Io)?;
+        }
+        Ok(())
+    }
+
+    /// The equity of `hero` against `vilan` between 0 and 1
+    pub fn equity(&self, hero: HandClass, vilan: HandClass) -> f64 {
+        self.equity[hero.index() * 169 + vilan.index()]
+    }
+
+    /// The number of ways `hero` and `vilan` can be dealt together
+    pub fn weight(&self, hero: HandClass, vilan: HandClass) -> f64 {
+        self.weight[hero.index() * 169 + vilan.index()]
+    }
+}
+
+/// Estimate the all-in equity of `hero` against `vilan` from `samples` random deals, each one a
+/// random pair of combos of the two classes and a random board; ties count as half a win.
+/// Returns the equity between 0 and 1, 0.5 without samples, and the number of combo pairs which
+/// don't share a card.
+pub fn sample_matchup<R: Rng>(
+    hero: HandClass,
+    vilan: HandClass,
+    samples: usize,
+    rng: &mut R,
+) -> (f64, usize) {
+    let pairs = pairs(hero, vilan);
+    if pairs.is_empty() || samples == 0 {
+        return (0.5, pairs.len());
+    }
+
+    let deck = Deck::new();
+    let mut won = 0.0;
+    let mut board: Vec<Card> = Vec::with_capacity(5);
+    for _ in 0..samples {
+        let (h, v) = pairs.choose(rng).unwrap();
+        board.clear();
+        while board.len() < 5 {
+            let card = deck.cards[rng.gen_range(0, 52)];
+            if !h.contains(&card) && !v.contains(&card) && !board.contains(&card) {
+                board.push(card);
+            }
+        }
+        let community = RawData::from_cards(&board);
+        let hero = strength(&RawData::from_cards(h), &community);
+        let vilan = strength(&RawData::from_cards(v), &community);
+        won += match hero.cmp(&vilan) {
+            Ordering::Greater => 1.0,
+            Ordering::Equal => 0.5,
+            Ordering::Less => 0.0,
+        };
+    }
+    (won / samples as f64, pairs.len())
+}
+
+/// The pairs of combos of `hero` and `vilan` which don't share a card
+fn pairs(hero: HandClass, vilan: HandClass) -> Vec<(Combo, Combo)> {
+    hero.combos()
+        .into_iter()
+        .flat_map(|h| vilan.combos().into_iter().map(move |v| (h, v)))
+        .filter(|(h, v)| !h.iter().any(|card| v.contains(card)))
+        .collect()
+}
+
+/// A heads-up push/fold spot: everybody folded to the small blind, who either shoves or folds,
+/// and the big blind calls or folds.
+///
+/// Only the last two players act; spots with three or more players still to act, e.g. the
+/// button shoving into both blinds, aren't solved.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PushFold {
+    /// the small blind's stack in big blinds before posting
+    pub small_blind: f64,
+    /// the big blind's stack in big blinds before posting
+    pub big_blind: f64,
+    /// the ante every player posts, in big blinds
+    pub ante: f64,
+    /// stacks of the players who already folded, after their antes; they only matter with
+    /// `payouts`
+    pub others: Vec<f64>,
+    /// the prizes still to be paid, first place first; empty to play for chips
+    pub payouts: Vec<f64>,
+}
+
+/// Push and call frequencies between 0 and 1 indexed by `HandClass::index`
+#[derive(Clone, Debug, PartialEq)]
+pub struct PushFoldRanges {
+    pub push: Vec<f64>,
+    pub call: Vec<f64>,
+}
+
+/// What each player ends up with in chips or prize equity after every outcome of the hand
+#[derive(Copy, Clone, Debug)]
+struct Outcomes {
+    /// the small blind folds
+    fold: [f64; 2],
+    /// the big blind folds to the shove
+    steal: [f64; 2],
+    /// the small blind wins the all-in
+    win: [f64; 2],
+    /// the big blind wins the all-in
+    lose: [f64; 2],
+}
+
+impl PushFold {
+    fn outcomes(&self) -> Outcomes {
+        let (sb, bb, ante) = (self.small_blind, self.big_blind, self.ante);
+        let dead = ante * self.others.len() as f64;
+        let effective = sb.min(bb);
+        let pot = 2.0 * effective + dead;
+        let value = |sb: f64, bb: f64| -> [f64; 2] {
+            if self.payouts.is_empty() {
+                return [sb, bb];
+            }
+            let stacks: Vec<f64> = [sb, bb].iter().chain(self.others.iter()).copied().collect();
+            let equity = icm(&stacks, &self.payouts);
+            [equity[0], equity[1]]
+        };
+        Outcomes {
+            fold: value(sb - 0.5 - ante, bb + 0.5 + ante + dead),
+            steal: value(sb + 1.0 + ante + dead, bb - 1.0 - ante),
+            win: value(sb - effective + pot, bb - effective),
+            lose: value(sb - effective, bb - effective + pot),
+        }
+    }
+
+    /// Approximate the Nash equilibrium with `iterations` rounds of fictitious play: both players
+    /// best respond to the other's average strategy so far.
+    ///
+    /// With `payouts` the players maximize their prize equity under the Malmuth-Harville model,
+    /// otherwise their chips. A split pot is valued like half a win and half a loss.
+    pub fn solve(&self, equities: &PreflopEquities, iterations: usize) -> PushFoldRanges {
+        let outcomes = self.outcomes();
+        let mut ranges = PushFoldRanges {
+            push: vec![1.0; 169],
+            call: vec![0.0; 169],
+        };
+        for t in 1..=iterations {
+            let step = 1.0 / (t + 1) as f64;
+            let (call, _) = best_call(&outcomes, equities, &ranges.push);
+            for (avg, br) in ranges.call.iter_mut().zip(call) {
+                *avg += (br - *avg) * step;
+            }
+            let (push, _) = best_push(&outcomes, equities, &ranges.call);
+            for (avg, br) in ranges.push.iter_mut().zip(push) {
+                *avg += (br - *avg) * step;
+            }
+        }
+        ranges
+    }
+
+    /// The expected chips or prize equity of the small blind and the big blind if both play
+    /// `ranges`
+    pub fn ev(&self, equities: &PreflopEquities, ranges: &PushFoldRanges) -> [f64; 2] {
+        let outcomes = self.outcomes();
+        let mut ev = [0.0; 2];
+        let mut total = 0.0;
+        for (hero, push) in ranges.push.iter().enumerate() {
+            for (vilan, call) in ranges.call.iter().enumerate() {
+                let w = equities.weight[hero * 169 + vilan];
+                let eq = equities.equity[hero * 169 + vilan];
+                for (player, ev) in ev.iter_mut().enumerate() {
+                    let called = eq * outcomes.win[player] + (1.0 - eq) * outcomes.lose[player];
+                    *ev += w
+                        * ((1.0 - push) * outcomes.fold[player]
+                            + push * (call * called + (1.0 - call) * outcomes.steal[player]));
+                }
+                total += w;
+            }
+        }
+        [ev[0] / total, ev[1] / total]
+    }
+
+    /// How much the small blind and the big blind could gain in total by deviating from
+    /// `ranges`, 0 at the equilibrium
+    pub fn exploitability(&self, equities: &PreflopEquities, ranges: &PushFoldRanges) -> f64 {
+        let outcomes = self.outcomes();
+        let ev = self.ev(equities, ranges);
+        let (_, push) = best_push(&outcomes, equities, &ranges.call);
+        let (_, call) = best_call(&outcomes, equities, &ranges.push);
+        push - ev[0] + call - ev[1]
+    }
+}
+
+/// The small blind's best pure strategy against `call` and its value
+fn best_push(outcomes: &Outcomes, equities: &PreflopEquities, call: &[f64]) -> (Vec<f64>, f64) {
+    let mut push = vec![0.0; 169];
+    let (mut value, mut total) = (0.0, 0.0);
+    for (hero, push) in push.iter_mut().enumerate() {
+        let (mut shove, mut weight) = (0.0, 0.0);
+        for (vilan, c) in call.iter().enumerate() {
+            let w = equities.weight[hero * 169 + vilan];
+            let eq = equities.equity[hero * 169 + vilan];
+            let called = eq * outcomes.win[0] + (1.0 - eq) * outcomes.lose[0];
+            shove += w * (c * called + (1.0 - c) * outcomes.steal[0]);
+            weight += w;
+        }
+        let fold = weight * outcomes.fold[0];
+        if shove > fold {
+            *push = 1.0;
+        }
+        value += shove.max(fold);
+        total += weight;
+    }
+    (push, value / total)
+}
+
+/// The big blind's best pure strategy against `push` and its value
+fn best_call(outcomes: &Outcomes, equities: &PreflopEquities, push: &[f64]) -> (Vec<f64>, f64) {
+    let mut call = vec![0.0; 169];
+    let (mut value, mut total) = (0.0, 0.0);
+    for (hero, call) in call.iter_mut().enumerate() {
+        let (mut walk, mut calling, mut folding, mut weight) = (0.0, 0.0, 0.0, 0.0);
+        for (vilan, p) in push.iter().enumerate() {
+            let w = equities.weight[vilan * 169 + hero];
+            // the equity of the small blind's hand
+            let eq = equities.equity[vilan * 169 + hero];
+            walk += w * (1.0 - p) * outcomes.fold[1];
+            calling += w * p * (eq * outcomes.win[1] + (1.0 - eq) * outcomes.lose[1]);
+            folding += w * p * outcomes.steal[1];
+            weight += w;
+        }
+        if calling > folding {
+            *call = 1.0;
+        }
+        value += walk + calling.max(folding);
+        total += weight;
+    }
+    (call, value / total)
+}
+
+/// Render a 13x13 grid of frequencies in percent, Aces in the top left corner, suited hands
+/// above the diagonal and offsuit hands below it
+pub fn grid(frequencies: &[f64]) -> String {
+    let ranks = "AKQJT98765432";
+    let mut out = String::from("  ");
+    for rank in ranks.chars() {
+        out.push_str(&format!("{:>4}", rank));
+    }
+    for (row, rank) in ranks.chars().enumerate() {
+        out.push('\n');
+        out.push(rank);
+        out.push(' ');
+        for col in 0..13 {
+            let frequency = frequencies[row * 13 + col];
+            out.push_str(&format!("{:>4.0}", frequency * 100.0));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::sync::OnceLock;
+
+    fn class(expr: &str) -> HandClass {
+        HandClass::from(expr).unwrap()
+    }
+
+    /// a rough table, sampled once for all tests
+    fn equities() -> &'static PreflopEquities {
+        static EQUITIES: OnceLock<PreflopEquities> = OnceLock::new();
+        EQUITIES.get_or_init(|| PreflopEquities::sample(12, &mut StdRng::seed_from_u64(39)))
+    }
+
+    #[test]
+    fn weights() {
+        let equities = PreflopEquities::sample(0, &mut StdRng::seed_from_u64(0));
+        assert_eq!(equities.weight(class("AA"), class("KK")), 36.0);
+        assert_eq!(equities.weight(class("AA"), class("AA")), 6.0);
+        assert_eq!(equities.weight(class("AKs"), class("AKo")), 4.0 * 6.0);
+        let total: f64 = HandClass::all()
+            .into_iter()
+            .flat_map(|a| HandClass::all().into_iter().map(move |b| (a, b)))
+            .map(|(a, b)| equities.weight(a, b))
+            .sum();
+        assert_eq!(total, 1326.0 * 1225.0);
+    }
+
+    #[test]
+    fn sampled_equities() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let (aa, pairs) = sample_matchup(class("AA"), class("72o"), 2000, &mut rng);
+        assert_eq!(pairs, 72);
+        assert!(aa > 0.84 && aa < 0.91, "{}", aa);
+        // QQ is a small favourite against AKo
+        let (flip, _) = sample_matchup(class("QQ"), class("AKo"), 2000, &mut rng);
+        assert!(flip > 0.52 && flip < 0.6, "{}", flip);
+
+        let equities = equities();
+        let sum =
+            equities.equity(class("AKo"), class("QQ")) + equities.equity(class("QQ"), class("AKo"));
+        assert!((sum - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn precomputed() {
+        let equities = PreflopEquities::precomputed();
+        let aa = equities.equity(class("AA"), class("72o"));
+        assert!(aa > 0.86 && aa < 0.89, "{}", aa);
+        let flip = equities.equity(class("QQ"), class("AKo"));
+        assert!(flip > 0.55 && flip < 0.58, "{}", flip);
+        assert!((equities.equity(class("AKs"), class("AKs")) - 0.5).abs() < 0.01);
+        assert_eq!(equities.weight(class("AA"), class("KK")), 36.0);
+
+        let mut csv = vec![];
+        equities.write(&mut csv).unwrap();
+        assert_eq!(PreflopEquities::read(&csv[..]).unwrap(), equities);
+        assert!(PreflopEquities::read(&csv[..1000]).is_err());
+    }
+
+    #[test]
+    fn heads_up_nash() {
+        let equities = equities();
+        let spot = PushFold {
+            small_blind: 10.0,
+            big_blind: 10.0,
+            ..PushFold::default()
+        };
+        let ranges = spot.solve(equities, 300);
+        assert!(ranges.push[class("AA").index()] > 0.99);
+        assert!(ranges.call[class("AA").index()] > 0.99);
+        assert!(ranges.call[class("72o").index()] < 0.01);
+
+        // around 60% of hands are shoved and 35% called at 10 big blinds
+        let share = |frequencies: &[f64]| {
+            HandClass::all()
+                .iter()
+                .map(|c| frequencies[c.index()] * c.combos().len() as f64)
+                .sum::<f64>()
+                / 1326.0
+        };
+        let push = share(&ranges.push);
+        let call = share(&ranges.call);
+        assert!(push > 0.45 && push < 0.75, "{}", push);
+        assert!(call > 0.25 && call < 0.5, "{}", call);
+        assert!(spot.exploitability(equities, &ranges) < 0.05);
+
+        // shallower stacks shove wider
+        let short = PushFold {
+            small_blind: 3.0,
+            big_blind: 3.0,
+            ..PushFold::default()
+        };
+        let short = short.solve(equities, 300);
+        assert!(share(&short.push) > push);
+    }
+
+    #[test]
+    fn bubble() {
+        let equities = equities();
+        let chips = PushFold {
+            small_blind: 10.0,
+            big_blind: 10.0,
+            others: vec![5.0],
+            ..PushFold::default()
+        };
+        let icm = PushFold {
+            payouts: vec![50.0, 30.0],
+            ..chips.clone()
+        };
+        let chips = chips.solve(equities, 300);
+        let icm = icm.solve(equities, 300);
+        // calling off on the bubble costs more than it wins
+        let calls = |ranges: &PushFoldRanges| ranges.call.iter().sum::<f64>();
+        assert!(calls(&icm) < calls(&chips));
+    }
+
+    #[test]
+    fn grid_layout() {
+        let mut frequencies = vec![0.0; 169];
+        frequencies[class("AKs").index()] = 1.0;
+        frequencies[class("72o").index()] = 0.5;
+        let grid = grid(&frequencies);
+        let lines: Vec<&str> = grid.lines().collect();
+        assert_eq!(lines.len(), 14);
+        assert!(lines[0].trim_start().starts_with("A   K"));
+        assert_eq!(&lines[1][..10], "A    0 100");
+        assert!(lines[13].ends_with("  50   0   0   0   0   0"));
+    }
+}
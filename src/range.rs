@@ -0,0 +1,359 @@
+use crate::card::*;
+use crate::error::{Error, Result};
+
+/// A two card holding, higher `Card` first
+pub type Combo = [Card; 2];
+
+/// A set of holdings, e.g. parsed from "QQ+,AKs,ATo-A8o,AhKh"
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Range {
+    pub combos: Vec<Combo>,
+}
+
+impl Range {
+    /// Parse a comma separated list of
+    ///
+    /// - specific holdings: `AhKh`
+    /// - pocket pairs: `QQ`, `QQ+`, `QQ-99`
+    /// - unpaired hands: `AK`, `AKs`, `AKo`, `ATs+`, `A9s-A6s` (the ends of a span in either order)
+    pub fn from(expr: &str) -> Result<Self> {
+        let mut range = Range::default();
+        for token in expr.split(',').map(str::trim) {
+            if token.is_empty() {
+                return Err(Error::ParseError);
+            }
+            for combo in parse_token(token)? {
+                range.add(combo);
+            }
+        }
+        Ok(range)
+    }
+
+    /// Add `combo` unless it is already part of the `Range`
+    pub fn add(&mut self, combo: Combo) {
+        let combo = if combo[0] > combo[1] {
+            combo
+        } else {
+            [combo[1], combo[0]]
+        };
+        if !self.combos.contains(&combo) {
+            self.combos.push(combo);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.combos.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.combos.is_empty()
+    }
+
+    /// Return the combos which don't contain any of the `dead` cards
+    pub fn without(&self, dead: &[Card]) -> Vec<Combo> {
+        self.combos
+            .iter()
+            .filter(|combo| !dead.contains(&combo[0]) && !dead.contains(&combo[1]))
+            .copied()
+            .collect()
+    }
+}
+
+/// One of the 169 starting hand classes, e.g. "AKs", "QQ" or "72o"
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct HandClass {
+    pub high: Rank,
+    pub low: Rank,
+    /// always `false` for pocket pairs
+    pub suited: bool,
+}
+
+impl HandClass {
+    /// Parse a class like "QQ", "AKs" or "AKo", an unpaired class needs its suitedness
+    pub fn from(expr: &str) -> Result<Self> {
+        let ((high, low), kind) = parse_class(expr)?;
+        if high != low && kind == Kind::Any {
+            return Err(Error::ParseError);
+        }
+        Ok(HandClass {
+            high,
+            low,
+            suited: kind == Kind::Suited,
+        })
+    }
+
+    /// The class of a two card holding
+    pub fn of(combo: &Combo) -> Self {
+        let (high, low) = if combo[0].rank >= combo[1].rank {
+            (combo[0].rank, combo[1].rank)
+        } else {
+            (combo[1].rank, combo[0].rank)
+        };
+        HandClass {
+            high,
+            low,
+            suited: high != low && combo[0].suit == combo[1].suit,
+        }
+    }
+
+    /// All 169 classes ordered like `index`
+    pub fn all() -> Vec<HandClass> {
+        (0..169).map(HandClass::from_index).collect()
+    }
+
+    /// The position in a 13x13 grid with Aces in the top left corner, read row by row: pairs on
+    /// the diagonal, suited hands above and offsuit hands below it
+    pub fn index(&self) -> usize {
+        let (high, low) = (12 - self.high as usize, 12 - self.low as usize);
+        if self.suited {
+            high * 13 + low
+        } else {
+            low * 13 + high
+        }
+    }
+
+    /// The class at grid position `index`, see `index`
+    pub fn from_index(index: usize) -> Self {
+        let (row, col) = (index / 13, index % 13);
+        HandClass {
+            high: Rank::from(12 - row.min(col)),
+            low: Rank::from(12 - row.max(col)),
+            suited: row < col,
+        }
+    }
+
+    /// All 6, 4 or 12 combos of the class
+    pub fn combos(&self) -> Vec<Combo> {
+        let kind = match (self.high == self.low, self.suited) {
+            (true, _) => Kind::Any,
+            (false, true) => Kind::Suited,
+            (false, false) => Kind::Offsuit,
+        };
+        class_combos(self.high, self.low, kind)
+    }
+
+    pub fn notation(&self) -> String {
+        match (self.high == self.low, self.suited) {
+            (true, _) => format!("{}{}", self.high, self.low),
+            (false, true) => format!("{}{}s", self.high, self.low),
+            (false, false) => format!("{}{}o", self.high, self.low),
+        }
+    }
+}
+
+/// Which suit combinations of two ranks a token refers to
+#[derive(Copy, Clone, PartialEq)]
+enum Kind {
+    Any,
+    Suited,
+    Offsuit,
+}
+
+fn parse_token(token: &str) -> Result<Vec<Combo>> {
+    if !token.is_ascii() {
+        return Err(Error::ParseError);
+    }
+
+    // specific holding, e.g. AhKh
+    if token.len() == 4 && token[1..2].chars().all(char::is_lowercase) {
+        let first = Card::from(&token[..2])?;
+        let second = Card::from(&token[2..])?;
+        if first == second {
+            return Err(Error::DuplicateCard);
+        }
+        return Ok(vec![[first, second]]);
+    }
+
+    // span of classes, e.g. QQ-99 or A9s-A6s
+    if let Some(idx) = token.find('-') {
+        let (high, kind) = parse_class(&token[..idx])?;
+        let (low, other_kind) = parse_class(&token[idx + 1..])?;
+        if kind != other_kind || (high.0 == high.1) != (low.0 == low.1) {
+            return Err(Error::ParseError);
+        }
+        // either end may come first, e.g. A6s-A9s
+        let (high, low) = if (high.0 as usize, high.1 as usize) < (low.0 as usize, low.1 as usize) {
+            (low, high)
+        } else {
+            (high, low)
+        };
+
+        let mut combos = vec![];
+        if high.0 == high.1 {
+            for rank in low.0 as usize..=high.0 as usize {
+                combos.extend(class_combos(Rank::from(rank), Rank::from(rank), kind));
+            }
+        } else {
+            if high.0 != low.0 {
+                return Err(Error::ParseError);
+            }
+            for rank in low.1 as usize..=high.1 as usize {
+                combos.extend(class_combos(high.0, Rank::from(rank), kind));
+            }
+        }
+        return Ok(combos);
+    }
+
+    // class with an optional '+', e.g. QQ+ or ATs+
+    let (expr, plus) = match token.strip_suffix('+') {
+        Some(expr) => (expr, true),
+        None => (token, false),
+    };
+    let ((high, low), kind) = parse_class(expr)?;
+
+    if !plus {
+        return Ok(class_combos(high, low, kind));
+    }
+
+    let mut combos = vec![];
+    if high == low {
+        for rank in high as usize..=Rank::Ace as usize {
+            combos.extend(class_combos(Rank::from(rank), Rank::from(rank), kind));
+        }
+    } else {
+        for rank in low as usize..high as usize {
+            combos.extend(class_combos(high, Rank::from(rank), kind));
+        }
+    }
+    Ok(combos)
+}
+
+/// Parse a starting hand class like "QQ", "AK", "AKs" or "AKo", higher `Rank` first
+fn parse_class(expr: &str) -> Result<((Rank, Rank), Kind)> {
+    if !expr.is_ascii() || expr.len() < 2 || expr.len() > 3 {
+        return Err(Error::ParseError);
+    }
+    let first = Rank::parse(&expr[..1])?;
+    let second = Rank::parse(&expr[1..2])?;
+    let kind = match &expr[2..] {
+        "" => Kind::Any,
+        "s" => Kind::Suited,
+        "o" => Kind::Offsuit,
+        _ => return Err(Error::ParseError),
+    };
+    if first == second && kind != Kind::Any {
+        return Err(Error::ParseError);
+    }
+    if first >= second {
+        Ok(((first, second), kind))
+    } else {
+        Ok(((second, first), kind))
+    }
+}
+
+/// All combos of the starting hand class `high`/`low`
+fn class_combos(high: Rank, low: Rank, kind: Kind) -> Vec<Combo> {
+    let mut combos = vec![];
+    for i in 0..4 {
+        for j in 0..4 {
+            if high == low && j <= i {
+                continue;
+            }
+            let suited = i == j;
+            if (kind == Kind::Suited && !suited) || (kind == Kind::Offsuit && suited) {
+                continue;
+            }
+            combos.push([
+                Card::new(high, Suit::from(i)),
+                Card::new(low, Suit::from(j)),
+            ]);
+        }
+    }
+    combos
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn specific() {
+        let range = Range::from("AhKh").unwrap();
+        assert_eq!(
+            range.combos,
+            vec![[Card::from("Ah").unwrap(), Card::from("Kh").unwrap()]]
+        );
+        // higher card first
+        assert_eq!(Range::from("KhAh").unwrap(), range);
+        assert!(Range::from("AhAh").is_err());
+    }
+
+    #[test]
+    fn classes() {
+        assert_eq!(Range::from("QQ").unwrap().len(), 6);
+        assert_eq!(Range::from("AK").unwrap().len(), 16);
+        assert_eq!(Range::from("AKs").unwrap().len(), 4);
+        assert_eq!(Range::from("AKo").unwrap().len(), 12);
+        assert_eq!(Range::from("KA").unwrap(), Range::from("AK").unwrap());
+    }
+
+    #[test]
+    fn plus() {
+        assert_eq!(Range::from("QQ+").unwrap().len(), 18);
+        assert_eq!(Range::from("22+").unwrap().len(), 78);
+        // ATs, AJs, AQs, AKs
+        assert_eq!(Range::from("ATs+").unwrap().len(), 16);
+        assert_eq!(
+            Range::from("ATs+").unwrap(),
+            Range::from("ATs,AJs,AQs,AKs").unwrap()
+        );
+    }
+
+    #[test]
+    fn spans() {
+        assert_eq!(
+            Range::from("QQ-99").unwrap(),
+            Range::from("99,TT,JJ,QQ").unwrap()
+        );
+        assert_eq!(Range::from("A9s-A6s").unwrap().len(), 16);
+        assert_eq!(
+            Range::from("A6s-A9s").unwrap(),
+            Range::from("A9s-A6s").unwrap()
+        );
+        assert_eq!(Range::from("99-QQ").unwrap(), Range::from("QQ-99").unwrap());
+        assert!(Range::from("A9s-K6s").is_err());
+        assert!(Range::from("QQ-A6s").is_err());
+    }
+
+    #[test]
+    fn duplicates() {
+        assert_eq!(Range::from("QQ+,KK,AhAd").unwrap().len(), 18);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(Range::from("").is_err());
+        assert!(Range::from("QQs").is_err());
+        assert!(Range::from("AKx").is_err());
+        assert!(Range::from("QQ,,KK").is_err());
+        assert!(Range::from("1K").is_err());
+    }
+
+    #[test]
+    fn hand_classes() {
+        let classes = HandClass::all();
+        assert_eq!(classes.len(), 169);
+        assert_eq!(classes[0].notation(), "AA");
+        assert_eq!(classes[1].notation(), "AKs");
+        assert_eq!(classes[13].notation(), "AKo");
+        assert_eq!(classes[168].notation(), "22");
+        for (i, class) in classes.iter().enumerate() {
+            assert_eq!(class.index(), i);
+            assert_eq!(HandClass::from(&class.notation()).unwrap(), *class);
+            assert!(class
+                .combos()
+                .iter()
+                .all(|combo| HandClass::of(combo) == *class));
+        }
+        let combos: usize = classes.iter().map(|class| class.combos().len()).sum();
+        assert_eq!(combos, 1326);
+        assert!(HandClass::from("AK").is_err());
+    }
+
+    #[test]
+    fn without() {
+        let range = Range::from("AA").unwrap();
+        let dead = [Card::from("Ah").unwrap()];
+        assert_eq!(range.without(&dead).len(), 3);
+    }
+}
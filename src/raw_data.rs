@@ -15,6 +15,15 @@ pub struct RawData {
 
 impl RawData {
     pub fn from_chain(chain: Chain<Iter<Card>, Iter<Card>>) -> RawData {
+        RawData::from_iter(chain)
+    }
+
+    /// The `RawData` of a single slice of cards, e.g. a holding or a board
+    pub fn from_cards(cards: &[Card]) -> RawData {
+        RawData::from_iter(cards.iter())
+    }
+
+    fn from_iter<'a, I: Iterator<Item = &'a Card>>(cards: I) -> RawData {
         let mut ranks = [
             // clubs, spades, hearts, diamonds
             [0, 0, 0, 0], // Two
@@ -38,7 +47,7 @@ impl RawData {
         // number of suites from Clubs to Diamonds
         let mut num_suits = [0, 0, 0, 0];
 
-        for card in chain.into_iter() {
+        for card in cards {
             let rank = card.rank as usize;
             let suit = card.suit as usize;
             ranks[rank][suit] = 1;
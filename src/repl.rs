@@ -0,0 +1,243 @@
+use crate::card::*;
+use crate::cli::parse_cards;
+use crate::deck::check_duplicates;
+use crate::equity::*;
+use crate::error::{Error, Result};
+use crate::hand::*;
+use crate::range::*;
+use crate::raw_data::*;
+
+pub const HELP: &str = "\
+commands:
+  hero <CARDS>       set hero's holding, e.g. hero AhKh
+  villain <RANGE>    set vilan's holding or range, e.g. villain QQ+,AKs
+  board <CARDS>      set the community cards, e.g. board Td9h2c
+  turn <CARD>        add the turn card
+  river <CARD>       add the river card
+  dead <CARDS>       set the dead cards
+  equity             evaluate hero vs. vilan
+  show               show the current spot
+  undo               revert the last change
+  clear              start over
+  quit               leave the session";
+
+/// The spot a `Session` is looking at
+#[derive(Clone, Debug, Default)]
+struct Spot {
+    hero: Vec<Card>,
+    vilan: Option<(String, Range)>,
+    board: Vec<Card>,
+    dead: Vec<Card>,
+}
+
+impl Spot {
+    /// all cards which can't be dealt anymore
+    fn known(&self) -> Vec<Card> {
+        self.hero
+            .iter()
+            .chain(self.board.iter())
+            .chain(self.dead.iter())
+            .copied()
+            .collect()
+    }
+}
+
+/// An interactive session keeping its `Spot` between commands
+#[derive(Debug, Default)]
+pub struct Session {
+    spot: Spot,
+    history: Vec<Spot>,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Session::default()
+    }
+
+    /// Execute a single command `line` and return the text to display. `None` ends the session.
+    pub fn execute(&mut self, line: &str) -> Result<Option<String>> {
+        let mut words = line.split_whitespace();
+        let cmd = match words.next() {
+            Some(cmd) => cmd,
+            None => return Ok(Some(String::new())),
+        };
+        let args: Vec<&str> = words.collect();
+        let arg = args.concat();
+
+        let mut spot = self.spot.clone();
+        match cmd {
+            "hero" => {
+                let hero = parse_cards(&arg)?;
+                if hero.len() != 2 {
+                    return Err(Error::ParseError);
+                }
+                spot.hero = hero;
+            }
+            "villain" | "vilan" => {
+                let range = Range::from(&arg)?;
+                spot.vilan = Some((arg, range));
+            }
+            "board" => {
+                let board = parse_cards(&arg)?;
+                if board.len() > 5 || board.len() == 1 || board.len() == 2 {
+                    return Err(Error::ParseError);
+                }
+                spot.board = board;
+            }
+            "turn" | "river" => {
+                let cards = parse_cards(&arg)?;
+                let street = if cmd == "turn" { 3 } else { 4 };
+                if cards.len() != 1 || spot.board.len() != street {
+                    return Err(Error::ParseError);
+                }
+                spot.board.extend(cards);
+            }
+            "dead" => spot.dead = parse_cards(&arg)?,
+            "undo" => {
+                return match self.history.pop() {
+                    Some(spot) => {
+                        self.spot = spot;
+                        Ok(Some(self.show()))
+                    }
+                    None => Ok(Some(String::from("nothing to undo"))),
+                }
+            }
+            "clear" => spot = Spot::default(),
+            "equity" => return Ok(Some(self.equity())),
+            "show" => return Ok(Some(self.show())),
+            "help" => return Ok(Some(String::from(HELP))),
+            "quit" | "exit" => return Ok(None),
+            _ => return Err(Error::ParseError),
+        }
+
+        check_duplicates(&spot.known())?;
+
+        self.history.push(std::mem::replace(&mut self.spot, spot));
+        Ok(Some(self.show()))
+    }
+
+    fn show(&self) -> String {
+        let spot = &self.spot;
+        let mut out = format!("hero: {}", cards(&spot.hero));
+        if spot.hero.len() == 2 && spot.board.len() >= 3 {
+            let raw_data = RawData::from_chain(spot.hero.iter().chain(spot.board.iter()));
+            let combo = vec![];
+            out.push_str(&format!(" ({})", Hand::new(&raw_data, &combo).rank));
+        }
+        if let Some((expr, range)) = &spot.vilan {
+            out.push_str(&format!("\nvilan: {} ({} combos)", expr, range.len()));
+        }
+        out.push_str(&format!("\nboard: {}", cards(&spot.board)));
+        if !spot.dead.is_empty() {
+            out.push_str(&format!("\ndead: {}", cards(&spot.dead)));
+        }
+        out
+    }
+
+    fn equity(&self) -> String {
+        let spot = &self.spot;
+        let range = match &spot.vilan {
+            Some((_, range)) if spot.hero.len() == 2 => range,
+            _ => return String::from("set hero and villain first"),
+        };
+
+        let equity = range_combos(&spot.hero, range, &spot.board, &spot.dead);
+        if equity.combos == 0 {
+            return String::from("vilan's range is blocked entirely");
+        }
+        let mut out = format!(
+            "equity: {:.2}% (win {:.2}%, tie {:.2}%) over {} runouts",
+            equity.equity(),
+            equity.win_pct(),
+            equity.tie_pct(),
+            equity.combos
+        );
+        for (i, n) in equity.stats.iter().enumerate() {
+            if *n > 0 {
+                let rank = crate::hand_rank::HandRank::from(i);
+                out.push_str(&format!(
+                    "\n{:>11}: {:>6.2}%",
+                    format!("{}", rank),
+                    equity.rank_pct(&rank)
+                ));
+            }
+        }
+        out
+    }
+}
+
+fn cards(cards: &[Card]) -> String {
+    cards
+        .iter()
+        .map(|card| format!("{}", card))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spot() {
+        let mut session = Session::new();
+        session.execute("hero AhKh").unwrap();
+        session.execute("villain QQ+").unwrap();
+        session.execute("board Td9h2c").unwrap();
+        session.execute("turn 5s").unwrap();
+
+        assert_eq!(session.spot.hero, parse_cards("AhKh").unwrap());
+        assert_eq!(session.spot.vilan.as_ref().unwrap().1.len(), 18);
+        assert_eq!(session.spot.board, parse_cards("Td9h2c5s").unwrap());
+    }
+
+    #[test]
+    fn undo() {
+        let mut session = Session::new();
+        session.execute("board Td9h2c").unwrap();
+        session.execute("turn 5s").unwrap();
+        session.execute("undo").unwrap();
+        assert_eq!(session.spot.board, parse_cards("Td9h2c").unwrap());
+        session.execute("undo").unwrap();
+        assert!(session.spot.board.is_empty());
+        assert_eq!(
+            session.execute("undo").unwrap(),
+            Some(String::from("nothing to undo"))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let mut session = Session::new();
+        assert!(session.execute("hero Ah").is_err());
+        assert!(session.execute("turn 5s").is_err());
+        assert!(session.execute("fold").is_err());
+
+        session.execute("board Td9h2c").unwrap();
+        assert!(session.execute("hero AhTd").is_err());
+        assert!(session.execute("dead 2c").is_err());
+        assert!(session.execute("turn Td").is_err());
+        session.execute("turn 5s").unwrap();
+        assert!(session.execute("river 9h").is_err());
+        // failed commands don't touch the spot
+        assert!(session.spot.hero.is_empty());
+        assert!(session.spot.dead.is_empty());
+        assert_eq!(session.spot.board, parse_cards("Td9h2c5s").unwrap());
+    }
+
+    #[test]
+    fn equity() {
+        let mut session = Session::new();
+        assert_eq!(
+            session.execute("equity").unwrap(),
+            Some(String::from("set hero and villain first"))
+        );
+        session.execute("hero AhAd").unwrap();
+        session.execute("villain KcKs").unwrap();
+        session.execute("board 2c7d9hTs3c").unwrap();
+        let out = session.execute("equity").unwrap().unwrap();
+        assert!(out.starts_with("equity: 100.00%"));
+
+        assert_eq!(session.execute("quit").unwrap(), None);
+    }
+}
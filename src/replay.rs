@@ -0,0 +1,299 @@
+use crate::card::*;
+use crate::deck::remaining_cards;
+use crate::error::{Error, Result};
+use crate::hand_rank::*;
+use crate::history::HandHistory;
+use crate::pot::{payouts, pots};
+use crate::raw_data::RawData;
+use crate::table::{Action, Street};
+
+use itertools::Itertools;
+
+/// The pot and the stacks of a hand history at one point in time
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplayState {
+    /// index into `HandHistory::actions` of the last applied action, `None` after the posts
+    pub action: Option<usize>,
+    pub street: Street,
+    pub pot: u64,
+    /// chips behind, indexed like `HandHistory::seats`
+    pub stacks: Vec<u64>,
+    /// chips put in during the hand, indexed like `HandHistory::seats`
+    pub contributed: Vec<u64>,
+    pub folded: Vec<bool>,
+}
+
+/// Replay `history` and return the state after the posts followed by the state after every
+/// action. Fails if a player isn't seated or puts in more chips than they have.
+pub fn replay(history: &HandHistory) -> Result<Vec<ReplayState>> {
+    let seat = |player: &str| {
+        history
+            .seats
+            .iter()
+            .position(|seat| seat.name == player)
+            .ok_or(Error::ParseError)
+    };
+    let n = history.seats.len();
+    let mut state = ReplayState {
+        action: None,
+        street: Street::Preflop,
+        pot: 0,
+        stacks: history.seats.iter().map(|seat| seat.stack).collect(),
+        contributed: vec![0; n],
+        folded: vec![false; n],
+    };
+    let put = |state: &mut ReplayState, i: usize, amount: u64| {
+        if amount > state.stacks[i] {
+            return Err(Error::IllegalAction);
+        }
+        state.stacks[i] -= amount;
+        state.contributed[i] += amount;
+        state.pot += amount;
+        Ok(())
+    };
+
+    for post in &history.posts {
+        put(&mut state, seat(&post.player)?, post.amount)?;
+    }
+    let mut states = vec![state.clone()];
+    for (idx, action) in history.actions.iter().enumerate() {
+        let i = seat(&action.player)?;
+        put(&mut state, i, action.amount)?;
+        if action.action == Action::Fold {
+            state.folded[i] = true;
+        }
+        state.action = Some(idx);
+        state.street = action.street;
+        states.push(state.clone());
+    }
+    Ok(states)
+}
+
+/// The expected outcome of a hand in which all chips went in before the river, averaged over
+/// every runout of the board instead of the one which was dealt
+#[derive(Clone, Debug, PartialEq)]
+pub struct AllInEv {
+    /// the street the last chips went in
+    pub street: Street,
+    /// the community cards known at that moment
+    pub board: Vec<Card>,
+    /// number of evaluated runouts
+    pub runouts: usize,
+    /// every player's expected share of the chips in the middle in percent, indexed like
+    /// `HandHistory::seats`
+    pub equity: Vec<f64>,
+    /// every player's expected net result, after paying the rake in proportion to the contested
+    /// chips they're expected to win
+    pub expected: Vec<f64>,
+    /// every player's actual net result, rake taken into account
+    pub actual: Vec<i64>,
+}
+
+/// Compute the all-in EV of `history`.
+///
+/// `None` if the hand wasn't decided by an all-in before the river, or if a holding of a player
+/// who went to showdown is unknown. Side pots and split pots are paid out like `pot::payouts`
+/// does. The `equity` ignores the rake, the `expected` results take it into account like the
+/// `actual` ones.
+pub fn all_in_ev(history: &HandHistory) -> Result<Option<AllInEv>> {
+    let states = replay(history)?;
+    let last = states.last().ok_or(Error::ParseError)?;
+    let in_hand: Vec<usize> = (0..last.folded.len())
+        .filter(|i| !last.folded[*i])
+        .collect();
+    let covered = in_hand.iter().filter(|i| last.stacks[**i] > 0).count();
+    let all_in = in_hand.iter().any(|i| last.stacks[*i] == 0);
+    if in_hand.len() < 2 || covered > 1 || !all_in {
+        return Ok(None);
+    }
+
+    let board_size = match last.street {
+        Street::Preflop => 0,
+        Street::Flop => 3,
+        Street::Turn => 4,
+        Street::River | Street::Showdown => return Ok(None),
+    };
+    if history.board.len() < board_size {
+        return Err(Error::ParseError);
+    }
+    let board = history.board[..board_size].to_vec();
+
+    let mut holdings: Vec<Vec<Card>> = vec![vec![]; history.seats.len()];
+    for (player, cards) in &history.shown {
+        if let Some(i) = history.seats.iter().position(|seat| &seat.name == player) {
+            holdings[i] = cards.clone();
+        }
+    }
+    if let Some(hero) = &history.hero {
+        if let Some(i) = history.seats.iter().position(|seat| &seat.name == hero) {
+            holdings[i] = history.hole_cards.clone();
+        }
+    }
+    if in_hand.iter().any(|i| holdings[*i].len() != 2) {
+        return Ok(None);
+    }
+
+    let known: Vec<Card> = holdings
+        .iter()
+        .flatten()
+        .chain(board.iter())
+        .copied()
+        .collect();
+    let deck = remaining_cards(&known);
+    let pots = pots(&last.contributed, &last.folded);
+    let button = history
+        .seats
+        .iter()
+        .position(|seat| seat.number == history.button)
+        .unwrap_or(0);
+
+    let n = history.seats.len();
+    let mut won = vec![0u64; n];
+    let mut runouts = 0;
+    let mut runout = board.clone();
+    for combo in deck.iter().sorted().combinations(5 - board_size) {
+        runout.truncate(board_size);
+        runout.extend(combo.into_iter().copied());
+        let strengths: Vec<Strength> = (0..n)
+            .map(|i| {
+                if last.folded[i] || holdings[i].is_empty() {
+                    Strength(0)
+                } else {
+                    strength(
+                        &RawData::from_cards(&holdings[i]),
+                        &RawData::from_cards(&runout),
+                    )
+                }
+            })
+            .collect();
+        for (total, payout) in won.iter_mut().zip(payouts(&pots, &strengths, button)) {
+            *total += payout;
+        }
+        runouts += 1;
+    }
+
+    let total: u64 = last.contributed.iter().sum();
+    // bets nobody called go back without rake
+    let mut uncalled = vec![0u64; n];
+    for pot in pots.iter().filter(|pot| pot.eligible.len() == 1) {
+        uncalled[pot.eligible[0]] += pot.amount;
+    }
+    let contested = total - uncalled.iter().sum::<u64>();
+    let rake = |i: usize| {
+        if contested == 0 {
+            return 0.0;
+        }
+        let won = won[i] as f64 / runouts as f64 - uncalled[i] as f64;
+        history.rake as f64 * won / contested as f64
+    };
+    let mut received = vec![0i64; n];
+    for (player, chips) in history.collected.iter().chain(history.uncalled.iter()) {
+        if let Some(i) = history.seats.iter().position(|seat| &seat.name == player) {
+            received[i] += *chips as i64;
+        }
+    }
+    Ok(Some(AllInEv {
+        street: last.street,
+        board,
+        runouts,
+        equity: won
+            .iter()
+            .map(|w| *w as f64 * 100.0 / (runouts as u64 * total) as f64)
+            .collect(),
+        expected: (0..n)
+            .map(|i| won[i] as f64 / runouts as f64 - last.contributed[i] as f64 - rake(i))
+            .collect(),
+        actual: (0..n)
+            .map(|i| received[i] - last.contributed[i] as i64)
+            .collect(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HAND: &str = "\
+PokerStars Hand #1: Hold'em No Limit ($0.05/$0.10 USD) - 2020/01/01 12:00:00 ET
+Table 'Alpha' 6-max Seat #1 is the button
+Seat 1: hero ($10.00 in chips)
+Seat 2: vilan ($5.00 in chips)
+Seat 3: other ($10.00 in chips)
+hero: posts small blind $0.05
+vilan: posts big blind $0.10
+*** HOLE CARDS ***
+Dealt to hero [Ah Ad]
+other: folds
+hero: raises $0.20 to $0.30
+vilan: calls $0.20
+*** FLOP *** [Kc 7d 2h]
+hero: bets $0.40
+vilan: raises $4.30 to $4.70 and is all-in
+hero: calls $4.30
+*** TURN *** [Kc 7d 2h] [Ks]
+*** RIVER *** [Kc 7d 2h Ks] [7c]
+*** SHOW DOWN ***
+vilan: shows [Kh Qh] (a full house, Kings full of Sevens)
+hero: shows [Ah Ad] (two pair, Aces and Kings)
+vilan collected $9.80 from pot
+*** SUMMARY ***
+Total pot $10.00 | Rake $0.20
+Board [Kc 7d 2h Ks 7c]
+";
+
+    #[test]
+    fn states() {
+        let history = HandHistory::from(HAND).unwrap();
+        let states = replay(&history).unwrap();
+        assert_eq!(states.len(), history.actions.len() + 1);
+        assert_eq!(states[0].pot, 15);
+        assert_eq!(states[0].stacks, vec![995, 490, 1000]);
+        assert!(states[1].folded[2]);
+        // after preflop
+        assert_eq!(states[3].pot, 60);
+        assert_eq!(states[3].street, Street::Preflop);
+        let last = states.last().unwrap();
+        assert_eq!(last.pot, 1000);
+        assert_eq!(last.stacks, vec![500, 0, 1000]);
+    }
+
+    #[test]
+    fn overdraft() {
+        let text = HAND.replace("hero: calls $4.30", "hero: calls $43.00");
+        let history = HandHistory::from(&text).unwrap();
+        assert!(replay(&history).is_err());
+    }
+
+    #[test]
+    fn ev() {
+        let history = HandHistory::from(HAND).unwrap();
+        let ev = all_in_ev(&history).unwrap().unwrap();
+        assert_eq!(ev.street, Street::Flop);
+        assert_eq!(ev.board.len(), 3);
+        // two cards to come out of 45
+        assert_eq!(ev.runouts, 990);
+        // Aces vs. top pair, the pot is 2 x $5
+        assert!(ev.equity[0] > 75.0 && ev.equity[0] < 90.0);
+        assert!((ev.equity[0] + ev.equity[1] - 100.0).abs() < 1e-9);
+        assert_eq!(ev.equity[2], 0.0);
+        // both pay their expected share of the 20 rake
+        assert!((ev.expected[0] + ev.expected[1] + 20.0).abs() < 1e-6);
+        let rake = |i: usize| 20.0 * ev.equity[i] / 100.0;
+        let expected = |i: usize| ev.equity[i] * 10.0 - 500.0 - rake(i);
+        assert!((ev.expected[0] - expected(0)).abs() < 1e-6);
+        assert!(ev.expected[0] > 0.0);
+        assert_eq!(ev.actual, vec![-500, 480, 0]);
+    }
+
+    #[test]
+    fn no_all_in() {
+        let text = HAND
+            .replace(
+                "vilan: raises $4.30 to $4.70 and is all-in",
+                "vilan: calls $0.40",
+            )
+            .replace("hero: calls $4.30\n", "");
+        let history = HandHistory::from(&text).unwrap();
+        assert_eq!(all_in_ev(&history).unwrap(), None);
+    }
+}
@@ -0,0 +1,676 @@
+use crate::card::*;
+use crate::deck::check_duplicates;
+use crate::error::{Error, Result};
+use crate::hand_rank::{strength, Strength};
+use crate::range::{Combo, Range};
+use crate::raw_data::RawData;
+
+/// A river decision between an out of position player (0), who acts first, and an in position
+/// player (1)
+#[derive(Clone, Debug, PartialEq)]
+pub struct RiverSpot {
+    pub board: Vec<Card>,
+    /// combos and their weights, combos which use a board card are ignored
+    pub ranges: [Vec<(Combo, f64)>; 2],
+    /// chips in the middle before the river action
+    pub pot: f64,
+    /// the effective stack behind
+    pub stack: f64,
+    /// bet sizes as fractions of the pot, capped at the stack
+    pub bet_sizes: Vec<f64>,
+    /// raise sizes as fractions of the pot after calling, capped at the stack
+    pub raise_sizes: Vec<f64>,
+    /// raises allowed after the first bet
+    pub max_raises: usize,
+}
+
+impl RiverSpot {
+    /// A spot with every combo of the two ranges weighted equally, half pot and pot sized bets
+    /// and a single pot sized raise
+    pub fn new(board: Vec<Card>, oop: &Range, ip: &Range, pot: f64, stack: f64) -> Self {
+        let weighted = |range: &Range| range.combos.iter().map(|combo| (*combo, 1.0)).collect();
+        RiverSpot {
+            board,
+            ranges: [weighted(oop), weighted(ip)],
+            pot,
+            stack,
+            bet_sizes: vec![0.5, 1.0],
+            raise_sizes: vec![1.0],
+            max_raises: 1,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RiverAction {
+    Check,
+    /// bet to the amount
+    Bet(f64),
+    Fold,
+    Call,
+    /// raise to the amount
+    Raise(f64),
+}
+
+/// A node of the betting tree
+#[derive(Clone, Debug, PartialEq)]
+pub struct RiverNode {
+    /// the actions which led here
+    pub history: Vec<RiverAction>,
+    /// chips each player put in on the river
+    pub bets: [f64; 2],
+    /// the player to act, `None` at the end of the hand
+    pub player: Option<usize>,
+    /// the player who folded
+    pub folded: Option<usize>,
+    pub actions: Vec<RiverAction>,
+    /// indices of the nodes after each of the `actions`
+    pub children: Vec<usize>,
+}
+
+/// Vector CFR+ over every combo of both ranges at once
+#[derive(Clone, Debug)]
+pub struct RiverSolver {
+    pub spot: RiverSpot,
+    pub tree: Vec<RiverNode>,
+    /// the live combos of each player
+    combos: [Vec<Combo>; 2],
+    weights: [Vec<f64>; 2],
+    strengths: [Vec<Strength>; 2],
+    /// per node: regrets and strategy sums, indexed by action then combo
+    regrets: Vec<Vec<Vec<f64>>>,
+    strategy_sums: Vec<Vec<Vec<f64>>>,
+    /// number of completed iterations
+    pub iterations: usize,
+}
+
+impl RiverSolver {
+    /// Build the betting tree of `spot` and rank every combo on its board
+    pub fn new(spot: RiverSpot) -> Result<Self> {
+        if spot.board.len() != 5 || spot.pot <= 0.0 || spot.stack < 0.0 {
+            return Err(Error::ParseError);
+        }
+        check_duplicates(&spot.board)?;
+
+        let board = RawData::from_cards(&spot.board);
+        let mut combos: [Vec<Combo>; 2] = [vec![], vec![]];
+        let mut weights: [Vec<f64>; 2] = [vec![], vec![]];
+        let mut strengths: [Vec<Strength>; 2] = [vec![], vec![]];
+        for player in 0..2 {
+            for (combo, weight) in spot.ranges[player].iter() {
+                if *weight <= 0.0 || combo.iter().any(|card| spot.board.contains(card)) {
+                    continue;
+                }
+                combos[player].push(*combo);
+                weights[player].push(*weight);
+                strengths[player].push(strength(&RawData::from_cards(combo), &board));
+            }
+        }
+
+        let mut solver = RiverSolver {
+            spot,
+            tree: vec![],
+            combos,
+            weights,
+            strengths,
+            regrets: vec![],
+            strategy_sums: vec![],
+            iterations: 0,
+        };
+        solver.build(vec![], [0.0, 0.0], Some(0), 0);
+        for node in solver.tree.iter() {
+            let player = match node.player {
+                Some(player) => player,
+                None => {
+                    solver.regrets.push(vec![]);
+                    solver.strategy_sums.push(vec![]);
+                    continue;
+                }
+            };
+            let n = solver.combos[player].len();
+            solver.regrets.push(vec![vec![0.0; n]; node.actions.len()]);
+            solver
+                .strategy_sums
+                .push(vec![vec![0.0; n]; node.actions.len()]);
+        }
+        Ok(solver)
+    }
+
+    /// Add the node after `history` and everything below it, returns its index
+    fn build(
+        &mut self,
+        history: Vec<RiverAction>,
+        bets: [f64; 2],
+        player: Option<usize>,
+        raises: usize,
+    ) -> usize {
+        let idx = self.tree.len();
+        let folded = match history.last() {
+            Some(RiverAction::Fold) => Some(1 - player.unwrap_or(0)),
+            _ => None,
+        };
+        self.tree.push(RiverNode {
+            history: history.clone(),
+            bets,
+            player: if folded.is_some() { None } else { player },
+            folded,
+            actions: vec![],
+            children: vec![],
+        });
+        let player = match (player, folded) {
+            (Some(player), None) => player,
+            _ => return idx,
+        };
+
+        let (spot, stack) = (&self.spot, self.spot.stack);
+        let facing = bets[1 - player] - bets[player];
+        let mut actions = vec![];
+        if facing > 0.0 {
+            actions.push(RiverAction::Fold);
+            actions.push(RiverAction::Call);
+            if raises < spot.max_raises && bets[1 - player] < stack {
+                let pot = spot.pot + 2.0 * bets[1 - player];
+                for size in spot.raise_sizes.iter() {
+                    let to = (bets[1 - player] + size * pot).min(stack);
+                    if to > bets[1 - player] && !actions.contains(&RiverAction::Raise(to)) {
+                        actions.push(RiverAction::Raise(to));
+                    }
+                }
+            }
+        } else {
+            actions.push(RiverAction::Check);
+            if stack > 0.0 {
+                for size in spot.bet_sizes.iter() {
+                    let to = (size * spot.pot).min(stack);
+                    if to > 0.0 && !actions.contains(&RiverAction::Bet(to)) {
+                        actions.push(RiverAction::Bet(to));
+                    }
+                }
+            }
+        }
+
+        let mut children = vec![];
+        for action in actions.iter() {
+            let mut next_history = history.clone();
+            next_history.push(*action);
+            let mut next_bets = bets;
+            let (next, raises) = match action {
+                RiverAction::Check if player == 0 => (Some(1), raises),
+                RiverAction::Check => (None, raises),
+                RiverAction::Fold => (Some(1 - player), raises),
+                RiverAction::Call => {
+                    next_bets[player] = bets[1 - player];
+                    (None, raises)
+                }
+                RiverAction::Bet(to) => {
+                    next_bets[player] = *to;
+                    (Some(1 - player), raises)
+                }
+                RiverAction::Raise(to) => {
+                    next_bets[player] = *to;
+                    (Some(1 - player), raises + 1)
+                }
+            };
+            children.push(self.build(next_history, next_bets, next, raises));
+        }
+        self.tree[idx].actions = actions;
+        self.tree[idx].children = children;
+        idx
+    }
+
+    /// The live combos of `player` in the order of every strategy
+    pub fn combos(&self, player: usize) -> &[Combo] {
+        &self.combos[player]
+    }
+
+    /// Run `iterations` of CFR+, each one updating the out of position player and then the in
+    /// position player
+    pub fn solve(&mut self, iterations: usize) {
+        for _ in 0..iterations {
+            self.iterations += 1;
+            for player in 0..2 {
+                let reach = self.weights.clone();
+                self.cfr(0, player, reach);
+            }
+        }
+    }
+
+    /// The average strategy at `node`, indexed by action then combo of the player to act
+    pub fn strategy(&self, node: usize) -> Vec<Vec<f64>> {
+        average(&self.strategy_sums[node])
+    }
+
+    /// How often `player` takes each action at `node` over their whole range
+    pub fn frequencies(&self, node: usize) -> Vec<f64> {
+        let player = match self.tree[node].player {
+            Some(player) => player,
+            None => return vec![],
+        };
+        let total: f64 = self.weights[player].iter().sum();
+        self.strategy(node)
+            .iter()
+            .map(|p| {
+                p.iter()
+                    .zip(self.weights[player].iter())
+                    .map(|(p, w)| p * w)
+                    .sum::<f64>()
+                    / total
+            })
+            .collect()
+    }
+
+    /// The expected chips both players win on the river with their average strategies, the
+    /// pot included
+    pub fn ev(&self) -> [f64; 2] {
+        let strategies: Vec<Vec<Vec<f64>>> = (0..self.tree.len())
+            .map(|node| self.strategy(node))
+            .collect();
+        let mut ev = [0.0; 2];
+        for (player, ev) in ev.iter_mut().enumerate() {
+            let values = self.evaluate(0, player, self.weights.clone(), &strategies, false);
+            *ev = self.normalize(player, &values);
+        }
+        ev
+    }
+
+    /// The expected chips of a best response of each player against the other's average
+    /// strategy
+    pub fn best_response(&self) -> [f64; 2] {
+        let strategies: Vec<Vec<Vec<f64>>> = (0..self.tree.len())
+            .map(|node| self.strategy(node))
+            .collect();
+        let mut ev = [0.0; 2];
+        for (player, ev) in ev.iter_mut().enumerate() {
+            let values = self.evaluate(0, player, self.weights.clone(), &strategies, true);
+            *ev = self.normalize(player, &values);
+        }
+        ev
+    }
+
+    /// How much best responses gain against the average strategies, in chips on average per
+    /// player; 0 at an equilibrium
+    pub fn exploitability(&self) -> f64 {
+        let [oop, ip] = self.best_response();
+        (oop + ip - self.spot.pot) / 2.0
+    }
+
+    /// The expected value per combination of hands from counterfactual values of `player`
+    fn normalize(&self, player: usize, values: &[f64]) -> f64 {
+        let total: f64 = values
+            .iter()
+            .zip(self.weights[player].iter())
+            .map(|(v, w)| v * w)
+            .sum();
+        let pairs: f64 = self
+            .live(player, &self.weights[1 - player])
+            .iter()
+            .zip(self.weights[player].iter())
+            .map(|(live, w)| live * w)
+            .sum();
+        if pairs == 0.0 {
+            return 0.0;
+        }
+        total / pairs
+    }
+
+    /// One CFR+ pass updating `player`, returns their counterfactual values at `node`
+    fn cfr(&mut self, node: usize, player: usize, reach: [Vec<f64>; 2]) -> Vec<f64> {
+        let acting = match self.tree[node].player {
+            Some(acting) => acting,
+            None => return self.terminal(node, player, &reach[1 - player]),
+        };
+        let strategy = regret_matching(&self.regrets[node]);
+        let children = self.tree[node].children.clone();
+
+        if acting != player {
+            let mut value = vec![0.0; self.combos[player].len()];
+            for (a, child) in children.iter().enumerate() {
+                let mut next = reach.clone();
+                for (r, p) in next[acting].iter_mut().zip(strategy[a].iter()) {
+                    *r *= p;
+                }
+                for (v, c) in value.iter_mut().zip(self.cfr(*child, player, next)) {
+                    *v += c;
+                }
+            }
+            return value;
+        }
+
+        let n = self.combos[player].len();
+        let mut values = vec![];
+        let mut value = vec![0.0; n];
+        for (a, child) in children.iter().enumerate() {
+            let mut next = reach.clone();
+            for (r, p) in next[player].iter_mut().zip(strategy[a].iter()) {
+                *r *= p;
+            }
+            let action = self.cfr(*child, player, next);
+            for c in 0..n {
+                value[c] += strategy[a][c] * action[c];
+            }
+            values.push(action);
+        }
+        let weight = self.iterations as f64;
+        for a in 0..children.len() {
+            for c in 0..n {
+                let regret = &mut self.regrets[node][a][c];
+                *regret = (*regret + values[a][c] - value[c]).max(0.0);
+                self.strategy_sums[node][a][c] += weight * reach[player][c] * strategy[a][c];
+            }
+        }
+        value
+    }
+
+    /// Counterfactual values of `player` at `node` with both players following `strategies`,
+    /// or `player` best responding
+    fn evaluate(
+        &self,
+        node: usize,
+        player: usize,
+        reach: [Vec<f64>; 2],
+        strategies: &[Vec<Vec<f64>>],
+        best_response: bool,
+    ) -> Vec<f64> {
+        let acting = match self.tree[node].player {
+            Some(acting) => acting,
+            None => return self.terminal(node, player, &reach[1 - player]),
+        };
+        let children = &self.tree[node].children;
+        let n = self.combos[player].len();
+        let mut value = vec![
+            if acting == player && best_response {
+                f64::NEG_INFINITY
+            } else {
+                0.0
+            };
+            n
+        ];
+        for (a, child) in children.iter().enumerate() {
+            let mut next = reach.clone();
+            if acting != player || !best_response {
+                for (r, p) in next[acting].iter_mut().zip(strategies[node][a].iter()) {
+                    *r *= p;
+                }
+            }
+            let action = self.evaluate(*child, player, next, strategies, best_response);
+            for c in 0..n {
+                if acting != player {
+                    value[c] += action[c];
+                } else if best_response {
+                    value[c] = value[c].max(action[c]);
+                } else {
+                    value[c] += strategies[node][a][c] * action[c];
+                }
+            }
+        }
+        value
+    }
+
+    /// `player`'s counterfactual values at the end of the hand against the opponent's `reach`
+    fn terminal(&self, node: usize, player: usize, reach: &[f64]) -> Vec<f64> {
+        let RiverNode { bets, folded, .. } = &self.tree[node];
+        let pot = self.spot.pot;
+        let live = self.live(player, reach);
+        if let Some(folded) = folded {
+            let payoff = if *folded == player {
+                -bets[player]
+            } else {
+                pot + bets[1 - player]
+            };
+            return live.iter().map(|l| l * payoff).collect();
+        }
+
+        let (win, lose) = self.showdown(player, reach);
+        // both players put in the same amount at a showdown
+        let bet = bets[player];
+        (0..live.len())
+            .map(|c| {
+                let tie = live[c] - win[c] - lose[c];
+                win[c] * (pot + bet) - lose[c] * bet + tie * pot / 2.0
+            })
+            .collect()
+    }
+
+    /// The opponent's reach of the combos which don't share a card with each of `player`'s
+    fn live(&self, player: usize, reach: &[f64]) -> Vec<f64> {
+        let opponent = &self.combos[1 - player];
+        let total: f64 = reach.iter().sum();
+        let mut by_card = [0.0; 52];
+        // the reach of the identical combo is subtracted twice, once per card
+        let mut by_combo = vec![0.0; 52 * 52];
+        for (combo, r) in opponent.iter().zip(reach.iter()) {
+            by_card[index(combo[0])] += r;
+            by_card[index(combo[1])] += r;
+            by_combo[pair_index(combo)] += r;
+        }
+        self.combos[player]
+            .iter()
+            .map(|combo| {
+                total - by_card[index(combo[0])] - by_card[index(combo[1])]
+                    + by_combo[pair_index(combo)]
+            })
+            .collect()
+    }
+
+    /// The opponent's live reach of weaker and of stronger combos than each of `player`'s
+    fn showdown(&self, player: usize, reach: &[f64]) -> (Vec<f64>, Vec<f64>) {
+        let own = &self.strengths[player];
+        let other = &self.strengths[1 - player];
+        let mut mine: Vec<usize> = (0..own.len()).collect();
+        mine.sort_by_key(|c| own[*c]);
+        let mut theirs: Vec<usize> = (0..other.len()).collect();
+        theirs.sort_by_key(|o| other[*o]);
+
+        let combos = &self.combos;
+        let sweep =
+            |mine: &[usize], theirs: &[usize], beats: &dyn Fn(Strength, Strength) -> bool| {
+                let mut result = vec![0.0; own.len()];
+                let mut total = 0.0;
+                let mut by_card = [0.0; 52];
+                let mut j = 0;
+                for c in mine.iter() {
+                    while j < theirs.len() && beats(own[*c], other[theirs[j]]) {
+                        let o = theirs[j];
+                        total += reach[o];
+                        by_card[index(combos[1 - player][o][0])] += reach[o];
+                        by_card[index(combos[1 - player][o][1])] += reach[o];
+                        j += 1;
+                    }
+                    let combo = combos[player][*c];
+                    result[*c] = total - by_card[index(combo[0])] - by_card[index(combo[1])];
+                }
+                result
+            };
+        let win = sweep(&mine, &theirs, &|own, other| other < own);
+        mine.reverse();
+        theirs.reverse();
+        let lose = sweep(&mine, &theirs, &|own, other| other > own);
+        (win, lose)
+    }
+}
+
+fn index(card: Card) -> usize {
+    card.rank as usize * 4 + card.suit as usize
+}
+
+fn pair_index(combo: &Combo) -> usize {
+    let (a, b) = (index(combo[0]), index(combo[1]));
+    a.min(b) * 52 + a.max(b)
+}
+
+/// Play each action in proportion to its positive regret, per combo
+fn regret_matching(regrets: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let actions = regrets.len();
+    let n = regrets.first().map_or(0, Vec::len);
+    let mut strategy = vec![vec![1.0 / actions as f64; n]; actions];
+    for c in 0..n {
+        let total: f64 = regrets.iter().map(|r| r[c].max(0.0)).sum();
+        if total > 0.0 {
+            for a in 0..actions {
+                strategy[a][c] = regrets[a][c].max(0.0) / total;
+            }
+        }
+    }
+    strategy
+}
+
+/// Normalize strategy sums per combo
+fn average(sums: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let actions = sums.len();
+    let n = sums.first().map_or(0, Vec::len);
+    let mut strategy = vec![vec![1.0 / actions as f64; n]; actions];
+    for c in 0..n {
+        let total: f64 = sums.iter().map(|s| s[c]).sum();
+        if total > 0.0 {
+            for a in 0..actions {
+                strategy[a][c] = sums[a][c] / total;
+            }
+        }
+    }
+    strategy
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(expr: &str) -> Vec<Card> {
+        expr.split(' ').map(|c| Card::from(c).unwrap()).collect()
+    }
+
+    /// Overpairs and air against a bluff catcher, one pot sized bet and no raises
+    fn polarized() -> RiverSolver {
+        let spot = RiverSpot {
+            bet_sizes: vec![1.0],
+            raise_sizes: vec![],
+            max_raises: 0,
+            ..RiverSpot::new(
+                board("Kh Qd 7c 4s 2h"),
+                &Range::from("AA,T9s").unwrap(),
+                &Range::from("AKo").unwrap(),
+                100.0,
+                100.0,
+            )
+        };
+        RiverSolver::new(spot).unwrap()
+    }
+
+    #[test]
+    fn tree() {
+        let solver = polarized();
+        // the root, check, check-check, check-bet and its fold and call, bet and its fold and call
+        assert_eq!(solver.tree.len(), 9);
+        assert_eq!(
+            solver.tree[0].actions,
+            vec![RiverAction::Check, RiverAction::Bet(100.0)]
+        );
+        // AKo without the King of Hearts
+        assert_eq!(solver.combos(1).len(), 9);
+
+        let spot = RiverSpot::new(
+            board("Kh Qd 7c 4s 2h"),
+            &Range::from("AA").unwrap(),
+            &Range::from("KK").unwrap(),
+            100.0,
+            80.0,
+        );
+        let solver = RiverSolver::new(spot).unwrap();
+        // the pot sized bet is capped at the stack, and nobody can raise an all-in
+        assert_eq!(
+            solver.tree[0].actions,
+            vec![
+                RiverAction::Check,
+                RiverAction::Bet(50.0),
+                RiverAction::Bet(80.0)
+            ]
+        );
+        let shove = solver.tree[0].children[2];
+        assert_eq!(
+            solver.tree[shove].actions,
+            vec![RiverAction::Fold, RiverAction::Call]
+        );
+        let half = solver.tree[0].children[1];
+        assert_eq!(
+            solver.tree[half].actions,
+            vec![
+                RiverAction::Fold,
+                RiverAction::Call,
+                RiverAction::Raise(80.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn showdown() {
+        let spot = RiverSpot::new(
+            board("Kh Qd 7c 4s 2h"),
+            &Range::from("AA,KQ,T9s,77,A5s").unwrap(),
+            &Range::from("AK,QQ,T9s,JTo").unwrap(),
+            100.0,
+            100.0,
+        );
+        let solver = RiverSolver::new(spot).unwrap();
+        let reach: Vec<f64> = (0..solver.combos(1).len())
+            .map(|i| 1.0 + i as f64 / 10.0)
+            .collect();
+        let (win, lose) = solver.showdown(0, &reach);
+        let live = solver.live(0, &reach);
+        for (c, combo) in solver.combos(0).iter().enumerate() {
+            let (mut w, mut l, mut t) = (0.0, 0.0, 0.0);
+            for (o, other) in solver.combos(1).iter().enumerate() {
+                if combo.iter().any(|card| other.contains(card)) {
+                    continue;
+                }
+                match solver.strengths[0][c].cmp(&solver.strengths[1][o]) {
+                    std::cmp::Ordering::Greater => w += reach[o],
+                    std::cmp::Ordering::Less => l += reach[o],
+                    std::cmp::Ordering::Equal => t += reach[o],
+                }
+            }
+            assert!((win[c] - w).abs() < 1e-9);
+            assert!((lose[c] - l).abs() < 1e-9);
+            assert!((live[c] - w - l - t).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn polarized_equilibrium() {
+        let mut solver = polarized();
+        solver.solve(300);
+        assert!(solver.exploitability() < 0.5, "{}", solver.exploitability());
+        let ev = solver.ev();
+        assert!((ev[0] + ev[1] - 100.0).abs() < 1e-6);
+
+        // the overpairs always bet
+        let strategy = solver.strategy(0);
+        for (c, combo) in solver.combos(0).iter().enumerate() {
+            if combo[0].rank == Rank::Ace {
+                assert!(strategy[1][c] > 0.95);
+            }
+        }
+        // a pot sized bet makes the bluff catcher call half of the time
+        let bet = solver.tree[0].children[1];
+        let call = solver.frequencies(bet)[1];
+        assert!(call > 0.4 && call < 0.6, "{}", call);
+    }
+
+    #[test]
+    fn errors() {
+        let spot = RiverSpot::new(
+            board("Kh Qd 7c 4s"),
+            &Range::from("AA").unwrap(),
+            &Range::from("KK").unwrap(),
+            100.0,
+            100.0,
+        );
+        assert!(RiverSolver::new(spot).is_err());
+        let spot = RiverSpot::new(
+            board("Kh Qd 7c 4s Kh"),
+            &Range::from("AA").unwrap(),
+            &Range::from("KK").unwrap(),
+            100.0,
+            100.0,
+        );
+        assert!(RiverSolver::new(spot).is_err());
+    }
+}
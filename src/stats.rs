@@ -0,0 +1,651 @@
+use crate::error::{Error, Result};
+use crate::history::HandHistory;
+use crate::replay::{all_in_ev, replay};
+use crate::table::{Action, Street};
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::Write;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A player's seat relative to the button
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Position {
+    SmallBlind,
+    BigBlind,
+    Early,
+    Middle,
+    Cutoff,
+    Button,
+}
+
+impl Position {
+    pub fn from(expr: &str) -> Result<Self> {
+        match expr {
+            "sb" => Ok(Position::SmallBlind),
+            "bb" => Ok(Position::BigBlind),
+            "ep" => Ok(Position::Early),
+            "mp" => Ok(Position::Middle),
+            "co" => Ok(Position::Cutoff),
+            "btn" => Ok(Position::Button),
+            _ => Err(Error::ParseError),
+        }
+    }
+
+    /// The positions of the players dealt into `history`, indexed like `HandHistory::seats`.
+    /// Heads up the button is the `Button` and the other player the `BigBlind`.
+    pub fn of(history: &HandHistory) -> Vec<Option<Position>> {
+        let n = history.seats.len();
+        let mut positions = vec![None; n];
+        let button = match history
+            .seats
+            .iter()
+            .position(|seat| seat.number == history.button)
+        {
+            Some(button) => button,
+            None => return positions,
+        };
+
+        // the players left of the button in the order they act preflop, the button last
+        let order: Vec<usize> = (1..=n)
+            .map(|i| (button + i) % n)
+            .filter(|i| !history.seats[*i].sitting_out)
+            .collect();
+        let dealt = order.len();
+        for (k, seat) in order.into_iter().enumerate() {
+            positions[seat] = Some(match (dealt, k) {
+                (_, k) if k == dealt - 1 => Position::Button,
+                (2, _) => Position::BigBlind,
+                (_, 0) => Position::SmallBlind,
+                (_, 1) => Position::BigBlind,
+                (_, k) if k == dealt - 2 => Position::Cutoff,
+                // the seats between the big blind and the cutoff, the first half is early
+                (_, k) if k - 2 < (dealt - 3) / 2 => Position::Early,
+                _ => Position::Middle,
+            });
+        }
+        positions
+    }
+}
+
+/// Restricts which hands and seats are counted, an empty or `None` field matches everything
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Filter {
+    pub positions: Vec<Position>,
+    /// the big blind in hundredths, see `HandHistory`
+    pub big_blind: Option<u64>,
+    /// first day to count, "YYYY/MM/DD"
+    pub from: Option<String>,
+    /// last day to count, "YYYY/MM/DD"
+    pub to: Option<String>,
+}
+
+impl Filter {
+    fn matches_hand(&self, history: &HandHistory) -> bool {
+        let day = history.date.get(..10).unwrap_or(&history.date);
+        self.big_blind.is_none_or(|bb| bb == history.big_blind)
+            && self.from.as_ref().is_none_or(|from| day >= from.as_str())
+            && self.to.as_ref().is_none_or(|to| day <= to.as_str())
+    }
+
+    fn matches_position(&self, position: Position) -> bool {
+        self.positions.is_empty() || self.positions.contains(&position)
+    }
+}
+
+/// The counters behind a player's HUD stats
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PlayerStats {
+    pub hands: usize,
+    /// hands the player voluntarily put chips in preflop
+    pub vpip: usize,
+    /// hands the player raised preflop
+    pub pfr: usize,
+    pub three_bets: usize,
+    /// hands the player faced a single raise preflop
+    pub three_bet_chances: usize,
+    pub folds_to_three_bet: usize,
+    /// hands the player's open raise was re-raised
+    pub three_bets_faced: usize,
+    pub cbets: usize,
+    /// hands the preflop raiser could bet the flop first
+    pub cbet_chances: usize,
+    pub saw_flop: usize,
+    pub showdowns: usize,
+    pub showdowns_won: usize,
+    /// bets and raises after the flop
+    pub aggressive: usize,
+    /// calls after the flop
+    pub calls: usize,
+    /// the net result in big blinds
+    pub won: f64,
+    /// the net result in big blinds with all-in hands counted at their all-in EV
+    pub ev_won: f64,
+}
+
+impl PlayerStats {
+    /// voluntarily put money in pot, in percent
+    pub fn vpip_pct(&self) -> f64 {
+        pct(self.vpip, self.hands)
+    }
+
+    /// preflop raise, in percent
+    pub fn pfr_pct(&self) -> f64 {
+        pct(self.pfr, self.hands)
+    }
+
+    pub fn three_bet_pct(&self) -> f64 {
+        pct(self.three_bets, self.three_bet_chances)
+    }
+
+    pub fn fold_to_three_bet_pct(&self) -> f64 {
+        pct(self.folds_to_three_bet, self.three_bets_faced)
+    }
+
+    /// continuation bets on the flop, in percent
+    pub fn cbet_pct(&self) -> f64 {
+        pct(self.cbets, self.cbet_chances)
+    }
+
+    /// went to showdown after seeing the flop, in percent
+    pub fn wtsd_pct(&self) -> f64 {
+        pct(self.showdowns, self.saw_flop)
+    }
+
+    /// won money at showdown, in percent
+    pub fn wsd_pct(&self) -> f64 {
+        pct(self.showdowns_won, self.showdowns)
+    }
+
+    /// postflop bets and raises per call, `None` without calls
+    pub fn aggression_factor(&self) -> Option<f64> {
+        if self.calls == 0 {
+            return None;
+        }
+        Some(self.aggressive as f64 / self.calls as f64)
+    }
+
+    /// big blinds won per 100 hands
+    pub fn bb_per_100(&self) -> f64 {
+        per_100(self.won, self.hands)
+    }
+
+    /// big blinds won per 100 hands, all-in hands counted at their all-in EV
+    pub fn ev_bb_per_100(&self) -> f64 {
+        per_100(self.ev_won, self.hands)
+    }
+
+    /// The stats as a CSV record matching `STATS_CSV_HEADER`
+    pub fn csv_record(&self, player: &str) -> String {
+        format!(
+            "{},{},{:.2},{:.2},{:.2},{:.2},{:.2},{:.2},{:.2},{},{:.2},{:.2}",
+            csv_field(player),
+            self.hands,
+            self.vpip_pct(),
+            self.pfr_pct(),
+            self.three_bet_pct(),
+            self.fold_to_three_bet_pct(),
+            self.cbet_pct(),
+            self.wtsd_pct(),
+            self.wsd_pct(),
+            self.aggression_factor()
+                .map(|af| format!("{:.2}", af))
+                .unwrap_or_default(),
+            self.bb_per_100(),
+            self.ev_bb_per_100()
+        )
+    }
+}
+
+pub const STATS_CSV_HEADER: &str =
+    "player,hands,vpip,pfr,3bet,fold_to_3bet,cbet,wtsd,wsd,af,bb_per_100,allin_ev_bb_per_100";
+
+fn pct(n: usize, of: usize) -> f64 {
+    if of == 0 {
+        return 0.0;
+    }
+    n as f64 * 100.0 / of as f64
+}
+
+fn per_100(won: f64, hands: usize) -> f64 {
+    if hands == 0 {
+        return 0.0;
+    }
+    won * 100.0 / hands as f64
+}
+
+/// Quote a player name if it would break the record
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        String::from(field)
+    }
+}
+
+/// `PlayerStats` by player name
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Stats {
+    pub players: BTreeMap<String, PlayerStats>,
+}
+
+impl Stats {
+    pub fn new() -> Self {
+        Stats::default()
+    }
+
+    /// Count every player of `history` who matches the `filter`
+    pub fn add(&mut self, history: &HandHistory, filter: &Filter) -> Result<()> {
+        if !filter.matches_hand(history) || history.big_blind == 0 {
+            return Ok(());
+        }
+        let states = replay(history)?;
+        let last = states.last().ok_or(Error::ParseError)?;
+        let ev = all_in_ev(history)?;
+        let positions = Position::of(history);
+        let bb = history.big_blind as f64;
+        let in_hand_at_end = last.folded.iter().filter(|folded| !**folded).count();
+
+        // preflop: who raised first, who re-raised and who raised last
+        let mut raises = 0;
+        let mut opener: Option<&str> = None;
+        let mut aggressor: Option<&str> = None;
+        let mut seen: BTreeMap<&str, HandFlags> = BTreeMap::new();
+        for action in history
+            .actions
+            .iter()
+            .filter(|a| a.street == Street::Preflop)
+        {
+            let flags = seen.entry(&action.player).or_default();
+            let aggressive = matches!(action.action, Action::Bet(_) | Action::Raise(_));
+            if action.action != Action::Fold && action.action != Action::Check {
+                flags.vpip = true;
+            }
+            if aggressive {
+                flags.pfr = true;
+            }
+            if raises == 1 && opener != Some(action.player.as_str()) && !flags.three_bet_chance {
+                flags.three_bet_chance = true;
+                flags.three_bet = aggressive;
+            }
+            if raises == 2 && opener == Some(action.player.as_str()) && !flags.faced_three_bet {
+                flags.faced_three_bet = true;
+                flags.folded_to_three_bet = action.action == Action::Fold;
+            }
+            if aggressive {
+                raises += 1;
+                if opener.is_none() {
+                    opener = Some(&action.player);
+                }
+                aggressor = Some(&action.player);
+            }
+        }
+
+        // the flop: the preflop aggressor gets a c-bet chance if nobody bet before them
+        let mut flop_bet = false;
+        for action in history.actions.iter().filter(|a| a.street == Street::Flop) {
+            let flags = seen.entry(&action.player).or_default();
+            if aggressor == Some(action.player.as_str()) && !flags.cbet_chance && !flop_bet {
+                flags.cbet_chance = true;
+                flags.cbet = matches!(action.action, Action::Bet(_));
+            }
+            if matches!(action.action, Action::Bet(_) | Action::Raise(_)) {
+                flop_bet = true;
+            }
+        }
+
+        let folded_preflop = |player: &str| {
+            history.actions.iter().any(|a| {
+                a.street == Street::Preflop && a.player == player && a.action == Action::Fold
+            })
+        };
+        for (i, seat) in history.seats.iter().enumerate() {
+            match positions[i] {
+                Some(position) if filter.matches_position(position) => {}
+                _ => continue,
+            }
+            let flags = seen.get(seat.name.as_str()).cloned().unwrap_or_default();
+            let stats = self.players.entry(seat.name.clone()).or_default();
+
+            stats.hands += 1;
+            stats.vpip += flags.vpip as usize;
+            stats.pfr += flags.pfr as usize;
+            stats.three_bet_chances += flags.three_bet_chance as usize;
+            stats.three_bets += flags.three_bet as usize;
+            stats.three_bets_faced += flags.faced_three_bet as usize;
+            stats.folds_to_three_bet += flags.folded_to_three_bet as usize;
+            stats.cbet_chances += flags.cbet_chance as usize;
+            stats.cbets += flags.cbet as usize;
+
+            let received: u64 = history
+                .collected
+                .iter()
+                .chain(history.uncalled.iter())
+                .filter(|(player, _)| player == &seat.name)
+                .map(|(_, chips)| chips)
+                .sum();
+            let saw_flop = history.board.len() >= 3 && !folded_preflop(&seat.name);
+            if saw_flop {
+                stats.saw_flop += 1;
+                if !last.folded[i] && in_hand_at_end > 1 {
+                    stats.showdowns += 1;
+                    let won: u64 = history
+                        .collected
+                        .iter()
+                        .filter(|(player, _)| player == &seat.name)
+                        .map(|(_, chips)| chips)
+                        .sum();
+                    stats.showdowns_won += (won > 0) as usize;
+                }
+            }
+            for action in history
+                .actions
+                .iter()
+                .filter(|a| a.player == seat.name && a.street > Street::Preflop)
+            {
+                match action.action {
+                    Action::Bet(_) | Action::Raise(_) => stats.aggressive += 1,
+                    Action::Call => stats.calls += 1,
+                    _ => {}
+                }
+            }
+
+            let net = received as f64 - last.contributed[i] as f64;
+            stats.won += net / bb;
+            stats.ev_won += match &ev {
+                Some(ev) => ev.expected[i] / bb,
+                None => net / bb,
+            };
+        }
+        Ok(())
+    }
+
+    /// Write one CSV record per player, sorted by name
+    pub fn write_csv<W: Write>(&self, output: &mut W) -> Result<()> {
+        writeln!(output, "{}", STATS_CSV_HEADER).map_err(Error::Io)?;
+        for (player, stats) in &self.players {
+            writeln!(output, "{}", stats.csv_record(player)).map_err(Error::Io)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<16} {:>6} {:>6} {:>6} {:>6} {:>6} {:>6} {:>6} {:>6} {:>5} {:>9} {:>9}",
+            "player",
+            "hands",
+            "vpip",
+            "pfr",
+            "3bet",
+            "f3bet",
+            "cbet",
+            "wtsd",
+            "wsd",
+            "af",
+            "bb/100",
+            "ev bb/100"
+        )?;
+        for (player, stats) in &self.players {
+            write!(
+                f,
+                "\n{:<16} {:>6} {:>6.1} {:>6.1} {:>6.1} {:>6.1} {:>6.1} {:>6.1} {:>6.1} {:>5} {:>9.2} {:>9.2}",
+                player,
+                stats.hands,
+                stats.vpip_pct(),
+                stats.pfr_pct(),
+                stats.three_bet_pct(),
+                stats.fold_to_three_bet_pct(),
+                stats.cbet_pct(),
+                stats.wtsd_pct(),
+                stats.wsd_pct(),
+                stats
+                    .aggression_factor()
+                    .map(|af| format!("{:.1}", af))
+                    .unwrap_or_else(|| String::from("-")),
+                stats.bb_per_100(),
+                stats.ev_bb_per_100()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// What a player did in a single hand
+#[derive(Clone, Debug, Default)]
+struct HandFlags {
+    vpip: bool,
+    pfr: bool,
+    three_bet_chance: bool,
+    three_bet: bool,
+    faced_three_bet: bool,
+    folded_to_three_bet: bool,
+    cbet_chance: bool,
+    cbet: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HAND: &str = "\
+PokerStars Hand #1: Hold'em No Limit ($0.05/$0.10 USD) - 2020/01/01 12:00:00 ET
+Table 'Alpha' 6-max Seat #1 is the button
+Seat 1: alice ($10.00 in chips)
+Seat 2: bob ($10.00 in chips)
+Seat 3: carol ($10.00 in chips)
+Seat 4: dave ($10.00 in chips)
+bob: posts small blind $0.05
+carol: posts big blind $0.10
+*** HOLE CARDS ***
+dave: raises $0.20 to $0.30
+alice: raises $0.60 to $0.90
+bob: folds
+carol: folds
+dave: calls $0.60
+*** FLOP *** [Kc 7d 2h]
+dave: checks
+alice: bets $1.00
+dave: calls $1.00
+*** TURN *** [Kc 7d 2h] [Ks]
+dave: checks
+alice: checks
+*** RIVER *** [Kc 7d 2h Ks] [7c]
+dave: bets $2.00
+alice: calls $2.00
+*** SHOW DOWN ***
+dave: shows [Ah Ad] (two pair, Aces and Kings)
+alice: shows [Qh Qd] (two pair, Queens and Kings)
+dave collected $7.95 from pot
+*** SUMMARY ***
+Total pot $7.95 | Rake $0
+Board [Kc 7d 2h Ks 7c]
+";
+
+    const FOLD: &str = "\
+PokerStars Hand #2: Hold'em No Limit ($0.05/$0.10 USD) - 2020/01/02 12:00:00 ET
+Table 'Alpha' 6-max Seat #2 is the button
+Seat 1: alice ($10.00 in chips)
+Seat 2: bob ($10.00 in chips)
+Seat 3: carol ($10.00 in chips)
+Seat 4: dave ($10.00 in chips)
+carol: posts small blind $0.05
+dave: posts big blind $0.10
+*** HOLE CARDS ***
+alice: raises $0.20 to $0.30
+bob: folds
+carol: folds
+dave: raises $0.70 to $1.00
+alice: folds
+Uncalled bet ($0.70) returned to dave
+dave collected $0.65 from pot
+*** SUMMARY ***
+Total pot $0.65 | Rake $0
+";
+
+    const ALL_IN: &str = "\
+PokerStars Hand #3: Hold'em No Limit ($0.05/$0.10 USD) - 2020/01/03 12:00:00 ET
+Table 'Alpha' 6-max Seat #1 is the button
+Seat 1: alice ($10.00 in chips)
+Seat 2: bob ($5.00 in chips)
+Seat 3: carol ($10.00 in chips)
+alice: posts small blind $0.05
+bob: posts big blind $0.10
+*** HOLE CARDS ***
+carol: folds
+alice: raises $0.20 to $0.30
+bob: calls $0.20
+*** FLOP *** [Kc 7d 2h]
+alice: bets $0.40
+bob: raises $4.30 to $4.70 and is all-in
+alice: calls $4.30
+*** TURN *** [Kc 7d 2h] [Ks]
+*** RIVER *** [Kc 7d 2h Ks] [7c]
+*** SHOW DOWN ***
+bob: shows [Kh Qh] (a full house, Kings full of Sevens)
+alice: shows [Ah Ad] (two pair, Aces and Kings)
+bob collected $9.80 from pot
+*** SUMMARY ***
+Total pot $10.00 | Rake $0.20
+Board [Kc 7d 2h Ks 7c]
+";
+
+    fn collect(filter: &Filter) -> Stats {
+        let mut stats = Stats::new();
+        for text in &[HAND, FOLD] {
+            stats
+                .add(&HandHistory::from(text).unwrap(), filter)
+                .unwrap();
+        }
+        stats
+    }
+
+    #[test]
+    fn positions() {
+        let history = HandHistory::from(HAND).unwrap();
+        assert_eq!(
+            Position::of(&history),
+            vec![
+                Some(Position::Button),
+                Some(Position::SmallBlind),
+                Some(Position::BigBlind),
+                Some(Position::Cutoff)
+            ]
+        );
+    }
+
+    #[test]
+    fn preflop() {
+        let stats = collect(&Filter::default());
+        let alice = &stats.players["alice"];
+        assert_eq!(alice.hands, 2);
+        assert_eq!(alice.vpip, 2);
+        assert_eq!(alice.pfr, 2);
+        // alice 3-bet dave's open, then folded her own open to dave's 3-bet
+        assert_eq!((alice.three_bets, alice.three_bet_chances), (1, 1));
+        assert_eq!((alice.folds_to_three_bet, alice.three_bets_faced), (1, 1));
+
+        let dave = &stats.players["dave"];
+        assert_eq!((dave.three_bets, dave.three_bet_chances), (1, 1));
+        assert_eq!((dave.folds_to_three_bet, dave.three_bets_faced), (0, 1));
+        assert_eq!(dave.vpip_pct(), 100.0);
+
+        let bob = &stats.players["bob"];
+        assert_eq!(bob.vpip, 0);
+        assert_eq!(bob.three_bet_chances, 1);
+    }
+
+    #[test]
+    fn postflop() {
+        let stats = collect(&Filter::default());
+        let alice = &stats.players["alice"];
+        assert_eq!((alice.cbets, alice.cbet_chances), (1, 1));
+        assert_eq!((alice.showdowns, alice.saw_flop), (1, 1));
+        assert_eq!(alice.showdowns_won, 0);
+        assert_eq!(alice.aggression_factor(), Some(1.0));
+
+        let dave = &stats.players["dave"];
+        assert_eq!(dave.wsd_pct(), 100.0);
+        assert_eq!(dave.cbet_chances, 0);
+    }
+
+    #[test]
+    fn winnings() {
+        let stats = collect(&Filter::default());
+        // alice lost $3.90 and $0.30, 42 big blinds in 2 hands
+        assert!((stats.players["alice"].won + 42.0).abs() < 1e-9);
+        assert!((stats.players["alice"].bb_per_100() + 2100.0).abs() < 1e-9);
+        assert!((stats.players["dave"].won - 40.5 - 3.5).abs() < 1e-9);
+        let total: f64 = stats.players.values().map(|p| p.won).sum();
+        assert!(total.abs() < 1e-9);
+        // no all-in, the EV equals the result
+        assert_eq!(stats.players["alice"].ev_won, stats.players["alice"].won);
+    }
+
+    #[test]
+    fn raked_all_in() {
+        let mut stats = Stats::new();
+        stats
+            .add(&HandHistory::from(ALL_IN).unwrap(), &Filter::default())
+            .unwrap();
+        let (alice, bob) = (&stats.players["alice"], &stats.players["bob"]);
+        assert!((alice.won + 50.0).abs() < 1e-9);
+        assert!((bob.won - 48.0).abs() < 1e-9);
+        // the 2 big blinds of rake come off the EV as well
+        assert!((alice.ev_won + bob.ev_won - alice.won - bob.won).abs() < 1e-6);
+        assert!(alice.ev_won > 0.0);
+    }
+
+    #[test]
+    fn filters() {
+        let stats = collect(&Filter {
+            positions: vec![Position::Button],
+            ..Filter::default()
+        });
+        assert_eq!(stats.players.len(), 2);
+        assert_eq!(stats.players["alice"].hands, 1);
+        assert_eq!(stats.players["bob"].hands, 1);
+
+        let stats = collect(&Filter {
+            from: Some(String::from("2020/01/02")),
+            ..Filter::default()
+        });
+        assert_eq!(stats.players["alice"].hands, 1);
+
+        let stats = collect(&Filter {
+            big_blind: Some(20),
+            ..Filter::default()
+        });
+        assert!(stats.players.is_empty());
+    }
+
+    #[test]
+    fn csv() {
+        let mut output: Vec<u8> = vec![];
+        collect(&Filter::default()).write_csv(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], STATS_CSV_HEADER);
+        assert!(
+            lines[1].starts_with("alice,2,100.00,100.00,100.00,100.00,100.00,100.00,0.00,1.00,")
+        );
+        assert!(lines.iter().all(|line| line.split(',').count() == 12));
+    }
+
+    #[test]
+    fn text() {
+        let text = collect(&Filter::default()).to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].starts_with("player"));
+        assert!(lines[1].starts_with("alice                 2  100.0  100.0"));
+    }
+}
@@ -0,0 +1,361 @@
+use crate::card::*;
+use crate::cli::parse_cards;
+use crate::deck::check_duplicates;
+use crate::equity::Equity;
+use crate::error::{Error, Result};
+use crate::game::Game;
+use crate::hilo::HiLoEquity;
+
+use itertools::Itertools;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::HashSet;
+
+/// A Seven Card Stud player's cards. `down` cards are only known to the player, `up` cards are
+/// visible to everybody at the table.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StudHand {
+    pub down: Vec<Card>,
+    pub up: Vec<Card>,
+}
+
+impl StudHand {
+    /// Parse `<DOWN>/<UP>[/<DOWN>]`: two down cards, one to four upcards and the down card of
+    /// seventh street, e.g. "AhKd/7c" on third street or "AhKd/7c8s9dTh/2c" on the river.
+    pub fn from(expr: &str) -> Result<Self> {
+        let parts: Vec<&str> = expr.split('/').collect();
+        if parts.len() < 2 || parts.len() > 3 {
+            return Err(Error::ParseError);
+        }
+        let mut down = parse_cards(parts[0])?;
+        let up = parse_cards(parts[1])?;
+        if down.len() != 2 || up.is_empty() || up.len() > 4 {
+            return Err(Error::ParseError);
+        }
+        if let Some(expr) = parts.get(2) {
+            let river = parse_cards(expr)?;
+            if river.len() != 1 || up.len() != 4 {
+                return Err(Error::ParseError);
+            }
+            down.extend(river);
+        }
+
+        let hand = StudHand { down, up };
+        check_duplicates(&hand.cards())?;
+        Ok(hand)
+    }
+
+    /// all of the player's cards, down cards first
+    pub fn cards(&self) -> Vec<Card> {
+        [&self.down[..], &self.up[..]].concat()
+    }
+
+    pub fn len(&self) -> usize {
+        self.down.len() + self.up.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.down.is_empty() && self.up.is_empty()
+    }
+}
+
+/// The hands at a Stud table and the cards which are known to be out of play, e.g. the upcards of
+/// players who folded
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StudTable {
+    pub hands: Vec<StudHand>,
+    pub dead: Vec<Card>,
+}
+
+impl StudTable {
+    /// The cards `player` knows can't be dealt anymore: the own hand, everybody else's upcards
+    /// and the dead cards
+    pub fn known_to(&self, player: usize) -> Vec<Card> {
+        let mut known = self.hands[player].cards();
+        for (i, hand) in self.hands.iter().enumerate() {
+            if i != player {
+                known.extend(hand.up.iter().copied());
+            }
+        }
+        known.extend(self.dead.iter().copied());
+        known
+    }
+
+    /// All cards at the table, down cards included
+    pub fn known(&self) -> Vec<Card> {
+        self.hands
+            .iter()
+            .flat_map(StudHand::cards)
+            .chain(self.dead.iter().copied())
+            .collect()
+    }
+
+    /// The player who has to bring in on third street: the lowest upcard, Aces high, ties broken
+    /// by `Suit::order`. `None` if a player has no upcard.
+    pub fn bring_in(&self) -> Option<usize> {
+        let mut lowest: Option<(usize, (Rank, usize))> = None;
+        for (i, hand) in self.hands.iter().enumerate() {
+            let card = hand.up.first()?;
+            let key = (card.rank, card.suit.order());
+            match lowest {
+                Some((_, other)) if other <= key => {}
+                _ => lowest = Some((i, key)),
+            }
+        }
+        lowest.map(|(i, _)| i)
+    }
+}
+
+/// Enumerate every way to deal hero and vilan up to seven cards out of the remaining `deck` and
+/// compare the `Strength` of their best five card hands in `game`.
+///
+/// `k` is the number of cards hero has to come. The number of runouts grows fast, before fifth
+/// street `sample_stud_combos` is the better choice.
+pub fn stud_combos(game: Game, hero: Vec<Card>, vilan: Vec<Card>, deck: HashSet<Card>) -> Equity {
+    let mut equity = Equity::new(
+        hero.clone(),
+        vilan.clone(),
+        vec![],
+        7 - hero.len(),
+        deck.len(),
+    );
+    enumerate_runouts(&hero, &vilan, &deck, |h, v| {
+        equity.add(game.strength(h, &[]), game.strength(v, &[]))
+    });
+    equity
+}
+
+/// Like `stud_combos` but only evaluates `samples` random runouts drawn with `rng`
+pub fn sample_stud_combos<R: Rng>(
+    game: Game,
+    hero: Vec<Card>,
+    vilan: Vec<Card>,
+    deck: HashSet<Card>,
+    samples: usize,
+    rng: &mut R,
+) -> Equity {
+    let mut equity = Equity::new(
+        hero.clone(),
+        vilan.clone(),
+        vec![],
+        7 - hero.len(),
+        deck.len(),
+    );
+    sample_runouts(&hero, &vilan, &deck, samples, rng, |h, v| {
+        equity.add(game.strength(h, &[]), game.strength(v, &[]))
+    });
+    equity
+}
+
+/// Enumerate every way to deal hero and vilan up to seven cards and split each pot between the
+/// best high and the best qualifying low hand of `game`, e.g. Stud Hi/Lo
+pub fn stud_hilo_combos(
+    game: Game,
+    hero: Vec<Card>,
+    vilan: Vec<Card>,
+    deck: HashSet<Card>,
+) -> HiLoEquity {
+    let mut equity = HiLoEquity::new(
+        hero.clone(),
+        vilan.clone(),
+        vec![],
+        7 - hero.len(),
+        deck.len(),
+    );
+    enumerate_runouts(&hero, &vilan, &deck, |h, v| {
+        let high = game.strength(h, &[]).cmp(&game.strength(v, &[]));
+        equity.add(high, game.low(h, &[]), game.low(v, &[]))
+    });
+    equity
+}
+
+/// Like `stud_hilo_combos` but only evaluates `samples` random runouts drawn with `rng`
+pub fn sample_stud_hilo_combos<R: Rng>(
+    game: Game,
+    hero: Vec<Card>,
+    vilan: Vec<Card>,
+    deck: HashSet<Card>,
+    samples: usize,
+    rng: &mut R,
+) -> HiLoEquity {
+    let mut equity = HiLoEquity::new(
+        hero.clone(),
+        vilan.clone(),
+        vec![],
+        7 - hero.len(),
+        deck.len(),
+    );
+    sample_runouts(&hero, &vilan, &deck, samples, rng, |h, v| {
+        let high = game.strength(h, &[]).cmp(&game.strength(v, &[]));
+        equity.add(high, game.low(h, &[]), game.low(v, &[]))
+    });
+    equity
+}
+
+/// Call `f` with hero's and vilan's seven cards for every way to complete both hands out of `deck`
+fn enumerate_runouts<F: FnMut(&[Card], &[Card])>(
+    hero: &[Card],
+    vilan: &[Card],
+    deck: &HashSet<Card>,
+    mut f: F,
+) {
+    let deck: Vec<Card> = deck.iter().copied().sorted().collect();
+    let mut h = hero.to_vec();
+    let mut v = vilan.to_vec();
+    for own in deck.iter().combinations(7 - hero.len()) {
+        h.truncate(hero.len());
+        h.extend(own.iter().copied());
+        let rest: Vec<&Card> = deck.iter().filter(|card| !own.contains(card)).collect();
+        for other in rest.into_iter().combinations(7 - vilan.len()) {
+            v.truncate(vilan.len());
+            v.extend(other.into_iter().copied());
+            f(&h, &v);
+        }
+    }
+}
+
+/// Call `f` with hero's and vilan's seven cards for `samples` random completions out of `deck`
+fn sample_runouts<R: Rng, F: FnMut(&[Card], &[Card])>(
+    hero: &[Card],
+    vilan: &[Card],
+    deck: &HashSet<Card>,
+    samples: usize,
+    rng: &mut R,
+    mut f: F,
+) {
+    let deck: Vec<Card> = deck.iter().copied().sorted().collect();
+    let k = 7 - hero.len();
+    let n = k + 7 - vilan.len();
+    let mut h = hero.to_vec();
+    let mut v = vilan.to_vec();
+    for _ in 0..samples {
+        let cards: Vec<Card> = deck.choose_multiple(rng, n).copied().collect();
+        h.truncate(hero.len());
+        h.extend_from_slice(&cards[..k]);
+        v.truncate(vilan.len());
+        v.extend_from_slice(&cards[k..]);
+        f(&h, &v);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn table(hands: &[&str], dead: &str) -> StudTable {
+        StudTable {
+            hands: hands.iter().map(|h| StudHand::from(h).unwrap()).collect(),
+            dead: parse_cards(dead).unwrap(),
+        }
+    }
+
+    #[test]
+    fn hand_from() {
+        let hand = StudHand::from("AhKd/7c").unwrap();
+        assert_eq!(hand.down, parse_cards("AhKd").unwrap());
+        assert_eq!(hand.up, parse_cards("7c").unwrap());
+
+        let hand = StudHand::from("AhKd/7c8s9dTh/2c").unwrap();
+        assert_eq!(hand.down, parse_cards("AhKd2c").unwrap());
+        assert_eq!(hand.len(), 7);
+
+        assert!(StudHand::from("AhKd7c").is_err());
+        assert!(StudHand::from("Ah/7c").is_err());
+        assert!(StudHand::from("AhKd/7c8s/2c").is_err());
+        assert!(StudHand::from("AhKd/Ah").is_err());
+    }
+
+    #[test]
+    fn upcards_are_dead_for_others() {
+        let table = table(&["AhKd/7c", "QsQd/2c", "JhJc/Ks"], "9d");
+        let known = table.known_to(0);
+        assert_eq!(known.len(), 6);
+        assert!(known.contains(&Card::from("Ks").unwrap()));
+        assert!(!known.contains(&Card::from("Qs").unwrap()));
+        assert_eq!(table.known().len(), 10);
+    }
+
+    #[test]
+    fn bring_in() {
+        assert_eq!(
+            table(&["AhKd/7c", "QsQd/2c", "JhJc/Ks"], "").bring_in(),
+            Some(1)
+        );
+        // Aces are high
+        assert_eq!(table(&["AhKd/Ac", "QsQd/Kc"], "").bring_in(), Some(1));
+        // clubs < diamonds < hearts < spades
+        assert_eq!(
+            table(&["AhKd/2s", "QsQd/2d", "JhJc/2h"], "").bring_in(),
+            Some(1)
+        );
+        assert_eq!(table(&["AhKd/2d", "QsQd/2c"], "").bring_in(), Some(1));
+        assert_eq!(StudTable::default().bring_in(), None);
+    }
+
+    #[test]
+    fn river() {
+        let table = table(&["AhAd/2c7s9dTh/3c", "KcKs/2d7d9hTs/3d"], "");
+        let deck = Game::Stud.remaining_cards(&table.known()).unwrap();
+        let equity = stud_combos(
+            Game::Stud,
+            table.hands[0].cards(),
+            table.hands[1].cards(),
+            deck,
+        );
+        assert_eq!(equity.combos, 1);
+        assert_eq!(equity.k, 0);
+        assert_eq!(equity.wins, 1);
+    }
+
+    #[test]
+    fn sixth_street() {
+        let table = table(&["AhAd/2c7s9dTh", "KcKs/2d7d9hTs"], "Ac");
+        let deck = Game::Stud.remaining_cards(&table.known()).unwrap();
+        let equity = stud_combos(
+            Game::Stud,
+            table.hands[0].cards(),
+            table.hands[1].cards(),
+            deck,
+        );
+        // hero draws one of 39 cards, vilan one of the remaining 38
+        assert_eq!(equity.combos, 39 * 38);
+        assert_eq!(equity.wins + equity.ties + equity.losses, equity.combos);
+        assert!(equity.equity() > 75.0 && equity.equity() < 85.0);
+    }
+
+    #[test]
+    fn sampling() {
+        let table = table(&["AhAd/2c", "KcKs/2d"], "");
+        let deck = Game::Stud.remaining_cards(&table.known()).unwrap();
+        let mut rng = StdRng::seed_from_u64(7);
+        let equity = sample_stud_combos(
+            Game::Stud,
+            table.hands[0].cards(),
+            table.hands[1].cards(),
+            deck,
+            2000,
+            &mut rng,
+        );
+        assert_eq!(equity.combos, 2000);
+        assert_eq!(equity.k, 4);
+        assert!(equity.equity() > 60.0 && equity.equity() < 75.0);
+    }
+
+    #[test]
+    fn hilo_river() {
+        // hero: an Ace high flush and the wheel, vilan: trips and no low
+        let table = table(&["Ah2h/3h4c5hKh/9d", "QsQd/Qc7s8sJd/Tc"], "");
+        let deck = Game::StudHiLo.remaining_cards(&table.known()).unwrap();
+        let equity = stud_hilo_combos(
+            Game::StudHiLo,
+            table.hands[0].cards(),
+            table.hands[1].cards(),
+            deck,
+        );
+        assert_eq!(equity.combos, 1);
+        assert_eq!(equity.hero_scoops, 1);
+        assert_eq!(equity.equity(), 100.0);
+    }
+}
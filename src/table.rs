@@ -0,0 +1,765 @@
+use crate::card::*;
+use crate::deck::check_duplicates;
+use crate::error::{Error, Result};
+use crate::hand::Hand;
+use crate::hand_rank::Strength;
+use crate::pot::{payouts, pots, Pot};
+use crate::raw_data::RawData;
+
+/// A player sitting at a `Table` with the chips in front of them
+#[derive(Clone, Debug, PartialEq)]
+pub struct Seat {
+    pub name: String,
+    pub stack: u64,
+}
+
+/// A No-Limit Hold'em table. Seats without chips sit out.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Table {
+    pub seats: Vec<Seat>,
+    /// index of the seat holding the dealer button
+    pub button: usize,
+    pub small_blind: u64,
+    pub big_blind: u64,
+    /// posted by every player before the blinds
+    pub ante: u64,
+}
+
+impl Table {
+    pub fn new(small_blind: u64, big_blind: u64, ante: u64) -> Self {
+        Table {
+            seats: vec![],
+            button: 0,
+            small_blind,
+            big_blind,
+            ante,
+        }
+    }
+
+    /// Add a player with `stack` chips in the next free seat
+    pub fn sit(&mut self, name: &str, stack: u64) {
+        self.seats.push(Seat {
+            name: String::from(name),
+            stack,
+        });
+    }
+
+    /// indices of the seats which have chips to play
+    pub fn active(&self) -> Vec<usize> {
+        (0..self.seats.len())
+            .filter(|i| self.seats[*i].stack > 0)
+            .collect()
+    }
+
+    /// Start a hand dealing hole cards and the board from the top of `deck`
+    pub fn start_hand(&self, deck: Vec<Card>) -> Result<HandState> {
+        HandState::new(self, deck)
+    }
+
+    /// Take over the stacks of a finished `hand` and pass the button on
+    pub fn finish(&mut self, hand: &HandState) -> Result<()> {
+        if !hand.is_over() {
+            return Err(Error::IllegalAction);
+        }
+        for (seat, stack) in self.seats.iter_mut().zip(hand.final_stacks()) {
+            seat.stack = stack;
+        }
+        self.move_button();
+        Ok(())
+    }
+
+    /// Move the button to the next seat which has chips
+    pub fn move_button(&mut self) {
+        let n = self.seats.len();
+        for i in 1..=n {
+            let seat = (self.button + i) % n;
+            if self.seats[seat].stack > 0 {
+                self.button = seat;
+                return;
+            }
+        }
+    }
+}
+
+/// The betting rounds of a hand
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Street {
+    Preflop,
+    Flop,
+    Turn,
+    River,
+    /// the hand is over, either everybody but one player folded or the cards are shown down
+    Showdown,
+}
+
+impl Street {
+    fn next(self) -> Self {
+        match self {
+            Street::Preflop => Street::Flop,
+            Street::Flop => Street::Turn,
+            Street::Turn => Street::River,
+            Street::River | Street::Showdown => Street::Showdown,
+        }
+    }
+
+    /// number of community cards after the street was dealt
+    fn board_size(self) -> usize {
+        match self {
+            Street::Preflop => 0,
+            Street::Flop => 3,
+            Street::Turn => 4,
+            Street::River | Street::Showdown => 5,
+        }
+    }
+}
+
+/// A player's decision. `Bet` and `Raise` carry the amount the player's bet on the current
+/// street is raised *to*, not the number of chips added.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Action {
+    Fold,
+    Check,
+    Call,
+    Bet(u64),
+    Raise(u64),
+}
+
+/// The kinds of forced bets
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PostKind {
+    SmallBlind,
+    BigBlind,
+    /// a dead small blind posted together with the big blind
+    SmallAndBigBlind,
+    Ante,
+}
+
+/// Whether a player still takes part in a hand
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Status {
+    Active,
+    Folded,
+    AllIn,
+    /// the player had no chips when the hand started
+    SittingOut,
+}
+
+/// A player's state during a hand, indexed like the `Table`'s seats
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlayerState {
+    /// chips behind
+    pub stack: u64,
+    /// chips put in on the current street
+    pub street_bet: u64,
+    /// chips put in during the whole hand, antes included
+    pub contributed: u64,
+    pub holding: Vec<Card>,
+    pub status: Status,
+    /// the player acted since the last full raise
+    acted: bool,
+    /// the player may still raise, which a short all-in raise doesn't allow players who already
+    /// acted
+    can_raise: bool,
+}
+
+impl PlayerState {
+    /// Move up to `amount` chips from the stack into the pot and return the chips actually put in
+    fn put(&mut self, amount: u64) -> u64 {
+        let amount = amount.min(self.stack);
+        self.stack -= amount;
+        self.street_bet += amount;
+        self.contributed += amount;
+        if self.stack == 0 && self.status == Status::Active {
+            self.status = Status::AllIn;
+        }
+        amount
+    }
+}
+
+/// What the player to act may do. Folding is always allowed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LegalActions {
+    pub check: bool,
+    /// the number of chips a call adds, possibly all-in for less
+    pub call: Option<u64>,
+    /// the smallest and largest amount a bet or raise may go to
+    pub raise: Option<(u64, u64)>,
+    /// whether an aggressive action is a `Bet` rather than a `Raise`
+    pub is_bet: bool,
+}
+
+impl LegalActions {
+    pub fn contains(&self, action: &Action) -> bool {
+        match action {
+            Action::Fold => true,
+            Action::Check => self.check,
+            Action::Call => self.call.is_some(),
+            Action::Bet(to) | Action::Raise(to) => {
+                self.is_bet == matches!(action, Action::Bet(_))
+                    && matches!(self.raise, Some((min, max)) if min <= *to && *to <= max)
+            }
+        }
+    }
+}
+
+/// The state of a No-Limit Hold'em hand from posting the blinds to the showdown
+#[derive(Clone, Debug, PartialEq)]
+pub struct HandState {
+    pub players: Vec<PlayerState>,
+    pub button: usize,
+    pub small_blind: Option<usize>,
+    pub big_blind: Option<usize>,
+    pub board: Vec<Card>,
+    pub street: Street,
+    /// the player whose turn it is, `None` once the hand is over
+    pub to_act: Option<usize>,
+    /// the highest bet on the current street
+    pub current_bet: u64,
+    /// the smallest increment a raise has to add to the `current_bet`
+    pub min_raise: u64,
+    /// the forced bets and the chips actually posted, which may be less when a player is all-in
+    pub posts: Vec<(usize, PostKind, u64)>,
+    /// every voluntary action in the order it was taken
+    pub history: Vec<(Street, usize, Action)>,
+    big_blind_size: u64,
+    deck: Vec<Card>,
+}
+
+impl HandState {
+    /// Post antes and blinds and deal two cards to every player with chips, one at a time
+    /// starting left of the button. The board is dealt from the remaining cards of `deck`.
+    pub fn new(table: &Table, deck: Vec<Card>) -> Result<Self> {
+        let active = table.active();
+        if active.len() < 2 || table.button >= table.seats.len() {
+            return Err(Error::IllegalAction);
+        }
+        if deck.len() < 2 * active.len() + 5 {
+            return Err(Error::ParseError);
+        }
+        check_duplicates(&deck)?;
+
+        let mut hand = HandState {
+            players: table
+                .seats
+                .iter()
+                .map(|seat| PlayerState {
+                    stack: seat.stack,
+                    street_bet: 0,
+                    contributed: 0,
+                    holding: vec![],
+                    status: if seat.stack > 0 {
+                        Status::Active
+                    } else {
+                        Status::SittingOut
+                    },
+                    acted: false,
+                    can_raise: true,
+                })
+                .collect(),
+            button: table.button,
+            small_blind: None,
+            big_blind: None,
+            board: vec![],
+            street: Street::Preflop,
+            to_act: None,
+            current_bet: 0,
+            min_raise: table.big_blind,
+            posts: vec![],
+            history: vec![],
+            big_blind_size: table.big_blind,
+            deck,
+        };
+        hand.deck.reverse();
+
+        let first = hand.next_in_hand(table.button);
+        for _ in 0..2 {
+            let mut seat = first;
+            for _ in 0..active.len() {
+                let card = hand.deck.pop().unwrap();
+                hand.players[seat].holding.push(card);
+                seat = hand.next_in_hand(seat);
+            }
+        }
+
+        if table.ante > 0 {
+            for i in &active {
+                let ante = hand.players[*i].put(table.ante);
+                hand.players[*i].street_bet -= ante;
+                hand.posts.push((*i, PostKind::Ante, ante));
+            }
+        }
+
+        // heads up the button posts the small blind
+        let small_blind =
+            if active.len() == 2 && hand.players[table.button].status != Status::SittingOut {
+                table.button
+            } else {
+                hand.next_in_hand(table.button)
+            };
+        let big_blind = hand.next_in_hand(small_blind);
+        let posted = hand.players[small_blind].put(table.small_blind);
+        hand.posts.push((small_blind, PostKind::SmallBlind, posted));
+        let posted = hand.players[big_blind].put(table.big_blind);
+        hand.posts.push((big_blind, PostKind::BigBlind, posted));
+        hand.small_blind = Some(small_blind);
+        hand.big_blind = Some(big_blind);
+        hand.current_bet = hand.players.iter().map(|p| p.street_bet).max().unwrap_or(0);
+
+        hand.to_act = hand.next_to_act(big_blind);
+        if hand.to_act.is_none() {
+            hand.end_street();
+        }
+        Ok(hand)
+    }
+
+    /// the size of the big blind, which is also the smallest bet
+    pub fn big_blind_size(&self) -> u64 {
+        self.big_blind_size
+    }
+
+    /// chips in the middle, bets of the current street included
+    pub fn pot(&self) -> u64 {
+        self.players.iter().map(|p| p.contributed).sum()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.street == Street::Showdown
+    }
+
+    /// What the player to act may do, `None` once the hand is over
+    pub fn legal_actions(&self) -> Option<LegalActions> {
+        let player = &self.players[self.to_act?];
+        let to_call = self.current_bet - player.street_bet;
+        let all_in = player.street_bet + player.stack;
+
+        let call = if to_call > 0 {
+            Some(to_call.min(player.stack))
+        } else {
+            None
+        };
+        let others_can_act = self
+            .players
+            .iter()
+            .enumerate()
+            .any(|(i, p)| Some(i) != self.to_act && p.status == Status::Active);
+        let raise = if player.can_raise && all_in > self.current_bet && others_can_act {
+            let min = if self.current_bet == 0 {
+                self.big_blind_size
+            } else {
+                self.current_bet + self.min_raise
+            };
+            Some((min.min(all_in), all_in))
+        } else {
+            None
+        };
+        Some(LegalActions {
+            check: to_call == 0,
+            call,
+            raise,
+            is_bet: self.current_bet == 0,
+        })
+    }
+
+    /// Apply `action` of the player to act and move the hand forward
+    pub fn act(&mut self, action: Action) -> Result<()> {
+        let seat = self.to_act.ok_or(Error::IllegalAction)?;
+        let legal = self.legal_actions().ok_or(Error::IllegalAction)?;
+        if !legal.contains(&action) {
+            return Err(Error::IllegalAction);
+        }
+
+        let current_bet = self.current_bet;
+        let player = &mut self.players[seat];
+        match action {
+            Action::Fold => player.status = Status::Folded,
+            Action::Check => {}
+            Action::Call => {
+                player.put(current_bet - player.street_bet);
+            }
+            Action::Bet(to) | Action::Raise(to) => {
+                player.put(to - player.street_bet);
+                let increment = to - current_bet;
+                self.current_bet = to;
+                // a full raise reopens the betting, a short all-in only has to be called
+                if increment >= self.min_raise {
+                    self.min_raise = increment;
+                    for other in self.players.iter_mut() {
+                        other.acted = false;
+                        other.can_raise = true;
+                    }
+                } else {
+                    for other in self.players.iter_mut() {
+                        other.acted = false;
+                    }
+                }
+            }
+        }
+        let player = &mut self.players[seat];
+        player.acted = true;
+        player.can_raise = false;
+        self.history.push((self.street, seat, action));
+
+        if self.in_hand().len() == 1 {
+            self.street = Street::Showdown;
+            self.to_act = None;
+            return Ok(());
+        }
+        self.to_act = self.next_to_act(seat);
+        if self.to_act.is_none() {
+            self.end_street();
+        }
+        Ok(())
+    }
+
+    /// indices of the players who didn't fold
+    pub fn in_hand(&self) -> Vec<usize> {
+        (0..self.players.len())
+            .filter(|i| matches!(self.players[*i].status, Status::Active | Status::AllIn))
+            .collect()
+    }
+
+    /// The `Strength` of `player`'s best hand with the current board
+    pub fn strength(&self, player: usize) -> Strength {
+        let own = RawData::from_chain(self.players[player].holding.iter().chain(self.board.iter()));
+        let combo = vec![];
+        Hand::new(&own, &combo).strength()
+    }
+
+    /// The players with the best hand among those who didn't fold, more than one on a split pot.
+    /// Empty until the hand is over.
+    pub fn winners(&self) -> Vec<usize> {
+        if !self.is_over() {
+            return vec![];
+        }
+        let in_hand = self.in_hand();
+        if in_hand.len() == 1 {
+            return in_hand;
+        }
+        let best = in_hand.iter().map(|i| self.strength(*i)).max();
+        in_hand
+            .into_iter()
+            .filter(|i| Some(self.strength(*i)) == best)
+            .collect()
+    }
+
+    /// The main pot and the side pots built from every player's contribution
+    pub fn pots(&self) -> Vec<Pot> {
+        let contributed: Vec<u64> = self.players.iter().map(|p| p.contributed).collect();
+        let folded: Vec<bool> = self
+            .players
+            .iter()
+            .map(|p| !matches!(p.status, Status::Active | Status::AllIn))
+            .collect();
+        pots(&contributed, &folded)
+    }
+
+    /// The chips every player wins once the hand is over, see `pot::payouts`. Bets nobody called
+    /// go back to the bettor.
+    pub fn payouts(&self) -> Vec<u64> {
+        if !self.is_over() {
+            return vec![0; self.players.len()];
+        }
+        let in_hand = self.in_hand();
+        let strengths: Vec<Strength> = (0..self.players.len())
+            .map(|i| {
+                if in_hand.len() > 1 && in_hand.contains(&i) {
+                    self.strength(i)
+                } else {
+                    Strength(0)
+                }
+            })
+            .collect();
+        payouts(&self.pots(), &strengths, self.button)
+    }
+
+    /// every player's stack after the `payouts`
+    pub fn final_stacks(&self) -> Vec<u64> {
+        self.players
+            .iter()
+            .zip(self.payouts())
+            .map(|(player, payout)| player.stack + payout)
+            .collect()
+    }
+
+    /// the next seat after `seat` which was dealt in
+    fn next_in_hand(&self, seat: usize) -> usize {
+        let n = self.players.len();
+        (1..=n)
+            .map(|i| (seat + i) % n)
+            .find(|i| self.players[*i].status != Status::SittingOut)
+            .unwrap_or(seat)
+    }
+
+    /// The next player after `seat` who has to act on the current street. A player acts if they
+    /// haven't matched the current bet or haven't acted yet while somebody else can still act.
+    fn next_to_act(&self, seat: usize) -> Option<usize> {
+        let n = self.players.len();
+        let active = self
+            .players
+            .iter()
+            .filter(|p| p.status == Status::Active)
+            .count();
+        (1..=n).map(|i| (seat + i) % n).find(|i| {
+            let player = &self.players[*i];
+            player.status == Status::Active
+                && (player.street_bet < self.current_bet || (!player.acted && active > 1))
+        })
+    }
+
+    /// Close the betting round and deal the next street, or run out the board if at most one
+    /// player can still bet
+    fn end_street(&mut self) {
+        loop {
+            for player in self.players.iter_mut() {
+                player.street_bet = 0;
+                player.acted = false;
+                player.can_raise = true;
+            }
+            self.current_bet = 0;
+            self.min_raise = self.big_blind_size;
+            self.street = self.street.next();
+            while self.board.len() < self.street.board_size() {
+                let card = self.deck.pop().unwrap();
+                self.board.push(card);
+            }
+            if self.street == Street::Showdown {
+                self.to_act = None;
+                return;
+            }
+            self.to_act = self.next_to_act(self.button);
+            if self.to_act.is_some() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::parse_cards;
+    use crate::deck::Deck;
+
+    /// A deck dealing `holdings` (one per player, starting left of the button) and then `board`
+    fn deck(holdings: &[&str], board: &str) -> Vec<Card> {
+        let holdings: Vec<Vec<Card>> = holdings.iter().map(|h| parse_cards(h).unwrap()).collect();
+        let mut deck: Vec<Card> = holdings.iter().map(|h| h[0]).collect();
+        deck.extend(holdings.iter().map(|h| h[1]));
+        deck.extend(parse_cards(board).unwrap());
+        deck
+    }
+
+    fn table(stacks: &[u64]) -> Table {
+        let mut table = Table::new(1, 2, 0);
+        for (i, stack) in stacks.iter().enumerate() {
+            table.sit(&format!("p{}", i), *stack);
+        }
+        table
+    }
+
+    #[test]
+    fn blinds_and_order() {
+        let table = table(&[100, 100, 100, 100]);
+        let hand = table
+            .start_hand(deck(&["AhAd", "KcKs", "QhQd", "JcJs"], "2c7d9hTs3c"))
+            .unwrap();
+        assert_eq!(hand.small_blind, Some(1));
+        assert_eq!(hand.big_blind, Some(2));
+        assert_eq!(hand.pot(), 3);
+        // under the gun acts first, the button got the last card
+        assert_eq!(hand.to_act, Some(3));
+        assert_eq!(hand.players[1].holding, parse_cards("AhAd").unwrap());
+        assert_eq!(hand.players[0].holding, parse_cards("JcJs").unwrap());
+    }
+
+    #[test]
+    fn heads_up() {
+        let table = table(&[100, 100]);
+        let mut hand = table
+            .start_hand(deck(&["AhAd", "KcKs"], "2c7d9hTs3c"))
+            .unwrap();
+        // the button posts the small blind and acts first preflop, last after the flop
+        assert_eq!(hand.small_blind, Some(0));
+        assert_eq!(hand.to_act, Some(0));
+        hand.act(Action::Call).unwrap();
+        assert_eq!(hand.to_act, Some(1));
+        hand.act(Action::Check).unwrap();
+        assert_eq!(hand.street, Street::Flop);
+        assert_eq!(hand.board.len(), 3);
+        assert_eq!(hand.to_act, Some(1));
+    }
+
+    #[test]
+    fn antes() {
+        let mut table = table(&[100, 100, 100]);
+        table.ante = 1;
+        let hand = table
+            .start_hand(deck(&["AhAd", "KcKs", "QhQd"], "2c7d9hTs3c"))
+            .unwrap();
+        assert_eq!(hand.pot(), 6);
+        assert_eq!(hand.current_bet, 2);
+        assert_eq!(hand.posts.len(), 5);
+        assert_eq!(hand.posts[3], (1, PostKind::SmallBlind, 1));
+        assert_eq!(hand.players[0].stack, 99);
+    }
+
+    #[test]
+    fn min_raise() {
+        let table = table(&[100, 100, 100]);
+        let mut hand = table
+            .start_hand(deck(&["AhAd", "KcKs", "QhQd"], "2c7d9hTs3c"))
+            .unwrap();
+        let legal = hand.legal_actions().unwrap();
+        assert!(!legal.check);
+        assert_eq!(legal.call, Some(2));
+        assert_eq!(legal.raise, Some((4, 100)));
+        assert!(hand.act(Action::Raise(3)).is_err());
+        assert!(hand.act(Action::Bet(4)).is_err());
+        assert!(hand.act(Action::Check).is_err());
+
+        hand.act(Action::Raise(10)).unwrap();
+        // the next raise has to add at least another 8
+        assert_eq!(hand.legal_actions().unwrap().raise, Some((18, 100)));
+        hand.act(Action::Fold).unwrap();
+        hand.act(Action::Call).unwrap();
+        assert_eq!(hand.street, Street::Flop);
+        assert_eq!(hand.pot(), 21);
+
+        // the big blind acts first after the flop
+        assert_eq!(hand.to_act, Some(2));
+        let legal = hand.legal_actions().unwrap();
+        assert!(legal.check && legal.is_bet);
+        assert_eq!(legal.raise, Some((2, 90)));
+    }
+
+    #[test]
+    fn short_all_in_doesnt_reopen() {
+        let table = table(&[100, 100, 13]);
+        let mut hand = table
+            .start_hand(deck(&["AhAd", "KcKs", "QhQd"], "2c7d9hTs3c"))
+            .unwrap();
+        // button raises to 10, small blind calls, big blind is all-in for 13
+        hand.act(Action::Raise(10)).unwrap();
+        hand.act(Action::Call).unwrap();
+        hand.act(Action::Raise(13)).unwrap();
+        assert_eq!(hand.players[2].status, Status::AllIn);
+        // the button already acted and may only call or fold
+        assert_eq!(hand.to_act, Some(0));
+        let legal = hand.legal_actions().unwrap();
+        assert_eq!(legal.call, Some(3));
+        assert_eq!(legal.raise, None);
+    }
+
+    #[test]
+    fn fold_wins() {
+        let mut table = table(&[100, 100, 100]);
+        let mut hand = table
+            .start_hand(deck(&["AhAd", "KcKs", "QhQd"], "2c7d9hTs3c"))
+            .unwrap();
+        hand.act(Action::Raise(6)).unwrap();
+        hand.act(Action::Fold).unwrap();
+        hand.act(Action::Fold).unwrap();
+        assert!(hand.is_over());
+        assert_eq!(hand.winners(), vec![0]);
+        assert_eq!(hand.payouts(), vec![9, 0, 0]);
+
+        table.finish(&hand).unwrap();
+        assert_eq!(
+            table.seats.iter().map(|s| s.stack).collect::<Vec<u64>>(),
+            vec![103, 99, 98]
+        );
+        assert_eq!(table.button, 1);
+    }
+
+    #[test]
+    fn showdown() {
+        let table = table(&[100, 100, 100]);
+        let mut hand = table
+            .start_hand(deck(&["AhAd", "KcKs", "QhQd"], "2c7d9hTs3c"))
+            .unwrap();
+        hand.act(Action::Call).unwrap();
+        hand.act(Action::Call).unwrap();
+        hand.act(Action::Check).unwrap();
+        for _ in 0..3 {
+            for _ in 0..3 {
+                hand.act(Action::Check).unwrap();
+            }
+        }
+        assert!(hand.is_over());
+        assert_eq!(hand.board, parse_cards("2c7d9hTs3c").unwrap());
+        // small blind holds the Aces
+        assert_eq!(hand.winners(), vec![1]);
+        assert_eq!(hand.final_stacks(), vec![98, 104, 98]);
+    }
+
+    #[test]
+    fn all_in_runs_out_the_board() {
+        let table = table(&[50, 100]);
+        let mut hand = table
+            .start_hand(deck(&["KcKs", "AhAd"], "2c7d9hTs3c"))
+            .unwrap();
+        // the short stacked button holds the Aces
+        hand.act(Action::Raise(50)).unwrap();
+        hand.act(Action::Call).unwrap();
+        assert!(hand.is_over());
+        assert_eq!(hand.board.len(), 5);
+        assert_eq!(hand.winners(), vec![0]);
+        assert_eq!(hand.final_stacks(), vec![100, 50]);
+    }
+
+    #[test]
+    fn side_pot() {
+        let table = table(&[20, 100, 100]);
+        let mut hand = table
+            .start_hand(deck(&["KcKs", "QhQd", "AhAd"], "2c7d9hTs3c"))
+            .unwrap();
+        // the short stacked button holds the Aces and wins the main pot only
+        hand.act(Action::Raise(20)).unwrap();
+        hand.act(Action::Raise(60)).unwrap();
+        hand.act(Action::Call).unwrap();
+        hand.act(Action::Check).unwrap();
+        hand.act(Action::Bet(40)).unwrap();
+        hand.act(Action::Call).unwrap();
+        assert!(hand.is_over());
+
+        let pots = hand.pots();
+        assert_eq!(pots.len(), 2);
+        assert_eq!(pots[0].amount, 60);
+        assert_eq!(pots[1].amount, 160);
+        assert_eq!(hand.final_stacks(), vec![60, 160, 0]);
+    }
+
+    #[test]
+    fn split_pot() {
+        let table = table(&[100, 100, 100]);
+        let mut hand = table
+            .start_hand(deck(&["AhKd", "AdKc", "2h3h"], "AsKs7c7d7h"))
+            .unwrap();
+        hand.act(Action::Fold).unwrap();
+        hand.act(Action::Call).unwrap();
+        hand.act(Action::Check).unwrap();
+        for _ in 0..3 {
+            hand.act(Action::Check).unwrap();
+            hand.act(Action::Check).unwrap();
+        }
+        assert_eq!(hand.winners(), vec![1, 2]);
+        assert_eq!(hand.payouts(), vec![0, 2, 2]);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(table(&[100, 0])
+            .start_hand(Deck::default().cards.to_vec())
+            .is_err());
+        assert!(table(&[100, 100])
+            .start_hand(parse_cards("AhAdKcKs").unwrap())
+            .is_err());
+
+        let mut hand = table(&[100, 100])
+            .start_hand(deck(&["AhAd", "KcKs"], "2c7d9hTs3c"))
+            .unwrap();
+        hand.act(Action::Fold).unwrap();
+        assert!(hand.act(Action::Check).is_err());
+        assert_eq!(hand.legal_actions(), None);
+    }
+}
@@ -0,0 +1,265 @@
+use crate::card::*;
+use crate::deck::check_duplicates;
+use crate::error::{Error, Result};
+use crate::hand_rank::{HandRank, Strength};
+use crate::nuts::NutRanking;
+use crate::range::Combo;
+use std::fmt;
+
+/// How the ranks of a board pair up
+#[derive(Copy, Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Pairing {
+    Unpaired,
+    Paired,
+    TwoPair,
+    Trips,
+    FullHouse,
+    Quads,
+}
+
+/// How the suits of a board are distributed
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Suitedness {
+    /// every card has a different suit
+    Rainbow,
+    /// neither rainbow nor monotone, e.g. two cards of one suit on the flop
+    TwoTone,
+    /// every card has the same suit
+    Monotone,
+}
+
+/// The texture of a Hold'em flop, turn or river
+#[derive(Clone, Debug, PartialEq)]
+pub struct Texture {
+    pub board: Vec<Card>,
+    pub pairing: Pairing,
+    pub suitedness: Suitedness,
+    /// the most cards of a single suit
+    pub suited: usize,
+    /// the most distinct ranks within five consecutive ones, A2345 included
+    pub connected: usize,
+    /// the high card of every straight a holding can make, highest first
+    pub straights: Vec<Rank>,
+    pub high: Rank,
+    /// the best hand any holding makes
+    pub nuts: Strength,
+    /// every holding making the nuts, higher card first
+    pub nut_combos: Vec<Combo>,
+}
+
+impl Texture {
+    /// Analyse a board of three to five cards
+    pub fn new(board: &[Card]) -> Result<Self> {
+        if board.len() < 3 || board.len() > 5 {
+            return Err(Error::ParseError);
+        }
+        check_duplicates(board)?;
+
+        let mut num_ranks = [0; 13];
+        let mut num_suits = [0; 4];
+        for card in board.iter() {
+            num_ranks[card.rank as usize] += 1;
+            num_suits[card.suit as usize] += 1;
+        }
+        let mut groups: Vec<usize> = num_ranks.iter().copied().filter(|n| *n > 1).collect();
+        groups.sort_unstable_by(|a, b| b.cmp(a));
+        let pairing = match groups[..] {
+            [] => Pairing::Unpaired,
+            [2] => Pairing::Paired,
+            [2, 2] => Pairing::TwoPair,
+            [3] => Pairing::Trips,
+            [3, 2] => Pairing::FullHouse,
+            _ => Pairing::Quads,
+        };
+
+        let suited = *num_suits.iter().max().unwrap();
+        let suitedness = if suited == 1 {
+            Suitedness::Rainbow
+        } else if suited == board.len() {
+            Suitedness::Monotone
+        } else {
+            Suitedness::TwoTone
+        };
+
+        // straights by their high card, Five (3) being the wheel
+        let mut connected = 0;
+        let mut straights = vec![];
+        for high in (3i32..13).rev() {
+            let ranks = (high - 4..=high).filter(|rank| {
+                // -1 is the Ace playing low
+                let rank = if *rank < 0 { 12 } else { *rank as usize };
+                num_ranks[rank] > 0
+            });
+            let count = ranks.count();
+            connected = connected.max(count);
+            if count >= 3 {
+                straights.push(Rank::from(high as usize));
+            }
+        }
+
+        let ranking = NutRanking::new(board)?;
+        let (nuts, nut_combos) = ranking.nuts();
+
+        Ok(Texture {
+            board: board.to_vec(),
+            pairing,
+            suitedness,
+            suited,
+            connected,
+            straights,
+            high: board.iter().map(|card| card.rank).max().unwrap(),
+            nuts,
+            nut_combos: nut_combos.to_vec(),
+        })
+    }
+
+    pub fn is_paired(&self) -> bool {
+        self.pairing != Pairing::Unpaired
+    }
+
+    /// Return whether a holding can make a flush
+    pub fn flush_possible(&self) -> bool {
+        self.suited >= 3
+    }
+
+    /// Return whether two suited hole cards can draw to a flush
+    pub fn flush_draw_possible(&self) -> bool {
+        self.board.len() < 5 && self.suited >= 2
+    }
+
+    /// Return whether a holding can make a straight
+    pub fn straight_possible(&self) -> bool {
+        !self.straights.is_empty()
+    }
+
+    /// Return whether two hole cards can draw to a straight
+    pub fn straight_draw_possible(&self) -> bool {
+        self.board.len() < 5 && self.connected >= 2
+    }
+
+    /// A rough measure from 0 (K72 rainbow) to 10 of how many strong hands and draws the board
+    /// allows: flushes and flush draws, possible straights, connected cards with cards to come,
+    /// and fewer of each once the board pairs.
+    pub fn wetness(&self) -> u32 {
+        let mut score: i32 = 0;
+        if self.flush_possible() {
+            score += 4;
+        } else if self.flush_draw_possible() {
+            score += 2;
+        }
+        score += self.straights.len().min(3) as i32;
+        if self.straight_draw_possible() {
+            score += 1;
+        }
+        score -= match self.pairing {
+            Pairing::Unpaired => 0,
+            Pairing::Paired => 1,
+            _ => 2,
+        };
+        score.clamp(0, 10) as u32
+    }
+}
+
+impl fmt::Display for Texture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cards = |cards: &[Card]| cards.iter().map(|c| c.notation()).collect::<String>();
+        let straights: Vec<String> = self.straights.iter().map(Rank::to_string).collect();
+        writeln!(f, "board: {}", cards(&self.board))?;
+        writeln!(f, "high card: {}", self.high)?;
+        writeln!(f, "pairing: {:?}", self.pairing)?;
+        writeln!(f, "suits: {:?}", self.suitedness)?;
+        writeln!(
+            f,
+            "flush: {}, flush draw: {}",
+            self.flush_possible(),
+            self.flush_draw_possible()
+        )?;
+        writeln!(
+            f,
+            "connected: {}, straights: {}, straight draw: {}",
+            self.connected,
+            if straights.is_empty() {
+                "none".to_string()
+            } else {
+                straights.join(",")
+            },
+            self.straight_draw_possible()
+        )?;
+        writeln!(f, "wetness: {}/10", self.wetness())?;
+        let combos: Vec<String> = self.nut_combos.iter().map(|c| cards(c)).collect();
+        write!(
+            f,
+            "nuts: {} ({})",
+            HandRank::from(self.nuts.category()),
+            combos.join(",")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::parse_cards;
+
+    fn texture(expr: &str) -> Texture {
+        Texture::new(&parse_cards(expr).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn dry_flop() {
+        let t = texture("Kc7d2h");
+        assert_eq!(t.pairing, Pairing::Unpaired);
+        assert_eq!(t.suitedness, Suitedness::Rainbow);
+        assert_eq!(t.high, Rank::King);
+        assert_eq!(t.connected, 1);
+        assert!(!t.straight_possible() && !t.straight_draw_possible());
+        assert!(!t.flush_possible() && !t.flush_draw_possible());
+        assert_eq!(t.wetness(), 0);
+        // top set
+        assert_eq!(t.nuts.category(), 3);
+        assert_eq!(t.nut_combos.len(), 3);
+    }
+
+    #[test]
+    fn wet_flop() {
+        let t = texture("Jh9hTh");
+        assert_eq!(t.suitedness, Suitedness::Monotone);
+        assert_eq!(t.connected, 3);
+        assert_eq!(t.straights, vec![Rank::King, Rank::Queen, Rank::Jack]);
+        assert!(t.flush_possible() && t.straight_draw_possible());
+        assert_eq!(t.wetness(), 8);
+        // KhQh makes the King high straight flush
+        let combo = parse_cards("KhQh").unwrap();
+        assert_eq!(t.nut_combos, vec![[combo[0], combo[1]]]);
+        assert!(texture("9c8d7d").wetness() > texture("9c8d2d").wetness());
+    }
+
+    #[test]
+    fn paired_and_wheel() {
+        let t = texture("8c8d4d");
+        assert_eq!(t.pairing, Pairing::Paired);
+        assert_eq!(t.suitedness, Suitedness::TwoTone);
+        // only one combo makes quads
+        assert_eq!(t.nuts.category(), 7);
+        assert_eq!(t.nut_combos.len(), 1);
+
+        let t = texture("Ac2d3h5sKs");
+        assert_eq!(t.straights.last(), Some(&Rank::Five));
+        assert!(!t.flush_draw_possible() && !t.straight_draw_possible());
+        assert_eq!(t.nuts.category(), 4);
+        assert_eq!(t.nuts.kickers()[0], Rank::Six);
+        assert_eq!(texture("7c7d7h7s2c").pairing, Pairing::Quads);
+        assert_eq!(texture("7c7d7h2s2c").pairing, Pairing::FullHouse);
+    }
+
+    #[test]
+    fn errors() {
+        let cards = parse_cards("AcKcQcJcTc9c").unwrap();
+        assert!(matches!(Texture::new(&cards[..2]), Err(Error::ParseError)));
+        assert!(matches!(Texture::new(&cards), Err(Error::ParseError)));
+        assert!(matches!(
+            Texture::new(&[cards[0], cards[1], cards[0]]),
+            Err(Error::DuplicateCard)
+        ));
+    }
+}
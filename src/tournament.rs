@@ -0,0 +1,510 @@
+use crate::error::{Error, Result};
+use crate::player::{play_hand, Player};
+use crate::table::Table;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+/// The blinds and the ante of a blind level, and for how many hands they're played
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Level {
+    pub small_blind: u64,
+    pub big_blind: u64,
+    pub ante: u64,
+    /// the number of hands every table plays at this level
+    pub hands: usize,
+}
+
+/// A common blind structure, from 10/20 to 3000/6000 with antes from the sixth level on
+pub fn blind_schedule(hands: usize) -> Vec<Level> {
+    [
+        (10, 20, 0),
+        (15, 30, 0),
+        (25, 50, 0),
+        (50, 100, 0),
+        (75, 150, 0),
+        (100, 200, 25),
+        (150, 300, 25),
+        (200, 400, 50),
+        (300, 600, 75),
+        (400, 800, 100),
+        (600, 1200, 200),
+        (1000, 2000, 300),
+        (1500, 3000, 400),
+        (2000, 4000, 500),
+        (3000, 6000, 1000),
+    ]
+    .iter()
+    .map(|(small_blind, big_blind, ante)| Level {
+        small_blind: *small_blind,
+        big_blind: *big_blind,
+        ante: *ante,
+        hands,
+    })
+    .collect()
+}
+
+/// How a freezeout tournament is played and paid
+#[derive(Clone, Debug, PartialEq)]
+pub struct Structure {
+    pub buy_in: f64,
+    /// the starting stack
+    pub stack: u64,
+    /// the most players seated at a table
+    pub table_size: usize,
+    /// the last level lasts until the tournament is over
+    pub levels: Vec<Level>,
+    /// the shares of the prize pool, i.e. of every buy-in, paid to the first places; they sum to 1
+    pub payouts: Vec<f64>,
+}
+
+impl Structure {
+    /// A single table turbo paying the top three
+    pub fn sit_and_go() -> Self {
+        Structure {
+            buy_in: 10.0,
+            stack: 1500,
+            table_size: 9,
+            levels: blind_schedule(10),
+            payouts: vec![0.5, 0.3, 0.2],
+        }
+    }
+
+    /// Nine handed tables paying about the top 15% of `entrants`, each place paying in
+    /// proportion to `1 / place`
+    pub fn multi_table(entrants: usize) -> Self {
+        let paid = (entrants * 15 / 100).max(1);
+        let total: f64 = (1..=paid).map(|place| 1.0 / place as f64).sum();
+        Structure {
+            buy_in: 10.0,
+            stack: 3000,
+            table_size: 9,
+            levels: blind_schedule(20),
+            payouts: (1..=paid).map(|place| 1.0 / place as f64 / total).collect(),
+        }
+    }
+
+    /// The level of the `round`th hand of a table
+    pub fn level(&self, round: usize) -> &Level {
+        let mut start = 0;
+        for level in self.levels.iter() {
+            start += level.hands;
+            if round < start {
+                return level;
+            }
+        }
+        self.levels.last().unwrap()
+    }
+
+    /// The prize of the finishing `place`, 1 for the winner, among `entrants`. With fewer entrants
+    /// than paid places, the shares of the places left are scaled up to pay out the whole pool.
+    pub fn prize(&self, place: usize, entrants: usize) -> f64 {
+        let paid = &self.payouts[..entrants.min(self.payouts.len())];
+        let share = match paid.get(place.wrapping_sub(1)) {
+            Some(share) => share / paid.iter().sum::<f64>(),
+            None => return 0.0,
+        };
+        share * self.buy_in * entrants as f64
+    }
+}
+
+/// How every entrant finished a tournament, indexed like the players
+#[derive(Clone, Debug, PartialEq)]
+pub struct Standings {
+    /// 1 for the winner
+    pub places: Vec<usize>,
+    pub prizes: Vec<f64>,
+    /// the hands dealt at all tables
+    pub hands: usize,
+}
+
+/// A table of a tournament and the players in its seats
+#[derive(Clone, Debug)]
+struct Seating {
+    table: Table,
+    players: Vec<usize>,
+}
+
+impl Seating {
+    /// Take a player and their chips from the table, keeping the button where it is
+    fn remove(&mut self, seat: usize) -> (usize, u64) {
+        let stack = self.table.seats.remove(seat).stack;
+        if seat < self.table.button {
+            self.table.button -= 1;
+        }
+        if self.table.button >= self.table.seats.len() {
+            self.table.button = 0;
+        }
+        (self.players.remove(seat), stack)
+    }
+
+    fn add(&mut self, player: usize, name: &str, stack: u64) {
+        self.table.sit(name, stack);
+        self.players.push(player);
+    }
+}
+
+/// Break tables until no fewer are needed for the remaining players, moving their players to the
+/// shortest tables, then move players from the longest to the shortest table until the sizes
+/// differ by one at most. Balancing moves the player due for the big blind next.
+fn balance(tables: &mut Vec<Seating>, table_size: usize, players: &[Box<dyn Player>]) {
+    let remaining: usize = tables.iter().map(|t| t.players.len()).sum();
+    let needed = remaining.div_ceil(table_size).max(1);
+    let shortest = |tables: &[Seating]| {
+        (0..tables.len())
+            .min_by_key(|i| tables[*i].players.len())
+            .unwrap()
+    };
+    while tables.len() > needed {
+        let broken = tables.remove(shortest(tables));
+        for (player, seat) in broken.players.iter().zip(broken.table.seats.iter()) {
+            let to = shortest(tables);
+            tables[to].add(*player, players[*player].name(), seat.stack);
+        }
+    }
+    loop {
+        let longest = (0..tables.len())
+            .max_by_key(|i| tables[*i].players.len())
+            .unwrap();
+        let to = shortest(tables);
+        if tables[longest].players.len() <= tables[to].players.len() + 1 {
+            return;
+        }
+        let from = &mut tables[longest];
+        let seat = (from.table.button + 2) % from.players.len();
+        let (player, stack) = from.remove(seat);
+        tables[to].add(player, players[player].name(), stack);
+    }
+}
+
+/// A freezeout tournament between agents
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tournament {
+    pub structure: Structure,
+    /// seeds the seating and the decks, the same seed plays the same tournaments
+    pub seed: u64,
+}
+
+impl Tournament {
+    /// Seat the `players` at random and play until one of them has every chip. Players busting
+    /// in the same round of hands finish in the order of the stacks they started the hand with.
+    pub fn play<R: Rng>(&self, players: &mut [Box<dyn Player>], rng: &mut R) -> Result<Standings> {
+        let structure = &self.structure;
+        let n = players.len();
+        // two hole cards each and the board come from a single deck
+        if n < 2 || structure.table_size < 2 || structure.table_size > 23 {
+            return Err(Error::IllegalAction);
+        }
+        if structure.levels.is_empty() || structure.stack == 0 {
+            return Err(Error::ParseError);
+        }
+
+        let mut order: Vec<usize> = (0..n).collect();
+        order.shuffle(rng);
+        let count = n.div_ceil(structure.table_size);
+        let mut tables: Vec<Seating> = (0..count)
+            .map(|_| Seating {
+                table: Table::new(0, 0, 0),
+                players: vec![],
+            })
+            .collect();
+        for (i, player) in order.into_iter().enumerate() {
+            tables[i % count].add(player, players[player].name(), structure.stack);
+        }
+        for seating in tables.iter_mut() {
+            seating.table.button = rng.gen_range(0, seating.players.len());
+        }
+
+        let mut places = vec![0; n];
+        let mut remaining = n;
+        let mut hands = 0;
+        let mut round = 0;
+        while remaining > 1 {
+            balance(&mut tables, structure.table_size, players);
+            let level = structure.level(round);
+            let mut busted: Vec<(usize, u64)> = vec![];
+            for seating in tables.iter_mut().filter(|t| t.players.len() > 1) {
+                let table = &mut seating.table;
+                table.small_blind = level.small_blind;
+                table.big_blind = level.big_blind;
+                table.ante = level.ante;
+                let before: Vec<u64> = table.seats.iter().map(|seat| seat.stack).collect();
+                let hand = play_hand(table, players, &seating.players, rng)?;
+                table.finish(&hand)?;
+                hands += 1;
+                for seat in (0..before.len()).rev() {
+                    if seating.table.seats[seat].stack == 0 {
+                        busted.push((seating.remove(seat).0, before[seat]));
+                    }
+                }
+            }
+            busted.sort_by_key(|(_, stack)| *stack);
+            for (player, _) in busted {
+                places[player] = remaining;
+                remaining -= 1;
+            }
+            round += 1;
+        }
+        let winner = tables.iter().find_map(|t| t.players.first()).unwrap();
+        places[*winner] = 1;
+
+        Ok(Standings {
+            prizes: places.iter().map(|p| structure.prize(*p, n)).collect(),
+            places,
+            hands,
+        })
+    }
+
+    /// Play `tournaments` tournaments and gather the results of every agent, entrants with the
+    /// same name counting as one agent
+    pub fn run(
+        &self,
+        players: &mut [Box<dyn Player>],
+        tournaments: usize,
+    ) -> Result<Vec<TournamentResult>> {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let n = players.len();
+        let mut results: Vec<TournamentResult> = vec![];
+        let agents: Vec<usize> = players
+            .iter()
+            .map(
+                |player| match results.iter().position(|r| r.name == player.name()) {
+                    Some(agent) => agent,
+                    None => {
+                        results.push(TournamentResult {
+                            name: player.name().to_string(),
+                            buy_in: self.structure.buy_in,
+                            entries: 0,
+                            finishes: vec![0; n],
+                            prizes: 0.0,
+                            prizes_squared: 0.0,
+                        });
+                        results.len() - 1
+                    }
+                },
+            )
+            .collect();
+
+        for _ in 0..tournaments {
+            let standings = self.play(players, &mut rng)?;
+            for (player, agent) in agents.iter().enumerate() {
+                let result = &mut results[*agent];
+                let prize = standings.prizes[player];
+                result.entries += 1;
+                result.finishes[standings.places[player] - 1] += 1;
+                result.prizes += prize;
+                result.prizes_squared += prize * prize;
+            }
+        }
+        Ok(results)
+    }
+}
+
+/// An agent's results over many tournaments
+#[derive(Clone, Debug, PartialEq)]
+pub struct TournamentResult {
+    pub name: String,
+    pub buy_in: f64,
+    pub entries: usize,
+    /// how often the agent finished first, second, ...
+    pub finishes: Vec<usize>,
+    pub prizes: f64,
+    /// the sum of the squared prizes per entry
+    pub prizes_squared: f64,
+}
+
+impl TournamentResult {
+    /// The share of entries finishing in each place
+    pub fn distribution(&self) -> Vec<f64> {
+        self.finishes
+            .iter()
+            .map(|count| *count as f64 / self.entries.max(1) as f64)
+            .collect()
+    }
+
+    /// The average finishing place
+    pub fn average_place(&self) -> f64 {
+        let total: usize = self
+            .finishes
+            .iter()
+            .enumerate()
+            .map(|(i, count)| (i + 1) * count)
+            .sum();
+        total as f64 / self.entries.max(1) as f64
+    }
+
+    /// The share of entries finishing in the top `paid` places
+    pub fn in_the_money(&self, paid: usize) -> f64 {
+        self.distribution().iter().take(paid).sum()
+    }
+
+    /// The return on investment in percent, the average profit per entry over the buy-in
+    pub fn roi(&self) -> f64 {
+        if self.entries == 0 {
+            return 0.0;
+        }
+        (self.prizes / (self.entries as f64 * self.buy_in) - 1.0) * 100.0
+    }
+
+    /// The standard error of the return on investment in percent
+    pub fn std_error(&self) -> f64 {
+        if self.entries < 2 {
+            return 0.0;
+        }
+        let n = self.entries as f64;
+        let mean = self.prizes / n;
+        let variance = (self.prizes_squared / n - mean * mean) * n / (n - 1.0);
+        100.0 * (variance.max(0.0) / n).sqrt() / self.buy_in
+    }
+
+    /// The interval around the return on investment within `z` standard errors, e.g. 1.96 for
+    /// 95%
+    pub fn confidence_interval(&self, z: f64) -> (f64, f64) {
+        let (roi, error) = (self.roi(), self.std_error());
+        (roi - z * error, roi + z * error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::{CallingStation, RandomPlayer, TightAggressive};
+
+    fn field(n: usize) -> Vec<Box<dyn Player>> {
+        (0..n)
+            .map(|i| -> Box<dyn Player> {
+                if i % 2 == 0 {
+                    Box::new(TightAggressive::default())
+                } else {
+                    Box::new(CallingStation)
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn levels() {
+        let structure = Structure::sit_and_go();
+        assert_eq!(structure.level(0).big_blind, 20);
+        assert_eq!(structure.level(9).big_blind, 20);
+        assert_eq!(structure.level(10).big_blind, 30);
+        assert_eq!(structure.level(55).ante, 25);
+        assert_eq!(structure.level(100_000).big_blind, 6000);
+        assert_eq!(structure.prize(1, 9), 45.0);
+        assert_eq!(structure.prize(4, 9), 0.0);
+
+        // heads up the two places left share the whole pool
+        let pool = (1..=2).map(|place| structure.prize(place, 2)).sum::<f64>();
+        assert!((pool - 2.0 * structure.buy_in).abs() < 1e-9);
+        assert!((structure.prize(1, 2) - 12.5).abs() < 1e-9);
+        assert_eq!(structure.prize(3, 2), 0.0);
+
+        let structure = Structure::multi_table(100);
+        assert_eq!(structure.payouts.len(), 15);
+        assert!((structure.payouts.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(structure.payouts[0] > structure.payouts[1]);
+    }
+
+    #[test]
+    fn sit_and_go() {
+        let tournament = Tournament {
+            structure: Structure::sit_and_go(),
+            seed: 50,
+        };
+        let mut players = field(6);
+        let mut rng = StdRng::seed_from_u64(50);
+        let standings = tournament.play(&mut players, &mut rng).unwrap();
+        let mut places = standings.places.clone();
+        places.sort_unstable();
+        assert_eq!(places, vec![1, 2, 3, 4, 5, 6]);
+        assert!((standings.prizes.iter().sum::<f64>() - 60.0).abs() < 1e-9);
+        assert!(standings.hands > 0);
+
+        let mut rng = StdRng::seed_from_u64(50);
+        assert_eq!(tournament.play(&mut field(6), &mut rng).unwrap(), standings);
+        assert!(tournament.play(&mut field(1), &mut rng).is_err());
+
+        // heads up the whole pool is paid out too
+        let standings = tournament.play(&mut field(2), &mut rng).unwrap();
+        assert!((standings.prizes.iter().sum::<f64>() - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn multi_table() {
+        let mut structure = Structure::multi_table(20);
+        structure.table_size = 6;
+        structure.levels = blind_schedule(5);
+        let tournament = Tournament {
+            structure,
+            seed: 50,
+        };
+        let mut players: Vec<Box<dyn Player>> = (0..20)
+            .map(|i| -> Box<dyn Player> { Box::new(RandomPlayer::new(i)) })
+            .collect();
+        let mut rng = StdRng::seed_from_u64(50);
+        let standings = tournament.play(&mut players, &mut rng).unwrap();
+        let mut places = standings.places.clone();
+        places.sort_unstable();
+        assert_eq!(places, (1..=20).collect::<Vec<_>>());
+        assert!((standings.prizes.iter().sum::<f64>() - 200.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn balancing() {
+        let players = field(14);
+        let seating = |players: &[usize]| {
+            let mut seating = Seating {
+                table: Table::new(1, 2, 0),
+                players: vec![],
+            };
+            for player in players.iter() {
+                seating.add(*player, "p", 100);
+            }
+            seating
+        };
+        // 14 players fit at two tables of 9
+        let mut tables = vec![
+            seating(&[0, 1, 2, 3, 4, 5, 6]),
+            seating(&[7, 8, 9, 10]),
+            seating(&[11, 12, 13]),
+        ];
+        tables[0].table.button = 6;
+        balance(&mut tables, 9, &players);
+        assert_eq!(tables.len(), 2);
+        assert_eq!(tables[0].players.len(), 7);
+        assert_eq!(tables[1].players.len(), 7);
+        assert_eq!(tables[0].table.seats.len(), 7);
+
+        // the next big blind, two seats after the button, moves
+        let mut tables = vec![seating(&[0, 1, 2, 3, 4, 5]), seating(&[6, 7, 8, 9])];
+        tables[0].table.button = 4;
+        balance(&mut tables, 6, &players);
+        assert_eq!(tables[1].players, vec![6, 7, 8, 9, 0]);
+        assert_eq!(tables[0].table.button, 3);
+        assert_eq!(tables[0].players[tables[0].table.button], 4);
+    }
+
+    #[test]
+    fn roi() {
+        let tournament = Tournament {
+            structure: Structure::sit_and_go(),
+            seed: 50,
+        };
+        let mut players = field(6);
+        let results = tournament.run(&mut players, 3).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].name, "tight aggressive");
+        assert_eq!(results[0].entries, 9);
+        assert_eq!(results[0].finishes.iter().sum::<usize>(), 9);
+        assert_eq!(results[0].finishes[0] + results[1].finishes[0], 3);
+        // the prize pool is paid out in full
+        let profit: f64 = results.iter().map(|r| r.roi() * r.entries as f64).sum();
+        assert!(profit.abs() < 1e-9);
+        assert!((results[0].distribution().iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        // three of the six entrants cash in every tournament
+        assert!((results[0].in_the_money(3) + results[1].in_the_money(3) - 1.0).abs() < 1e-9);
+        let (low, high) = results[0].confidence_interval(1.96);
+        assert!(low <= results[0].roi() && results[0].roi() <= high);
+    }
+}